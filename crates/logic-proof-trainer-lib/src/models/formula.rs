@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
 
 /// Type alias for shared ownership of formulas using Arc
@@ -321,6 +321,40 @@ impl Formula {
         }
     }
 
+    /// Count the nodes in the formula's syntax tree (atoms, ⊥ and connectives)
+    pub fn node_count(&self) -> usize {
+        match self {
+            Formula::Atom(_) | Formula::Contradiction => 1,
+            Formula::Not(inner) => 1 + inner.node_count(),
+            Formula::And(left, right)
+            | Formula::Or(left, right)
+            | Formula::Implies(left, right)
+            | Formula::Biconditional(left, right) => 1 + left.node_count() + right.node_count(),
+        }
+    }
+
+    /// Truth value under `assignment`, or None if an atom is unassigned
+    pub fn evaluate(&self, assignment: &BTreeMap<String, bool>) -> Option<bool> {
+        Some(match self {
+            Formula::Atom(name) => *assignment.get(name)?,
+            Formula::Contradiction => false,
+            Formula::Not(inner) => !inner.evaluate(assignment)?,
+            Formula::And(left, right)
+            | Formula::Or(left, right)
+            | Formula::Implies(left, right)
+            | Formula::Biconditional(left, right) => {
+                // Both sides first, so an unassigned atom is never skipped
+                let (l, r) = (left.evaluate(assignment)?, right.evaluate(assignment)?);
+                match self {
+                    Formula::And(..) => l && r,
+                    Formula::Or(..) => l || r,
+                    Formula::Implies(..) => !l || r,
+                    _ => l == r,
+                }
+            }
+        })
+    }
+
     /// Get the main connective as a string
     pub fn main_connective(&self) -> Option<&'static str> {
         match self {
@@ -917,4 +951,16 @@ mod tests {
         let f = Formula::parse("~~P").unwrap();
         assert_eq!(f.ascii_string_bracketed(), "~~P");
     }
+
+    #[test]
+    fn test_evaluate() {
+        let f = Formula::parse("(P -> Q) & ~R").unwrap();
+        let assignment: BTreeMap<String, bool> = [("P", true), ("Q", false), ("R", false)]
+            .iter().map(|(a, v)| (a.to_string(), *v)).collect();
+        assert_eq!(f.evaluate(&assignment), Some(false));
+        // P -> Q is already false, but R is still required
+        let mut partial = assignment.clone();
+        partial.remove("R");
+        assert_eq!(f.evaluate(&partial), None);
+    }
 }
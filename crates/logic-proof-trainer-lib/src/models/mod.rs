@@ -4,9 +4,11 @@ pub mod proof;
 pub mod scope;
 pub mod rules;
 pub mod statistics;
+pub mod tier_presets;
 
 pub use formula::*;
 pub use theorem::*;
 pub use proof::*;
 pub use scope::*;
 pub use statistics::*;
+pub use tier_presets::*;
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use crate::models::formula::Formula;
 
 /// Valid Equivalence Forms (9-18) from rules.md
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum EquivalenceRule {
     // 9. Double Negation (DN): p :: ~~p
@@ -388,12 +388,30 @@ impl EquivalenceRule {
             EquivalenceRule::Equivalence,       // 18. Equiv
         ]
     }
+
+    /// Look up a rule by abbreviation (`Dist`) or name (`distribution`,
+    /// `deMorgan`), ignoring case.
+    pub fn from_name(s: &str) -> Option<EquivalenceRule> {
+        let wanted: String = s.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_lowercase();
+        Self::all().into_iter().find(|rule| {
+            rule.abbreviation().to_lowercase() == wanted
+                || format!("{:?}", rule).to_lowercase() == wanted
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_from_name() {
+        assert_eq!(EquivalenceRule::from_name("Dist"), Some(EquivalenceRule::Distribution));
+        assert_eq!(EquivalenceRule::from_name("deMorgan"), Some(EquivalenceRule::DeMorgan));
+        assert_eq!(EquivalenceRule::from_name("double-negation"), Some(EquivalenceRule::DoubleNegation));
+        assert_eq!(EquivalenceRule::from_name("Absorption"), None);
+    }
+
     #[test]
     fn test_demorgan_and() {
         let formula = Formula::parse("~(P & Q)").unwrap();
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use std::collections::BTreeMap;
 use super::formula::Formula;
+use super::rules::equivalence::EquivalenceRule;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -37,6 +39,51 @@ pub struct DifficultySpec {
     /// None = auto (derived from difficulty_value >= 85), Some(bool) = explicit override.
     #[serde(default)]
     pub gnarly_combos: Option<bool>,
+    /// Size band the finished theorem should land in. Passes that overshoot
+    /// it are undone and single rewrites top up an undersized formula;
+    /// gated generation resamples anything still outside the band.
+    #[serde(default)]
+    pub target_size: Option<TargetSize>,
+    /// Relative weight of each rule when a rewrite is drawn; 0 excludes the
+    /// rule. Rules left out keep their default weight (1.0, or 0.2 for the
+    /// subtree-duplicating Distribution and Equivalence).
+    #[serde(default)]
+    pub rule_weights: Option<BTreeMap<EquivalenceRule, f64>>,
+    /// Rule chains for gnarly combos, replacing the built-in ones. Setting
+    /// them turns gnarly combos on unless `gnarly_combos` is explicitly false.
+    #[serde(default)]
+    pub combo_chains: Option<Vec<Vec<EquivalenceRule>>>,
+}
+
+/// What a `TargetSize` band measures, summed over premises and conclusion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeMetric {
+    /// AST nodes (`Formula::node_count`)
+    Nodes,
+    /// Characters of the bracketed ASCII rendering used in theorem sets
+    AsciiLength,
+}
+
+/// Inclusive `min..=max` band on a `SizeMetric`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetSize {
+    pub metric: SizeMetric,
+    pub min: u32,
+    pub max: u32,
+}
+
+impl TargetSize {
+    pub fn measure(&self, formulas: &[Formula]) -> usize {
+        match self.metric {
+            SizeMetric::Nodes => formulas.iter().map(|f| f.node_count()).sum(),
+            SizeMetric::AsciiLength => formulas.iter().map(|f| f.ascii_string_bracketed().len()).sum(),
+        }
+    }
+
+    pub fn contains(&self, size: usize) -> bool {
+        (self.min as usize..=self.max as usize).contains(&size)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -54,19 +101,34 @@ pub enum DifficultyTier {
     Mind,
 }
 
+/// Allowed ranges for DifficultySpec fields loaded from a presets file.
+/// `variables` is capped by the dynamic truth-table engine (2^20 rows).
+pub const VARIABLES_RANGE: (u8, u8) = (2, 20);
+pub const PASSES_RANGE: (u16, u16) = (1, 100);
+pub const TRANSFORMS_RANGE: (u16, u16) = (1, 100);
+pub const SUBSTITUTION_RANGE: (u16, u16) = (0, 10);
+pub const BRIDGE_ATOMS_RANGE: (u8, u8) = (0, 5);
+
+fn check_range<T: PartialOrd + std::fmt::Display>(field: &str, value: T, (min, max): (T, T)) -> Result<(), String> {
+    if value < min || value > max {
+        return Err(format!("{} = {} is outside {}-{}", field, value, min, max));
+    }
+    Ok(())
+}
+
 impl DifficultySpec {
     pub fn from_tier(tier: DifficultyTier) -> Self {
         match tier {
-            DifficultyTier::Baby      => Self { variables: 2, passes: 1,  transforms_per_pass: 2,  base_complexity: BaseComplexity::Simple,  substitution_depth: 0, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(0), gnarly_combos: Some(false) },
-            DifficultyTier::Easy      => Self { variables: 2, passes: 1,  transforms_per_pass: 2,  base_complexity: BaseComplexity::Simple,  substitution_depth: 0, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(0), gnarly_combos: Some(false) },
-            DifficultyTier::Medium    => Self { variables: 3, passes: 1,  transforms_per_pass: 5,  base_complexity: BaseComplexity::Simple,  substitution_depth: 0, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(0), gnarly_combos: Some(false) },
-            DifficultyTier::Hard      => Self { variables: 4, passes: 1,  transforms_per_pass: 10, base_complexity: BaseComplexity::Complex, substitution_depth: 0, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(0), gnarly_combos: Some(false) },
-            DifficultyTier::Expert    => Self { variables: 5, passes: 1,  transforms_per_pass: 15, base_complexity: BaseComplexity::Complex, substitution_depth: 2, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(0), gnarly_combos: Some(true) },
-            DifficultyTier::Nightmare => Self { variables: 5, passes: 2,  transforms_per_pass: 12, base_complexity: BaseComplexity::Complex, substitution_depth: 3, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(1), gnarly_combos: Some(true) },
-            DifficultyTier::Marathon  => Self { variables: 5, passes: 3,  transforms_per_pass: 15, base_complexity: BaseComplexity::Complex, substitution_depth: 4, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(1), gnarly_combos: Some(true) },
-            DifficultyTier::Absurd    => Self { variables: 6, passes: 5,  transforms_per_pass: 20, base_complexity: BaseComplexity::Complex, substitution_depth: 4, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(1), gnarly_combos: Some(true) },
-            DifficultyTier::Cosmic    => Self { variables: 7, passes: 10, transforms_per_pass: 20, base_complexity: BaseComplexity::Complex, substitution_depth: 4, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(2), gnarly_combos: Some(true) },
-            DifficultyTier::Mind      => Self { variables: 7, passes: 20, transforms_per_pass: 24, base_complexity: BaseComplexity::Complex, substitution_depth: 4, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(2), gnarly_combos: Some(true) },
+            DifficultyTier::Baby      => Self { variables: 2, passes: 1,  transforms_per_pass: 2,  base_complexity: BaseComplexity::Simple,  substitution_depth: 0, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(0), gnarly_combos: Some(false), target_size: None, rule_weights: None, combo_chains: None },
+            DifficultyTier::Easy      => Self { variables: 2, passes: 1,  transforms_per_pass: 2,  base_complexity: BaseComplexity::Simple,  substitution_depth: 0, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(0), gnarly_combos: Some(false), target_size: None, rule_weights: None, combo_chains: None },
+            DifficultyTier::Medium    => Self { variables: 3, passes: 1,  transforms_per_pass: 5,  base_complexity: BaseComplexity::Simple,  substitution_depth: 0, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(0), gnarly_combos: Some(false), target_size: None, rule_weights: None, combo_chains: None },
+            DifficultyTier::Hard      => Self { variables: 4, passes: 1,  transforms_per_pass: 10, base_complexity: BaseComplexity::Complex, substitution_depth: 0, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(0), gnarly_combos: Some(false), target_size: None, rule_weights: None, combo_chains: None },
+            DifficultyTier::Expert    => Self { variables: 5, passes: 1,  transforms_per_pass: 15, base_complexity: BaseComplexity::Complex, substitution_depth: 2, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(0), gnarly_combos: Some(true), target_size: None, rule_weights: None, combo_chains: None },
+            DifficultyTier::Nightmare => Self { variables: 5, passes: 2,  transforms_per_pass: 12, base_complexity: BaseComplexity::Complex, substitution_depth: 3, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(1), gnarly_combos: Some(true), target_size: None, rule_weights: None, combo_chains: None },
+            DifficultyTier::Marathon  => Self { variables: 5, passes: 3,  transforms_per_pass: 15, base_complexity: BaseComplexity::Complex, substitution_depth: 4, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(1), gnarly_combos: Some(true), target_size: None, rule_weights: None, combo_chains: None },
+            DifficultyTier::Absurd    => Self { variables: 6, passes: 5,  transforms_per_pass: 20, base_complexity: BaseComplexity::Complex, substitution_depth: 4, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(1), gnarly_combos: Some(true), target_size: None, rule_weights: None, combo_chains: None },
+            DifficultyTier::Cosmic    => Self { variables: 7, passes: 10, transforms_per_pass: 20, base_complexity: BaseComplexity::Complex, substitution_depth: 4, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(2), gnarly_combos: Some(true), target_size: None, rule_weights: None, combo_chains: None },
+            DifficultyTier::Mind      => Self { variables: 7, passes: 20, transforms_per_pass: 24, base_complexity: BaseComplexity::Complex, substitution_depth: 4, max_formula_nodes: None, max_formula_depth: None, bridge_atoms: Some(2), gnarly_combos: Some(true), target_size: None, rule_weights: None, combo_chains: None },
         }
     }
 
@@ -107,7 +169,65 @@ impl DifficultySpec {
             max_formula_depth: None,
             bridge_atoms: None,
             gnarly_combos: None,
+            target_size: None,
+            rule_weights: None,
+            combo_chains: None,
+        }
+    }
+
+    /// Check every field against the allowed ranges.
+    pub fn validate(&self) -> Result<(), String> {
+        check_range("variables", self.variables, VARIABLES_RANGE)?;
+        check_range("passes", self.passes, PASSES_RANGE)?;
+        check_range("transforms_per_pass", self.transforms_per_pass, TRANSFORMS_RANGE)?;
+        check_range("substitution_depth", self.substitution_depth, SUBSTITUTION_RANGE)?;
+        if let Some(bridge) = self.bridge_atoms {
+            check_range("bridge_atoms", bridge, BRIDGE_ATOMS_RANGE)?;
+        }
+        if self.max_formula_nodes == Some(0) {
+            return Err("max_formula_nodes must be at least 1".to_string());
+        }
+        if self.max_formula_depth == Some(0) {
+            return Err("max_formula_depth must be at least 1".to_string());
+        }
+        if let Some(weights) = &self.rule_weights {
+            if let Some((rule, weight)) = weights.iter().find(|(_, w)| !w.is_finite() || **w < 0.0) {
+                return Err(format!("rule_weights: {} = {} must be a non-negative number", rule.abbreviation(), weight));
+            }
+            // Rules left out keep a non-zero default weight
+            if EquivalenceRule::all().iter().all(|rule| weights.get(rule) == Some(&0.0)) {
+                return Err("rule_weights exclude every rule".to_string());
+            }
+        }
+        if let Some(chains) = &self.combo_chains {
+            if chains.is_empty() || chains.iter().any(|chain| chain.is_empty()) {
+                return Err("combo_chains must be non-empty lists of rules".to_string());
+            }
+        }
+        if let Some(target) = self.target_size {
+            if target.min == 0 || target.min > target.max {
+                return Err(format!("target_size {}-{} is not a valid band (need 1 <= min <= max)", target.min, target.max));
+            }
         }
+        Ok(())
+    }
+
+    /// A cheaper spec for retrying a theorem that ran over its generation
+    /// budget: one substitution level less (the main source of blow-up), then
+    /// one pass less, then half the transforms per pass. `None` once there is
+    /// nothing left to cut.
+    pub fn shrunk(&self) -> Option<Self> {
+        let mut spec = self.clone();
+        if spec.substitution_depth > 0 {
+            spec.substitution_depth -= 1;
+        } else if spec.passes > 1 {
+            spec.passes -= 1;
+        } else if spec.transforms_per_pass > 1 {
+            spec.transforms_per_pass /= 2;
+        } else {
+            return None;
+        }
+        Some(spec)
     }
 }
 
@@ -229,6 +349,10 @@ pub struct Theorem {
     pub theme: Option<Theme>,
     pub name: Option<String>,
     pub is_classic: bool,
+    /// Semantic class id of the base argument this theorem was obfuscated from
+    /// (see `services::semantic_class`). None when there is no separate base form.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_class: Option<String>,
 }
 
 /// Default difficulty value for backwards compatibility with serialized data
@@ -267,6 +391,7 @@ impl Theorem {
             theme,
             name,
             is_classic: false,
+            base_class: None,
         }
     }
 
@@ -287,6 +412,7 @@ impl Theorem {
             theme,
             name: None,
             is_classic: false,
+            base_class: None,
         }
     }
 
@@ -332,6 +458,7 @@ pub fn get_classic_theorems() -> Vec<Theorem> {
             name: Some("Modus Ponens".to_string()),
             tier: None,
             is_classic: true,
+            base_class: None,
         },
         // 2. Modus Tollens
         Theorem {
@@ -347,6 +474,7 @@ pub fn get_classic_theorems() -> Vec<Theorem> {
             name: Some("Modus Tollens".to_string()),
             tier: None,
             is_classic: true,
+            base_class: None,
         },
         // 3. Hypothetical Syllogism
         Theorem {
@@ -362,6 +490,7 @@ pub fn get_classic_theorems() -> Vec<Theorem> {
             name: Some("Hypothetical Syllogism".to_string()),
             tier: None,
             is_classic: true,
+            base_class: None,
         },
         // 4. Disjunctive Syllogism
         Theorem {
@@ -377,6 +506,7 @@ pub fn get_classic_theorems() -> Vec<Theorem> {
             name: Some("Disjunctive Syllogism".to_string()),
             tier: None,
             is_classic: true,
+            base_class: None,
         },
         // 5. Constructive Dilemma
         Theorem {
@@ -392,6 +522,7 @@ pub fn get_classic_theorems() -> Vec<Theorem> {
             name: Some("Constructive Dilemma".to_string()),
             tier: None,
             is_classic: true,
+            base_class: None,
         },
         // 6. Law of Excluded Middle (requires indirect proof)
         Theorem {
@@ -404,6 +535,7 @@ pub fn get_classic_theorems() -> Vec<Theorem> {
             name: Some("Law of Excluded Middle".to_string()),
             tier: None,
             is_classic: true,
+            base_class: None,
         },
         // 7. Double Negation Elimination
         Theorem {
@@ -416,6 +548,7 @@ pub fn get_classic_theorems() -> Vec<Theorem> {
             name: Some("Double Negation Elimination".to_string()),
             tier: None,
             is_classic: true,
+            base_class: None,
         },
         // 8. Contraposition
         Theorem {
@@ -428,6 +561,7 @@ pub fn get_classic_theorems() -> Vec<Theorem> {
             name: Some("Contraposition".to_string()),
             tier: None,
             is_classic: true,
+            base_class: None,
         },
         // 9. DeMorgan's Law (And to Or)
         Theorem {
@@ -440,6 +574,7 @@ pub fn get_classic_theorems() -> Vec<Theorem> {
             name: Some("De Morgan (And to Or)".to_string()),
             tier: None,
             is_classic: true,
+            base_class: None,
         },
         // 10. DeMorgan's Law (Or to And)
         Theorem {
@@ -452,6 +587,7 @@ pub fn get_classic_theorems() -> Vec<Theorem> {
             name: Some("De Morgan (Or to And)".to_string()),
             tier: None,
             is_classic: true,
+            base_class: None,
         },
         // 11. Material Implication
         Theorem {
@@ -464,6 +600,7 @@ pub fn get_classic_theorems() -> Vec<Theorem> {
             name: Some("Material Implication".to_string()),
             tier: None,
             is_classic: true,
+            base_class: None,
         },
         // 12. Exportation
         Theorem {
@@ -476,6 +613,7 @@ pub fn get_classic_theorems() -> Vec<Theorem> {
             name: Some("Exportation".to_string()),
             tier: None,
             is_classic: true,
+            base_class: None,
         },
         // 13. Pierce's Law (requires indirect proof)
         Theorem {
@@ -488,6 +626,7 @@ pub fn get_classic_theorems() -> Vec<Theorem> {
             name: Some("Peirce's Law".to_string()),
             tier: None,
             is_classic: true,
+            base_class: None,
         },
     ]
 }
@@ -510,4 +649,18 @@ mod tests {
         let theorem = &get_classic_theorems()[0];
         assert_eq!(theorem.display_string(), "P ⊃ Q, P ⊢ Q");
     }
+
+    #[test]
+    fn test_spec_shrinks_to_minimum() {
+        let mut spec = DifficultySpec::from_tier(DifficultyTier::Marathon);
+        let mut steps = 0;
+        while let Some(smaller) = spec.shrunk() {
+            spec = smaller;
+            steps += 1;
+        }
+        // substitution 4 -> 0, passes 3 -> 1, transforms 15 -> 7 -> 3 -> 1
+        assert_eq!(steps, 9);
+        assert_eq!((spec.substitution_depth, spec.passes, spec.transforms_per_pass), (0, 1, 1));
+        assert_eq!(spec.variables, 5);
+    }
 }
@@ -0,0 +1,280 @@
+use serde::de::{Deserializer, MapAccess, Visitor};
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+
+use super::theorem::{DifficultySpec, DifficultyTier};
+
+// ─── Tier presets ────────────────────────────────────────────────────────────
+
+/// A named generation preset. Built-in names map to a `DifficultyTier` so
+/// generated theorems keep their tier metadata; custom names have no tier.
+#[derive(Debug, Clone)]
+pub struct TierPreset {
+    pub name: String,
+    pub tier: Option<DifficultyTier>,
+    pub spec: DifficultySpec,
+}
+
+impl TierPreset {
+    /// Display label: the built-in tier label, or the name as written in the file.
+    pub fn label(&self) -> &str {
+        match self.tier {
+            Some(tier) => tier.label(),
+            None => &self.name,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TierPresetError {
+    Io(String),
+    Parse(String),
+    UnsupportedFormat(String),
+    InvalidName(String),
+    InvalidSpec { tier: String, reason: String },
+}
+
+impl fmt::Display for TierPresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TierPresetError::Io(e) => write!(f, "Failed to read tier presets: {}", e),
+            TierPresetError::Parse(e) => write!(f, "Failed to parse tier presets: {}", e),
+            TierPresetError::UnsupportedFormat(ext) => {
+                write!(f, "Unsupported tier presets format '{}'. Use .json or .toml.", ext)
+            }
+            TierPresetError::InvalidName(name) => {
+                write!(f, "Invalid tier name '{}': names must be non-empty and cannot contain ':' or ','", name)
+            }
+            TierPresetError::InvalidSpec { tier, reason } => write!(f, "Invalid tier '{}': {}", tier, reason),
+        }
+    }
+}
+
+/// Ordered set of tier presets, easiest first.
+#[derive(Debug, Clone)]
+pub struct TierPresets {
+    presets: Vec<TierPreset>,
+}
+
+/// `name -> spec` entries in document order (serde maps are otherwise unordered).
+struct OrderedSpecs(Vec<(String, DifficultySpec)>);
+
+impl<'de> Deserialize<'de> for OrderedSpecs {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct OrderedVisitor;
+
+        impl<'de> Visitor<'de> for OrderedVisitor {
+            type Value = OrderedSpecs;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of tier name to DifficultySpec")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry::<String, DifficultySpec>()? {
+                    entries.push(entry);
+                }
+                Ok(OrderedSpecs(entries))
+            }
+        }
+
+        deserializer.deserialize_map(OrderedVisitor)
+    }
+}
+
+impl TierPresets {
+    /// The built-in table from `DifficultySpec::from_tier`.
+    pub fn builtin() -> Self {
+        let presets = DifficultyTier::all().iter()
+            .map(|&tier| TierPreset {
+                name: tier.label().to_lowercase(),
+                tier: Some(tier),
+                spec: DifficultySpec::from_tier(tier),
+            })
+            .collect();
+        Self { presets }
+    }
+
+    /// Load presets from a `.json` or `.toml` file, layered over the built-in table.
+    pub fn load(path: &Path) -> Result<Self, TierPresetError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| TierPresetError::Io(format!("{}: {}", path.display(), e)))?;
+        match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+            Some("json") => Self::from_json_str(&text),
+            Some("toml") => Self::from_toml_str(&text),
+            other => Err(TierPresetError::UnsupportedFormat(other.unwrap_or("").to_string())),
+        }
+    }
+
+    /// Parse a JSON object of `name -> DifficultySpec` (the `tier-presets.json` format).
+    pub fn from_json_str(text: &str) -> Result<Self, TierPresetError> {
+        let specs: OrderedSpecs = serde_json::from_str(text)
+            .map_err(|e| TierPresetError::Parse(e.to_string()))?;
+        Self::builtin().merged(specs.0)
+    }
+
+    /// Parse a TOML document with one `[name]` table per tier.
+    pub fn from_toml_str(text: &str) -> Result<Self, TierPresetError> {
+        let specs: OrderedSpecs = toml::from_str(text)
+            .map_err(|e| TierPresetError::Parse(e.to_string()))?;
+        Self::builtin().merged(specs.0)
+    }
+
+    /// Override built-in tiers in place and append new tiers in file order.
+    fn merged(mut self, entries: Vec<(String, DifficultySpec)>) -> Result<Self, TierPresetError> {
+        for (name, spec) in entries {
+            if name.trim().is_empty() || name.contains(':') || name.contains(',') {
+                return Err(TierPresetError::InvalidName(name));
+            }
+            spec.validate()
+                .map_err(|reason| TierPresetError::InvalidSpec { tier: name.clone(), reason })?;
+            match self.presets.iter_mut().find(|p| p.name.eq_ignore_ascii_case(&name)) {
+                Some(existing) => existing.spec = spec,
+                None => self.presets.push(TierPreset {
+                    tier: DifficultyTier::from_str(&name),
+                    name,
+                    spec,
+                }),
+            }
+        }
+        Ok(self)
+    }
+
+    /// Look up a preset by name (case-insensitive).
+    pub fn get(&self, name: &str) -> Option<&TierPreset> {
+        self.presets.iter().find(|p| p.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// All presets, easiest first (built-in order, then custom tiers in file order).
+    pub fn all(&self) -> &[TierPreset] {
+        &self.presets
+    }
+
+    /// Slash-separated preset names, for error messages.
+    pub fn names(&self) -> String {
+        self.presets.iter().map(|p| p.name.as_str()).collect::<Vec<_>>().join("/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::rules::equivalence::EquivalenceRule;
+    use crate::models::theorem::{BaseComplexity, SizeMetric, TargetSize};
+
+    #[test]
+    fn test_builtin_matches_from_tier() {
+        let presets = TierPresets::builtin();
+        assert_eq!(presets.all().len(), DifficultyTier::all().len());
+        let easy = presets.get("Easy").unwrap();
+        assert_eq!(easy.tier, Some(DifficultyTier::Easy));
+        assert_eq!(easy.spec.variables, DifficultySpec::from_tier(DifficultyTier::Easy).variables);
+    }
+
+    #[test]
+    fn test_json_overrides_and_extends() {
+        let json = r#"{
+            "easy": { "variables": 3, "passes": 1, "transforms_per_pass": 5,
+                      "base_complexity": "simple", "substitution_depth": 0 },
+            "warmup": { "variables": 2, "passes": 1, "transforms_per_pass": 1,
+                        "base_complexity": "simple", "substitution_depth": 0 }
+        }"#;
+        let presets = TierPresets::from_json_str(json).unwrap();
+        assert_eq!(presets.get("easy").unwrap().spec.variables, 3);
+        let warmup = presets.get("warmup").unwrap();
+        assert_eq!(warmup.tier, None);
+        assert_eq!(warmup.label(), "warmup");
+        assert_eq!(presets.all().last().unwrap().name, "warmup");
+    }
+
+    #[test]
+    fn test_toml_preserves_file_order() {
+        let toml = r#"
+            [zeta]
+            variables = 4
+            passes = 2
+            transforms_per_pass = 8
+            base_complexity = "complex"
+            substitution_depth = 1
+
+            [alpha]
+            variables = 5
+            passes = 3
+            transforms_per_pass = 8
+            base_complexity = "complex"
+            substitution_depth = 2
+            bridge_atoms = 1
+        "#;
+        let presets = TierPresets::from_toml_str(toml).unwrap();
+        let names: Vec<&str> = presets.all().iter().rev().take(2).map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["alpha", "zeta"]);
+        assert_eq!(presets.get("alpha").unwrap().spec.base_complexity, BaseComplexity::Complex);
+    }
+
+    #[test]
+    fn test_out_of_range_rejected() {
+        let json = r#"{ "huge": { "variables": 30, "passes": 1, "transforms_per_pass": 5,
+                                   "base_complexity": "simple", "substitution_depth": 0 } }"#;
+        match TierPresets::from_json_str(json) {
+            Err(TierPresetError::InvalidSpec { tier, reason }) => {
+                assert_eq!(tier, "huge");
+                assert!(reason.contains("variables"));
+            }
+            other => panic!("expected InvalidSpec, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_target_size_parsed_and_checked() {
+        let toml = r#"
+            [sized]
+            variables = 4
+            passes = 1
+            transforms_per_pass = 5
+            base_complexity = "complex"
+            substitution_depth = 0
+            target_size = { metric = "ascii_length", min = 60, max = 100 }
+        "#;
+        let presets = TierPresets::from_toml_str(toml).unwrap();
+        let target = presets.get("sized").unwrap().spec.target_size.unwrap();
+        assert_eq!(target, TargetSize { metric: SizeMetric::AsciiLength, min: 60, max: 100 });
+
+        let json = r#"{ "bad": { "variables": 3, "passes": 1, "transforms_per_pass": 5,
+                                  "base_complexity": "simple", "substitution_depth": 0,
+                                  "target_size": { "metric": "nodes", "min": 80, "max": 40 } } }"#;
+        assert!(matches!(TierPresets::from_json_str(json), Err(TierPresetError::InvalidSpec { .. })));
+    }
+
+    #[test]
+    fn test_rule_weights_and_combo_chains_parsed_and_checked() {
+        let json = r#"{ "dist": { "variables": 3, "passes": 1, "transforms_per_pass": 5,
+                                   "base_complexity": "simple", "substitution_depth": 0,
+                                   "rule_weights": { "distribution": 3.0, "tautology": 0 },
+                                   "combo_chains": [["contraposition", "deMorgan"]] } }"#;
+        let spec = TierPresets::from_json_str(json).unwrap().get("dist").unwrap().spec.clone();
+        assert_eq!(spec.rule_weights.unwrap()[&EquivalenceRule::Distribution], 3.0);
+        assert_eq!(spec.combo_chains.unwrap(), vec![vec![EquivalenceRule::Contraposition, EquivalenceRule::DeMorgan]]);
+
+        let negative = json.replace("3.0", "-1");
+        assert!(matches!(TierPresets::from_json_str(&negative), Err(TierPresetError::InvalidSpec { .. })));
+        let empty_chain = json.replace(r#"["contraposition", "deMorgan"]"#, "[]");
+        assert!(matches!(TierPresets::from_json_str(&empty_chain), Err(TierPresetError::InvalidSpec { .. })));
+    }
+
+    #[test]
+    fn test_distribution_syntax_in_name_rejected() {
+        let json = r#"{ "a:b": { "variables": 2, "passes": 1, "transforms_per_pass": 1,
+                                  "base_complexity": "simple", "substitution_depth": 0 } }"#;
+        assert!(matches!(TierPresets::from_json_str(json), Err(TierPresetError::InvalidName(_))));
+    }
+
+    #[test]
+    fn test_repo_presets_file_loads() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../tier-presets.json");
+        let presets = TierPresets::load(&path).unwrap();
+        assert_eq!(presets.get("easy").unwrap().spec.variables, 3);
+        assert_eq!(presets.get("mind").unwrap().spec.passes, 50);
+    }
+}
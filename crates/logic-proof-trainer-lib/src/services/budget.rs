@@ -0,0 +1,187 @@
+//! Per-theorem time and memory budgets for spec generation.
+//!
+//! `max_formula_nodes` is only checked between passes, so a spec with deep
+//! substitutions, many passes or bridge atoms can spend a long time (or a lot
+//! of memory) inside a single pass before it fires. A `GenerationBudget` is
+//! checked cooperatively inside the rewrite loops and before substitutions
+//! are applied; `BudgetPolicy` decides what happens to a theorem that runs
+//! over.
+
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use crate::models::Formula;
+
+/// What to do with a theorem whose generation ran over its budget
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPolicy {
+    /// Retry with `DifficultySpec::shrunk` until it fits or cannot shrink
+    Retry,
+    /// Leave the theorem out of the set
+    Skip,
+    /// Stop generation with an error
+    #[default]
+    Fail,
+}
+
+impl std::str::FromStr for BudgetPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "retry" => Ok(Self::Retry),
+            "skip" => Ok(Self::Skip),
+            "fail" => Ok(Self::Fail),
+            _ => Err(format!("Unknown budget policy: '{}'. Use retry/skip/fail.", s)),
+        }
+    }
+}
+
+/// Limits for generating one theorem, quality-gate retries included.
+/// `None` means unlimited.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GenerationBudget {
+    /// Wall-clock limit in milliseconds
+    #[serde(default)]
+    pub max_millis: Option<u64>,
+    /// Limit on the estimated size of the formulas being built, in KiB
+    /// (see `estimated_bytes`)
+    #[serde(default)]
+    pub max_kilobytes: Option<u64>,
+    #[serde(default)]
+    pub policy: BudgetPolicy,
+}
+
+impl GenerationBudget {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_millis.is_none() && self.max_kilobytes.is_none()
+    }
+}
+
+/// Which limit a theorem ran over
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetExceeded {
+    Time { limit_millis: u64 },
+    Memory { kilobytes: u64, limit_kilobytes: u64 },
+}
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetExceeded::Time { limit_millis } => write!(f, "ran over the {} ms time budget", limit_millis),
+            BudgetExceeded::Memory { kilobytes, limit_kilobytes } => {
+                write!(f, "formulas reached ~{} KiB, over the {} KiB memory budget", kilobytes, limit_kilobytes)
+            }
+        }
+    }
+}
+
+/// Estimated heap size of `nodes` formula nodes: every node below the root
+/// is a boxed `Formula`. Atom names and temporary copies are not counted, so
+/// this is a lower bound on what generation allocates.
+pub fn estimated_bytes(nodes: usize) -> usize {
+    nodes.saturating_mul(std::mem::size_of::<Formula>())
+}
+
+/// A running budget. Checks are cheap enough to call once per rewrite; the
+/// first limit that trips is remembered, so every later check fails fast and
+/// callers unwinding through several loops can ask why.
+#[derive(Debug)]
+pub(crate) struct BudgetClock {
+    deadline: Option<(Instant, u64)>,
+    max_bytes: Option<usize>,
+    exceeded: Cell<Option<BudgetExceeded>>,
+}
+
+impl BudgetClock {
+    pub(crate) fn unlimited() -> Self {
+        Self { deadline: None, max_bytes: None, exceeded: Cell::new(None) }
+    }
+
+    /// Start `budget`'s clock now.
+    pub(crate) fn start(budget: &GenerationBudget) -> Self {
+        Self {
+            deadline: budget.max_millis.map(|ms| (Instant::now() + Duration::from_millis(ms), ms)),
+            max_bytes: budget.max_kilobytes.map(|kb| (kb as usize).saturating_mul(1024)),
+            exceeded: Cell::new(None),
+        }
+    }
+
+    /// Whether the budget is spent, given the formulas currently being built.
+    pub(crate) fn spent(&self, formulas: &[Formula]) -> bool {
+        if self.max_bytes.is_none() {
+            return self.spent_nodes(0);
+        }
+        self.spent_nodes(formulas.iter().map(|f| f.node_count()).sum())
+    }
+
+    /// `spent` for a known (or projected) node count.
+    pub(crate) fn spent_nodes(&self, nodes: usize) -> bool {
+        if self.exceeded.get().is_some() {
+            return true;
+        }
+        if let Some((deadline, limit_millis)) = self.deadline {
+            if Instant::now() >= deadline {
+                self.exceeded.set(Some(BudgetExceeded::Time { limit_millis }));
+                return true;
+            }
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            let bytes = estimated_bytes(nodes);
+            if bytes > max_bytes {
+                self.exceeded.set(Some(BudgetExceeded::Memory {
+                    kilobytes: bytes.div_ceil(1024) as u64,
+                    limit_kilobytes: (max_bytes / 1024) as u64,
+                }));
+                return true;
+            }
+        }
+        false
+    }
+
+    pub(crate) fn exceeded(&self) -> Option<BudgetExceeded> {
+        self.exceeded.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_never_trips() {
+        let clock = BudgetClock::start(&GenerationBudget::default());
+        assert!(!clock.spent_nodes(usize::MAX));
+        assert_eq!(clock.exceeded(), None);
+    }
+
+    #[test]
+    fn test_memory_limit_trips_and_sticks() {
+        let budget = GenerationBudget { max_kilobytes: Some(1), ..Default::default() };
+        let clock = BudgetClock::start(&budget);
+        let small = 1024 / std::mem::size_of::<Formula>();
+        assert!(!clock.spent_nodes(small));
+        assert!(clock.spent_nodes(small + 1));
+        assert!(matches!(clock.exceeded(), Some(BudgetExceeded::Memory { limit_kilobytes: 1, .. })));
+        // Remembered even when the next check would fit
+        assert!(clock.spent_nodes(0));
+    }
+
+    #[test]
+    fn test_time_limit_trips() {
+        let budget = GenerationBudget { max_millis: Some(0), ..Default::default() };
+        let clock = BudgetClock::start(&budget);
+        assert!(clock.spent(&[]));
+        assert_eq!(clock.exceeded(), Some(BudgetExceeded::Time { limit_millis: 0 }));
+    }
+
+    #[test]
+    fn test_policy_from_str() {
+        assert_eq!("Retry".parse(), Ok(BudgetPolicy::Retry));
+        assert_eq!("skip".parse(), Ok(BudgetPolicy::Skip));
+        assert_eq!("fail".parse(), Ok(BudgetPolicy::Fail));
+        assert!("abort".parse::<BudgetPolicy>().is_err());
+    }
+}
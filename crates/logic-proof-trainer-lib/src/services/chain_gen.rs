@@ -0,0 +1,395 @@
+//! Chain theorems: arguments whose proof is a chain of exactly k inference steps.
+//!
+//! The obfuscation generator grows theorems by rewriting a few small base
+//! forms, so the proof horizon barely moves with difficulty. A chain is built
+//! forward instead: every step applies MP, MT, DS, HS or CD to the formula
+//! derived so far and one or two fresh premises. Each link gets fresh atoms,
+//! so every premise is needed and the emitted proof uses each one once, with
+//! one derivation per step. Atom names and premises are shuffled, so the chain
+//! has to be traced rather than read off top to bottom.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::models::rules::InferenceRule;
+use crate::models::Formula;
+use super::obfuscate_gen::build_atom_pool;
+use super::proof_tree::{ProofNode, ProofTree};
+
+/// Allowed chain lengths (inference steps)
+pub const CHAIN_STEPS_RANGE: (usize, usize) = (1, 100);
+
+/// Rules a chain can be built from
+pub const CHAIN_RULES: [InferenceRule; 5] = [
+    InferenceRule::ModusPonens,
+    InferenceRule::ModusTollens,
+    InferenceRule::DisjunctiveSyllogism,
+    InferenceRule::HypotheticalSyllogism,
+    InferenceRule::ConstructiveDilemma,
+];
+
+/// Configuration for chain generation
+#[derive(Debug, Clone)]
+pub struct ChainConfig {
+    /// Inference steps in the proof
+    pub steps: usize,
+    /// Rules the steps are drawn from, a non-empty subset of `CHAIN_RULES`
+    pub rules: Vec<InferenceRule>,
+}
+
+impl ChainConfig {
+    /// A chain of `steps` steps drawing on every chain rule.
+    pub fn new(steps: usize) -> Self {
+        Self { steps, rules: CHAIN_RULES.to_vec() }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let (min, max) = CHAIN_STEPS_RANGE;
+        if !(min..=max).contains(&self.steps) {
+            return Err(format!("chain steps = {} is outside {}-{}", self.steps, min, max));
+        }
+        if self.rules.is_empty() {
+            return Err("chain rules must not be empty".to_string());
+        }
+        if let Some(rule) = self.rules.iter().find(|r| !CHAIN_RULES.contains(r)) {
+            return Err(format!("{} cannot link a chain; use MP, MT, DS, HS or CD", rule.abbreviation()));
+        }
+        Ok(())
+    }
+}
+
+/// A generated chain argument with its proof
+#[derive(Debug, Clone)]
+pub struct ChainArgument {
+    pub premises: Vec<Formula>,
+    pub conclusion: Formula,
+    pub proof: ProofTree,
+}
+
+/// Shape of the formula derived so far, which decides the rules that can
+/// extend the chain. Literals are atoms or negated atoms; a conditional has
+/// an atom antecedent, a disjunction an atom disjunct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    /// X
+    Positive,
+    /// ~X
+    Negative,
+    /// A > X
+    ConditionalPositive,
+    /// A > ~X
+    ConditionalNegative,
+    /// X v Y
+    DisjunctionPositive,
+    /// X v ~Y
+    DisjunctionNegative,
+}
+
+const ALL_SHAPES: [Shape; 6] = [
+    Shape::Positive,
+    Shape::Negative,
+    Shape::ConditionalPositive,
+    Shape::ConditionalNegative,
+    Shape::DisjunctionPositive,
+    Shape::DisjunctionNegative,
+];
+
+impl Shape {
+    /// Rules that apply to this shape, each with the shapes it can produce.
+    fn moves(self) -> Vec<(InferenceRule, Vec<Shape>)> {
+        use InferenceRule::*;
+        use Shape::*;
+        match self {
+            Positive => vec![(ModusPonens, ALL_SHAPES.to_vec())],
+            Negative => vec![
+                (ModusPonens, ALL_SHAPES.to_vec()),
+                (ModusTollens, vec![Negative]),
+                (DisjunctiveSyllogism, ALL_SHAPES.to_vec()),
+            ],
+            ConditionalPositive => vec![
+                (ModusPonens, vec![Positive]),
+                (ModusTollens, vec![Negative]),
+                (HypotheticalSyllogism, vec![ConditionalPositive, ConditionalNegative]),
+            ],
+            ConditionalNegative => vec![
+                (ModusPonens, vec![Negative]),
+                (HypotheticalSyllogism, vec![ConditionalPositive, ConditionalNegative]),
+            ],
+            DisjunctionPositive => vec![
+                (DisjunctiveSyllogism, vec![Positive]),
+                (ConstructiveDilemma, vec![DisjunctionPositive, DisjunctionNegative]),
+            ],
+            DisjunctionNegative => vec![
+                (DisjunctiveSyllogism, vec![Negative]),
+                (ConstructiveDilemma, vec![DisjunctionPositive, DisjunctionNegative]),
+            ],
+        }
+    }
+}
+
+/// Generates chain arguments for a `ChainConfig`
+pub struct ChainGenerator {
+    config: ChainConfig,
+    /// Shapes from which a chain can go on indefinitely with the configured rules
+    viable: Vec<Shape>,
+}
+
+impl ChainGenerator {
+    /// `config` must pass `ChainConfig::validate`.
+    pub fn new(config: ChainConfig) -> Self {
+        // Greatest fixed point: drop shapes until every remaining one has a
+        // configured rule leading back into the set
+        let mut viable = ALL_SHAPES.to_vec();
+        loop {
+            let before = viable.len();
+            let current = viable.clone();
+            viable.retain(|shape| {
+                shape.moves().iter().any(|(rule, results)| {
+                    config.rules.contains(rule) && results.iter().any(|r| current.contains(r))
+                })
+            });
+            if viable.len() == before {
+                break;
+            }
+        }
+        debug_assert!(!viable.is_empty(), "every chain rule can repeat itself");
+        Self { config, viable }
+    }
+
+    pub fn generate(&self, rng: &mut impl Rng) -> ChainArgument {
+        let mut builder = ChainBuilder::new(self.config.steps, rng);
+        let mut shape = *self.viable.choose(rng).expect("viable shapes");
+        let start = builder.fresh(shape, rng);
+        builder.premises.push(start.clone());
+        let mut node = ProofNode::premise(start);
+
+        for step in 0..self.config.steps {
+            let last = step + 1 == self.config.steps;
+            let moves: Vec<(InferenceRule, Vec<Shape>)> = shape.moves()
+                .into_iter()
+                .filter(|(rule, _)| self.config.rules.contains(rule))
+                .map(|(rule, results)| {
+                    let results = results.into_iter().filter(|r| last || self.viable.contains(r)).collect();
+                    (rule, results)
+                })
+                .filter(|(_, results): &(InferenceRule, Vec<Shape>)| !results.is_empty())
+                .collect();
+            let (rule, results) = moves.choose(rng).expect("viable shapes always have a move");
+            let next = *results.choose(rng).expect("moves are filtered to non-empty results");
+            node = builder.apply(*rule, node, next, rng);
+            shape = next;
+        }
+
+        let ChainBuilder { mut premises, .. } = builder;
+        premises.shuffle(rng);
+        let proof = ProofTree::new(node);
+        ChainArgument { premises, conclusion: proof.conclusion().clone(), proof }
+    }
+}
+
+/// Fresh atoms and the premises introduced so far
+struct ChainBuilder {
+    atoms: Vec<String>,
+    premises: Vec<Formula>,
+}
+
+impl ChainBuilder {
+    fn new(steps: usize, rng: &mut impl Rng) -> Self {
+        // Two atoms for the start and at most two per step (CD)
+        let mut atoms = atom_names(2 * steps + 2);
+        atoms.shuffle(rng);
+        Self { atoms, premises: Vec::new() }
+    }
+
+    fn atom(&mut self) -> Formula {
+        Formula::Atom(self.atoms.pop().expect("enough atoms for every step"))
+    }
+
+    /// A formula of `shape` over fresh atoms.
+    fn fresh(&mut self, shape: Shape, rng: &mut impl Rng) -> Formula {
+        match shape {
+            Shape::Positive => self.atom(),
+            Shape::Negative => not(self.atom()),
+            Shape::ConditionalPositive => implies(self.atom(), self.atom()),
+            Shape::ConditionalNegative => implies(self.atom(), not(self.atom())),
+            Shape::DisjunctionPositive => or(self.atom(), self.atom()),
+            Shape::DisjunctionNegative => {
+                let (x, y) = (self.atom(), not(self.atom()));
+                if rng.gen_bool(0.5) { or(x, y) } else { or(y, x) }
+            }
+        }
+    }
+
+    fn premise(&mut self, formula: Formula) -> ProofNode {
+        self.premises.push(formula.clone());
+        ProofNode::premise(formula)
+    }
+
+    /// Extend the chain ending in `node` by one `rule` step whose result has
+    /// shape `next`.
+    fn apply(&mut self, rule: InferenceRule, node: ProofNode, next: Shape, rng: &mut impl Rng) -> ProofNode {
+        let current = node.formula().clone();
+        let (result, children) = match (rule, &current) {
+            // X, X > R ⊢ R
+            (InferenceRule::ModusPonens, Formula::Atom(_) | Formula::Not(_)) => {
+                let result = self.fresh(next, rng);
+                let premise = self.premise(implies(current.clone(), result.clone()));
+                (result, vec![premise, node])
+            }
+            // A > L, A ⊢ L
+            (InferenceRule::ModusPonens, Formula::Implies(antecedent, consequent)) => {
+                let premise = self.premise((**antecedent).clone());
+                ((**consequent).clone(), vec![node, premise])
+            }
+            // ~X, Y > X ⊢ ~Y
+            (InferenceRule::ModusTollens, Formula::Not(x)) => {
+                let y = self.atom();
+                let premise = self.premise(implies(y.clone(), (**x).clone()));
+                (not(y), vec![premise, node])
+            }
+            // A > X, ~X ⊢ ~A
+            (InferenceRule::ModusTollens, Formula::Implies(antecedent, consequent)) => {
+                let premise = self.premise(not((**consequent).clone()));
+                (not((**antecedent).clone()), vec![node, premise])
+            }
+            // ~X, X v R ⊢ R
+            (InferenceRule::DisjunctiveSyllogism, Formula::Not(x)) => {
+                let result = self.fresh(next, rng);
+                let disjunction = if rng.gen_bool(0.5) {
+                    or((**x).clone(), result.clone())
+                } else {
+                    or(result.clone(), (**x).clone())
+                };
+                let premise = self.premise(disjunction);
+                (result, vec![premise, node])
+            }
+            // X v L, ~X ⊢ L (X is the atom disjunct)
+            (InferenceRule::DisjunctiveSyllogism, Formula::Or(left, right)) => {
+                let left_is_atom = matches!(**left, Formula::Atom(_));
+                let right_is_atom = matches!(**right, Formula::Atom(_));
+                let eliminate_left = left_is_atom && (!right_is_atom || rng.gen_bool(0.5));
+                let (gone, kept) = if eliminate_left { (left, right) } else { (right, left) };
+                let premise = self.premise(not((**gone).clone()));
+                ((**kept).clone(), vec![node, premise])
+            }
+            // A > L, L > M ⊢ A > M
+            (InferenceRule::HypotheticalSyllogism, Formula::Implies(antecedent, consequent)) => {
+                let m = match next {
+                    Shape::ConditionalNegative => not(self.atom()),
+                    _ => self.atom(),
+                };
+                let premise = self.premise(implies((**consequent).clone(), m.clone()));
+                (implies((**antecedent).clone(), m), vec![node, premise])
+            }
+            // P v Q, P > U, Q > W ⊢ U v W
+            (InferenceRule::ConstructiveDilemma, Formula::Or(left, right)) => {
+                let (mut u, mut w) = (self.atom(), self.atom());
+                if next == Shape::DisjunctionNegative {
+                    if rng.gen_bool(0.5) { u = not(u) } else { w = not(w) }
+                }
+                let left_premise = self.premise(implies((**left).clone(), u.clone()));
+                let right_premise = self.premise(implies((**right).clone(), w.clone()));
+                (or(u, w), vec![node, left_premise, right_premise])
+            }
+            _ => unreachable!("{} does not apply to {}", rule.abbreviation(), current.display_string()),
+        };
+        ProofNode::derivation(result, rule.abbreviation(), children, None)
+    }
+}
+
+/// `n` distinct atom names: the standard pool, then the same letters numbered.
+fn atom_names(n: usize) -> Vec<String> {
+    let letters = build_atom_pool(26);
+    (0..n)
+        .map(|i| match i / letters.len() {
+            0 => letters[i].clone(),
+            round => format!("{}{}", letters[i % letters.len()], round),
+        })
+        .collect()
+}
+
+fn not(f: Formula) -> Formula {
+    Formula::Not(Box::new(f))
+}
+
+fn implies(a: Formula, b: Formula) -> Formula {
+    Formula::Implies(Box::new(a), Box::new(b))
+}
+
+fn or(a: Formula, b: Formula) -> Formula {
+    Formula::Or(Box::new(a), Box::new(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::theorem::{Difficulty, Theorem};
+    use crate::services::truth_table::validate_theorem_dynamic;
+    use crate::services::verifier::ProofVerifier;
+
+    /// Verify the chain's proof line by line and check its length.
+    fn assert_chain(chain: &ChainArgument, steps: usize) {
+        assert_eq!(chain.proof.fragment_count, steps);
+        let theorem = Theorem::new(chain.premises.clone(), chain.conclusion.clone(), Difficulty::Medium, None, None);
+        let mut proof = chain.proof.to_proof(theorem).unwrap();
+        ProofVerifier::verify_proof(&mut proof);
+        assert!(proof.lines.iter().all(|l| l.is_valid), "{}", chain.proof.pretty_print());
+        assert!(proof.check_complete());
+        assert_eq!(proof.lines.len(), chain.premises.len() + steps);
+    }
+
+    #[test]
+    fn test_chains_verify_and_need_every_premise() {
+        let mut rng = rand::thread_rng();
+        for steps in 1..=6 {
+            let generator = ChainGenerator::new(ChainConfig::new(steps));
+            for _ in 0..10 {
+                let chain = generator.generate(&mut rng);
+                assert_chain(&chain, steps);
+                assert!(validate_theorem_dynamic(&chain.premises, &chain.conclusion).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_single_rule_chains() {
+        let mut rng = rand::thread_rng();
+        for rule in CHAIN_RULES {
+            let config = ChainConfig { steps: 8, rules: vec![rule] };
+            let chain = ChainGenerator::new(config).generate(&mut rng);
+            assert_chain(&chain, 8);
+            let mut rules = Vec::new();
+            collect_rules(&chain.proof.root, &mut rules);
+            assert!(rules.iter().all(|r| r == rule.abbreviation()), "{:?} in a {} chain", rules, rule.abbreviation());
+        }
+    }
+
+    #[test]
+    fn test_long_chain_round_trips() {
+        let mut rng = rand::thread_rng();
+        let chain = ChainGenerator::new(ChainConfig::new(100)).generate(&mut rng);
+        assert_chain(&chain, 100);
+        for premise in &chain.premises {
+            assert_eq!(&Formula::parse(&premise.ascii_string_bracketed()).unwrap(), premise);
+        }
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(ChainConfig::new(1).validate().is_ok());
+        assert!(ChainConfig::new(0).validate().is_err());
+        assert!(ChainConfig::new(101).validate().is_err());
+        assert!(ChainConfig { steps: 3, rules: vec![] }.validate().is_err());
+        let simp = ChainConfig { steps: 3, rules: vec![InferenceRule::Simplification] };
+        assert!(simp.validate().unwrap_err().contains("Simp"));
+    }
+
+    fn collect_rules(node: &ProofNode, rules: &mut Vec<String>) {
+        if let Some(rule) = node.rule_name() {
+            rules.push(rule.to_string());
+        }
+        for child in node.children() {
+            collect_rules(child, rules);
+        }
+    }
+}
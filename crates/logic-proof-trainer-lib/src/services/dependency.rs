@@ -1,5 +1,7 @@
 use std::collections::{HashMap, HashSet};
 
+use crate::models::Proof;
+
 /// Tracks dependencies between proof lines for cascading invalidation
 #[derive(Debug, Clone, Default)]
 pub struct DependencyTracker {
@@ -14,6 +16,16 @@ impl DependencyTracker {
         Self::default()
     }
 
+    /// Build the citation graph of a proof: each line depends on the lines
+    /// its justification cites
+    pub fn from_proof(proof: &Proof) -> Self {
+        let mut tracker = Self::new();
+        for line in &proof.lines {
+            tracker.add_dependencies(line.line_number, &line.justification.referenced_lines());
+        }
+        tracker
+    }
+
     /// Add a dependency: `line` depends on `depends_on`
     pub fn add_dependency(&mut self, line: usize, depends_on: usize) {
         self.dependencies
@@ -77,6 +89,24 @@ impl DependencyTracker {
         result
     }
 
+    /// Get all lines the given line depends on (transitively)
+    pub fn all_dependencies(&self, line: usize) -> HashSet<usize> {
+        let mut result = HashSet::new();
+        let mut to_visit = vec![line];
+
+        while let Some(current) = to_visit.pop() {
+            if let Some(deps) = self.dependencies.get(&current) {
+                for &dep in deps {
+                    if result.insert(dep) {
+                        to_visit.push(dep);
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
     /// Get all lines affected by invalidating the given line (cascade)
     pub fn cascade_invalidation(&self, line: usize) -> Vec<usize> {
         let mut affected: Vec<usize> = self.all_dependents(line).into_iter().collect();
@@ -220,6 +250,18 @@ mod tests {
         assert!(affected.contains(&5));
     }
 
+    #[test]
+    fn test_all_dependencies() {
+        let mut tracker = DependencyTracker::new();
+        tracker.add_dependencies(3, &[1, 2]);
+        tracker.add_dependency(5, 3);
+        tracker.add_dependency(6, 4);
+
+        let deps = tracker.all_dependencies(5);
+        assert_eq!(deps, HashSet::from([1, 2, 3]));
+        assert!(tracker.all_dependencies(1).is_empty());
+    }
+
     #[test]
     fn test_no_cycle() {
         let mut tracker = DependencyTracker::new();
@@ -0,0 +1,165 @@
+//! DIMACS export: the negated conjecture of a theorem in CNF for SAT
+//! solvers.
+//!
+//! The premises and the negated conclusion are Tseitin-encoded: each
+//! compound subformula gets a variable defined by clauses equivalent to its
+//! connective, so the CNF grows linearly with the formulas. It is
+//! unsatisfiable exactly when the theorem is valid. Atoms are variables
+//! 1..n in name order; the Tseitin variables follow.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::models::{Formula, Theorem};
+
+/// A CNF formula
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cnf {
+    pub num_vars: usize,
+    /// Clauses of non-zero DIMACS literals
+    pub clauses: Vec<Vec<i64>>,
+    /// Variable of each atom
+    pub atoms: BTreeMap<String, usize>,
+}
+
+impl Cnf {
+    /// DIMACS text: `comments` as `c` lines, the problem line, the clauses
+    pub fn to_dimacs(&self, comments: &[String]) -> String {
+        let mut lines: Vec<String> = comments.iter().map(|c| format!("c {}", c)).collect();
+        lines.push(format!("p cnf {} {}", self.num_vars, self.clauses.len()));
+        for clause in &self.clauses {
+            let literals: Vec<String> = clause.iter().map(|l| l.to_string()).collect();
+            lines.push(format!("{} 0", literals.join(" ")));
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+/// Tseitin encoding of `formulas` taken together: satisfiable exactly when
+/// some assignment makes all of them true, by a model that agrees on the
+/// atom variables.
+pub fn tseitin_cnf(formulas: &[Formula]) -> Cnf {
+    let atoms: BTreeMap<String, usize> = formulas.iter()
+        .flat_map(|f| f.atoms())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .enumerate()
+        .map(|(i, atom)| (atom, i + 1))
+        .collect();
+    let mut encoder = Encoder { num_vars: atoms.len(), clauses: Vec::new(), defined: HashMap::new(), atoms: &atoms };
+    for formula in formulas {
+        let literal = encoder.literal(formula);
+        encoder.clauses.push(vec![literal]);
+    }
+    let Encoder { num_vars, clauses, .. } = encoder;
+    Cnf { num_vars, clauses, atoms }
+}
+
+/// CNF of the premises of `theorem` with its conclusion negated
+pub fn negated_conjecture_cnf(theorem: &Theorem) -> Cnf {
+    let mut formulas = theorem.premises.clone();
+    formulas.push(theorem.conclusion.negate());
+    tseitin_cnf(&formulas)
+}
+
+struct Encoder<'a> {
+    num_vars: usize,
+    clauses: Vec<Vec<i64>>,
+    /// Variable of each compound subformula already encoded
+    defined: HashMap<&'a Formula, i64>,
+    atoms: &'a BTreeMap<String, usize>,
+}
+
+impl<'a> Encoder<'a> {
+    /// A literal equivalent to `formula`, defining variables as needed.
+    /// Negation flips the literal rather than adding a variable.
+    fn literal(&mut self, formula: &'a Formula) -> i64 {
+        match formula {
+            Formula::Atom(name) => self.atoms[name] as i64,
+            Formula::Not(inner) => -self.literal(inner),
+            _ => {
+                if let Some(&var) = self.defined.get(formula) {
+                    return var;
+                }
+                let parts = match formula {
+                    Formula::And(l, r) | Formula::Or(l, r) | Formula::Implies(l, r) | Formula::Biconditional(l, r) => {
+                        Some((self.literal(l), self.literal(r)))
+                    }
+                    _ => None,
+                };
+                self.num_vars += 1;
+                let v = self.num_vars as i64;
+                let definition = match (formula, parts) {
+                    (Formula::And(..), Some((a, b))) => vec![vec![-v, a], vec![-v, b], vec![v, -a, -b]],
+                    (Formula::Or(..), Some((a, b))) => vec![vec![-v, a, b], vec![v, -a], vec![v, -b]],
+                    (Formula::Implies(..), Some((a, b))) => vec![vec![-v, -a, b], vec![v, a], vec![v, -b]],
+                    (Formula::Biconditional(..), Some((a, b))) => {
+                        vec![vec![-v, -a, b], vec![-v, a, -b], vec![v, a, b], vec![v, -a, -b]]
+                    }
+                    // ⊥: a variable forced false
+                    _ => vec![vec![-v]],
+                };
+                self.clauses.extend(definition);
+                self.defined.insert(formula, v);
+                v
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Difficulty;
+
+    fn parse(s: &str) -> Formula {
+        Formula::parse(s).unwrap()
+    }
+
+    fn theorem(premises: &[&str], conclusion: &str) -> Theorem {
+        Theorem::new(premises.iter().map(|p| parse(p)).collect(), parse(conclusion), Difficulty::Easy, None, None)
+    }
+
+    /// Brute-force satisfiability over every assignment
+    fn satisfiable(cnf: &Cnf) -> bool {
+        (0..1u64 << cnf.num_vars).any(|bits| {
+            cnf.clauses.iter().all(|clause| {
+                clause.iter().any(|&l| ((bits >> (l.unsigned_abs() - 1)) & 1 == 1) == (l > 0))
+            })
+        })
+    }
+
+    #[test]
+    fn test_negated_conjecture_unsat_iff_valid() {
+        let valid = [
+            (vec!["P -> Q", "P"], "Q"),
+            (vec!["P | Q", "~P"], "Q"),
+            (vec!["P <-> Q", "~Q"], "~P"),
+            (vec!["P & ~P"], "R"),
+            (vec![], "P -> (Q -> P)"),
+            (vec!["P"], "~#"),
+        ];
+        for (premises, conclusion) in valid {
+            assert!(!satisfiable(&negated_conjecture_cnf(&theorem(&premises, conclusion))), "{}", conclusion);
+        }
+        let invalid = [(vec!["P -> Q", "Q"], "P"), (vec!["P | Q"], "P & Q"), (vec!["P"], "#")];
+        for (premises, conclusion) in invalid {
+            assert!(satisfiable(&negated_conjecture_cnf(&theorem(&premises, conclusion))), "{}", conclusion);
+        }
+    }
+
+    #[test]
+    fn test_shared_subformulas_get_one_variable() {
+        let cnf = tseitin_cnf(&[parse("(P & Q) -> (P & Q)"), parse("~(P & Q)")]);
+        assert_eq!(cnf.atoms, BTreeMap::from([("P".to_string(), 1), ("Q".to_string(), 2)]));
+        // P & Q and the conditional
+        assert_eq!(cnf.num_vars, 4);
+        assert_eq!(cnf.clauses.last(), Some(&vec![-3]));
+    }
+
+    #[test]
+    fn test_dimacs_text() {
+        let cnf = tseitin_cnf(&[parse("P | ~Q")]);
+        let text = cnf.to_dimacs(&["easy-1".to_string()]);
+        assert_eq!(text, "c easy-1\np cnf 3 4\n-3 1 -2 0\n3 -1 0\n3 2 0\n3 0\n");
+    }
+}
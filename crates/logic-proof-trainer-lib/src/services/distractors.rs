@@ -0,0 +1,288 @@
+//! Distractor premises for relevance testing.
+//!
+//! `validate_theorem_dynamic` rejects arguments whose premises are not all
+//! needed (`DegenerateProofError::UnnecessaryPremise`). Distractors add such
+//! premises on purpose: extra premises, recombined from the argument's own
+//! subformulas so they look like the real ones, that are consistent with the
+//! real premises but play no part in the entailment.
+//! Comparing which premises a proof cites against the essential ones shows
+//! whether a model spends lines on irrelevant information.
+
+use rand::Rng;
+use std::collections::BTreeSet;
+
+use crate::models::Formula;
+use super::obfuscate_gen::build_atom_pool;
+use super::truth_table::{compute_truth_table_over, DynTruthTable};
+
+/// Candidates drawn per requested distractor before giving up.
+pub const MAX_DISTRACTOR_ATTEMPTS: usize = 200;
+
+/// Most atoms a distractor search builds truth tables over: arguments with
+/// more are refused, and fresh atoms are added only up to this many
+pub const MAX_DISTRACTOR_ATOMS: usize = 12;
+
+/// Premises with distractors mixed in. `essential` lists the indices of the
+/// original premises the entailment needs, in order; every other premise is
+/// irrelevant to it.
+#[derive(Debug, Clone)]
+pub struct WithDistractors {
+    pub premises: Vec<Formula>,
+    pub essential: Vec<usize>,
+}
+
+/// Why distractors could not be added
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DistractorError {
+    /// The argument has more atoms than the truth-table search handles
+    TooManyAtoms { atoms: usize },
+    /// Fewer acceptable distractors were found than requested
+    NotEnough { found: usize, wanted: usize },
+}
+
+impl std::fmt::Display for DistractorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DistractorError::TooManyAtoms { atoms } => write!(
+                f,
+                "the argument has {} atoms; distractors need at most {}",
+                atoms, MAX_DISTRACTOR_ATOMS
+            ),
+            DistractorError::NotEnough { found, wanted } => write!(
+                f,
+                "found only {} of {} distractor premises, even with fresh atoms",
+                found, wanted
+            ),
+        }
+    }
+}
+
+/// Add `count` distractor premises to a valid argument, at random positions.
+///
+/// A distractor is not a tautology, is not equivalent to any other premise or
+/// to the conclusion or its negation, and keeps the premises consistent.
+/// Every premise the argument needed stays needed, so no distractor can stand
+/// in for a real premise. Distractors use the argument's own atoms while
+/// those leave room; small arguments (two atoms, say) admit only a handful of
+/// such formulas, so after `MAX_DISTRACTOR_ATTEMPTS` failures a fresh atom is
+/// brought in. With no premises (tautology format) every premise of the
+/// result is a distractor.
+pub fn add_distractors(
+    premises: &[Formula],
+    conclusion: &Formula,
+    count: usize,
+    rng: &mut impl Rng,
+) -> Result<WithDistractors, DistractorError> {
+    let mut atoms: Vec<String> = premises.iter()
+        .chain(std::iter::once(conclusion))
+        .flat_map(|f| f.atoms())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    if atoms.len() > MAX_DISTRACTOR_ATOMS {
+        return Err(DistractorError::TooManyAtoms { atoms: atoms.len() });
+    }
+    let essential = essential_premises(premises, conclusion, &atoms);
+    let pool = subformula_pool(premises, conclusion);
+
+    let mut all = premises.to_vec();
+    let mut fresh_atoms = build_atom_pool(MAX_DISTRACTOR_ATOMS as u8)
+        .into_iter()
+        .filter(|a| !atoms.contains(a))
+        .collect::<Vec<_>>()
+        .into_iter();
+    while all.len() < premises.len() + count {
+        match find_distractor(&all, conclusion, &essential, &pool, &atoms, rng) {
+            Some(distractor) => all.push(distractor),
+            None => match fresh_atoms.next() {
+                Some(atom) if atoms.len() < MAX_DISTRACTOR_ATOMS => atoms.push(atom),
+                _ => return Err(DistractorError::NotEnough { found: all.len() - premises.len(), wanted: count }),
+            },
+        }
+    }
+
+    // Mix the distractors in, keeping the real premises in their order
+    let mut mixed: Vec<(Formula, Option<usize>)> = premises.iter()
+        .cloned()
+        .enumerate()
+        .map(|(i, p)| (p, Some(i)))
+        .collect();
+    for distractor in all.drain(premises.len()..) {
+        let at = rng.gen_range(0..=mixed.len());
+        mixed.insert(at, (distractor, None));
+    }
+    let essential = mixed.iter()
+        .enumerate()
+        .filter(|(_, (_, original))| original.is_some_and(|i| essential.contains(&i)))
+        .map(|(index, _)| index)
+        .collect();
+    Ok(WithDistractors { premises: mixed.into_iter().map(|(p, _)| p).collect(), essential })
+}
+
+/// Indices of the premises whose removal breaks the entailment.
+fn essential_premises(premises: &[Formula], conclusion: &Formula, atoms: &[String]) -> Vec<usize> {
+    let tables: Vec<DynTruthTable> = premises.iter().map(|p| compute_truth_table_over(p, atoms)).collect();
+    let not_conclusion = compute_truth_table_over(conclusion, atoms).not();
+    (0..premises.len())
+        .filter(|&i| !entails_without(&tables, i, None, &not_conclusion, atoms.len()))
+        .collect()
+}
+
+/// Whether the premises other than `skip`, plus `extra`, entail the
+/// conclusion (given as its negation's table).
+fn entails_without(
+    tables: &[DynTruthTable],
+    skip: usize,
+    extra: Option<&DynTruthTable>,
+    not_conclusion: &DynTruthTable,
+    num_atoms: usize,
+) -> bool {
+    tables.iter()
+        .enumerate()
+        .filter(|(j, _)| *j != skip)
+        .map(|(_, t)| t)
+        .chain(extra)
+        .fold(DynTruthTable::tautology(num_atoms.max(1) as u8), |acc, t| acc.and(t))
+        .and(not_conclusion)
+        .is_contradiction()
+}
+
+/// Draw up to `MAX_DISTRACTOR_ATTEMPTS` candidates over `atoms` and return
+/// the first acceptable one. `premises` holds the original premises followed
+/// by the distractors accepted so far.
+fn find_distractor(
+    premises: &[Formula],
+    conclusion: &Formula,
+    essential: &[usize],
+    pool: &[Formula],
+    atoms: &[String],
+    rng: &mut impl Rng,
+) -> Option<Formula> {
+    let table = |f: &Formula| compute_truth_table_over(f, atoms);
+    let tables: Vec<DynTruthTable> = premises.iter().map(table).collect();
+    let conclusion_tt = table(conclusion);
+    let not_conclusion = conclusion_tt.not();
+    let together = tables.iter()
+        .fold(DynTruthTable::tautology(atoms.len().max(1) as u8), |acc, t| acc.and(t));
+
+    (0..MAX_DISTRACTOR_ATTEMPTS)
+        .map(|_| random_distractor(pool, atoms, rng))
+        .find(|candidate| {
+            let candidate_tt = table(candidate);
+            !candidate_tt.is_tautology()
+                && !candidate_tt.eq(&conclusion_tt)
+                && !candidate_tt.eq(&not_conclusion)
+                && !tables.iter().any(|t| t.eq(&candidate_tt))
+                && !together.and(&candidate_tt).is_contradiction()
+                && essential.iter().all(|&i| {
+                    !entails_without(&tables, i, Some(&candidate_tt), &not_conclusion, atoms.len())
+                })
+        })
+}
+
+/// Compound subformulas of the argument to recombine, between a quarter and
+/// a half of the median formula size (at most 3 nodes for small arguments),
+/// so that two of them joined are about as large as a real premise.
+fn subformula_pool(premises: &[Formula], conclusion: &Formula) -> Vec<Formula> {
+    let mut sizes: Vec<usize> = premises.iter().chain(std::iter::once(conclusion)).map(|f| f.node_count()).collect();
+    sizes.sort_unstable();
+    let max = (sizes[sizes.len() / 2] / 2).max(3);
+    let min = (max / 2).max(2);
+    let mut pool: Vec<Formula> = Vec::new();
+    for formula in premises.iter().chain(std::iter::once(conclusion)) {
+        for (_, sub) in formula.subformulas_with_paths() {
+            let nodes = sub.node_count();
+            if (min..=max).contains(&nodes) && !pool.contains(sub) {
+                pool.push(sub.clone());
+            }
+        }
+    }
+    pool
+}
+
+/// Join two random parts, each a pooled subformula or a (possibly negated)
+/// atom, with a random binary connective.
+fn random_distractor(pool: &[Formula], atoms: &[String], rng: &mut impl Rng) -> Formula {
+    let part = |rng: &mut dyn rand::RngCore| {
+        if !pool.is_empty() && rng.gen_bool(0.75) {
+            return pool[rng.gen_range(0..pool.len())].clone();
+        }
+        let atom = Formula::Atom(atoms[rng.gen_range(0..atoms.len())].clone());
+        if rng.gen_bool(0.3) { Formula::Not(Box::new(atom)) } else { atom }
+    };
+    let left = Box::new(part(rng));
+    let right = Box::new(part(rng));
+    match rng.gen_range(0..4) {
+        0 => Formula::And(left, right),
+        1 => Formula::Or(left, right),
+        2 => Formula::Implies(left, right),
+        _ => Formula::Biconditional(left, right),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::truth_table::validate_theorem_dynamic;
+
+    fn parse(s: &str) -> Formula {
+        Formula::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_distractors_are_irrelevant() {
+        let mut rng = rand::thread_rng();
+        let premises = vec![parse("P -> Q"), parse("Q -> R"), parse("P")];
+        let conclusion = parse("R");
+        for _ in 0..20 {
+            let mixed = add_distractors(&premises, &conclusion, 2, &mut rng).unwrap();
+            assert_eq!(mixed.premises.len(), 5);
+            assert_eq!(mixed.essential.len(), 3);
+
+            // The essential premises are the originals, in order, and still valid
+            let essential: Vec<Formula> = mixed.essential.iter().map(|&i| mixed.premises[i].clone()).collect();
+            assert_eq!(essential, premises);
+            assert!(validate_theorem_dynamic(&essential, &conclusion).is_ok());
+
+            // Each distractor can be dropped, no essential premise can
+            let atoms: Vec<String> = mixed.premises.iter().flat_map(|p| p.atoms()).collect::<BTreeSet<_>>().into_iter().collect();
+            let all: Vec<DynTruthTable> = mixed.premises.iter().map(|p| compute_truth_table_over(p, &atoms)).collect();
+            let without = |skip: usize| all.iter().enumerate().filter(|(i, _)| *i != skip)
+                .fold(DynTruthTable::tautology(atoms.len() as u8), |acc, (_, t)| acc.and(t));
+            let goal = compute_truth_table_over(&conclusion, &atoms).not();
+            assert!(!without(usize::MAX).is_contradiction(), "premises must stay consistent");
+            for i in 0..mixed.premises.len() {
+                let still_entails = without(i).and(&goal).is_contradiction();
+                assert_eq!(still_entails, !mixed.essential.contains(&i));
+            }
+        }
+    }
+
+    #[test]
+    fn test_distractors_for_tautology() {
+        let mut rng = rand::thread_rng();
+        let mixed = add_distractors(&[], &parse("(P & Q) -> P"), 1, &mut rng).unwrap();
+        assert_eq!(mixed.premises.len(), 1);
+        assert!(mixed.essential.is_empty());
+    }
+
+    #[test]
+    fn test_distractors_widen_atoms() {
+        // One atom leaves no consistent, non-duplicate, non-tautological
+        // candidate, so the distractor must bring in a fresh atom
+        let mut rng = rand::thread_rng();
+        let mixed = add_distractors(&[parse("P")], &parse("~~P"), 1, &mut rng).unwrap();
+        assert_eq!(mixed.premises.len(), 2);
+        let distractor = &mixed.premises[1 - mixed.essential[0]];
+        assert!(distractor.atoms().iter().any(|a| a != "P"));
+    }
+
+    #[test]
+    fn test_distractors_refuse_wide_arguments() {
+        let mut rng = rand::thread_rng();
+        let atoms: Vec<Formula> = (0..=MAX_DISTRACTOR_ATOMS).map(|i| Formula::Atom(format!("A{}", i))).collect();
+        let conclusion = atoms.iter().skip(1).fold(atoms[0].clone(), |acc, a| Formula::Or(Box::new(acc), Box::new(a.clone())));
+        let err = add_distractors(&atoms[..1], &conclusion, 1, &mut rng).unwrap_err();
+        assert_eq!(err, DistractorError::TooManyAtoms { atoms: MAX_DISTRACTOR_ATOMS + 1 });
+    }
+}
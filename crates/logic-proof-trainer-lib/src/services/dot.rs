@@ -0,0 +1,279 @@
+//! Graphviz DOT export: formula syntax trees, proof-tree derivations and the
+//! citation graph of a proof.
+//!
+//! Labels use the trainer's notation (`Formula::display_string` and
+//! `Justification::display_string`). Output is plain DOT for `dot -Tsvg`.
+
+use std::collections::HashMap;
+
+use crate::models::{Formula, Proof, ProofLine, ProofScope};
+use super::dependency::DependencyTracker;
+use super::proof_tree::{is_nesting_rule, ProofNode, ProofTree};
+
+/// Escape a string for a double-quoted DOT label
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Node label for the root of `formula`: its main connective, atom or ⊥
+fn connective_label(formula: &Formula) -> String {
+    match formula {
+        Formula::Atom(name) => name.clone(),
+        Formula::Contradiction => "⊥".to_string(),
+        _ => formula.main_connective().unwrap_or_default().to_string(),
+    }
+}
+
+fn children(formula: &Formula) -> Vec<&Formula> {
+    match formula {
+        Formula::Atom(_) | Formula::Contradiction => vec![],
+        Formula::Not(inner) => vec![inner],
+        Formula::And(l, r) | Formula::Or(l, r) | Formula::Implies(l, r) | Formula::Biconditional(l, r) => vec![l, r],
+    }
+}
+
+/// Syntax trees of labelled formulas, one cluster each. With `share`,
+/// identical subtrees of a formula are drawn once, turning its tree into a
+/// DAG; edges keep left-to-right order.
+pub fn formulas_to_dot(formulas: &[(String, &Formula)], share: bool) -> String {
+    let mut lines = vec![
+        "digraph formulas {".to_string(),
+        "  ordering=out;".to_string(),
+        "  node [shape=circle, fontname=\"monospace\"];".to_string(),
+    ];
+    for (i, (label, formula)) in formulas.iter().enumerate() {
+        lines.push(format!("  subgraph cluster_{} {{", i));
+        lines.push(format!("    label=\"{}\";", escape(label)));
+        let mut builder = AstBuilder { prefix: format!("f{}_", i), share, shared: HashMap::new(), next: 0, lines: Vec::new() };
+        builder.node(formula);
+        lines.extend(builder.lines);
+        lines.push("  }".to_string());
+    }
+    lines.push("}".to_string());
+    lines.join("\n")
+}
+
+/// Emits the nodes and edges of one formula's syntax tree
+struct AstBuilder<'a> {
+    prefix: String,
+    share: bool,
+    /// Node id of each subtree already drawn (with `share`)
+    shared: HashMap<&'a Formula, String>,
+    next: usize,
+    lines: Vec<String>,
+}
+
+impl<'a> AstBuilder<'a> {
+    /// Draw `formula` (or find it, when sharing) and return its node id
+    fn node(&mut self, formula: &'a Formula) -> String {
+        if self.share {
+            if let Some(id) = self.shared.get(formula) {
+                return id.clone();
+            }
+        }
+        let id = format!("{}{}", self.prefix, self.next);
+        self.next += 1;
+        let shape = if children(formula).is_empty() { ", shape=box" } else { "" };
+        self.lines.push(format!("    {} [label=\"{}\"{}];", id, escape(&connective_label(formula)), shape));
+        for child in children(formula) {
+            let child_id = self.node(child);
+            self.lines.push(format!("    {} -> {};", id, child_id));
+        }
+        if self.share {
+            self.shared.insert(formula, id.clone());
+        }
+        id
+    }
+}
+
+/// `tree` as a derivation graph with the conclusion at the top. Premises
+/// are boxes, assumptions dashed boxes, and the assumption a subproof rule
+/// discharges hangs off it by a dashed edge.
+pub fn proof_tree_to_dot(tree: &ProofTree) -> String {
+    let mut lines = vec![
+        "digraph proof_tree {".to_string(),
+        "  rankdir=BT;".to_string(),
+        "  node [shape=plaintext, fontname=\"monospace\"];".to_string(),
+    ];
+    let mut next = 0;
+    tree_node(&tree.root, &mut next, &mut lines);
+    lines.push("}".to_string());
+    lines.join("\n")
+}
+
+fn tree_node(node: &ProofNode, next: &mut usize, lines: &mut Vec<String>) -> String {
+    let id = format!("n{}", *next);
+    *next += 1;
+    let formula = escape(&node.formula().display_string());
+    match node {
+        ProofNode::Premise(_) => lines.push(format!("  {} [label=\"{}\\nPremise\", shape=box];", id, formula)),
+        ProofNode::Assumption(_) => {
+            lines.push(format!("  {} [label=\"{}\\nAssumption\", shape=box, style=dashed];", id, formula))
+        }
+        ProofNode::Derivation { rule, children, .. } => {
+            lines.push(format!("  {} [label=\"{}\\n{}\"];", id, formula, escape(rule)));
+            for child in children {
+                let child_id = tree_node(child, next, lines);
+                let style = if is_nesting_rule(rule) && child.is_assumption() { " [style=dashed]" } else { "" };
+                lines.push(format!("  {} -> {}{};", child_id, id, style));
+            }
+        }
+    }
+    id
+}
+
+/// The line that establishes the theorem: the last valid depth-0 line with
+/// the conclusion, else the last line
+fn conclusion_line(proof: &Proof) -> Option<usize> {
+    proof.lines.iter().rev()
+        .find(|l| l.depth == 0 && l.is_valid && l.formula == proof.theorem.conclusion)
+        .or(proof.lines.last())
+        .map(|l| l.line_number)
+}
+
+/// `proof` as a citation graph: one node per line, an edge from each cited
+/// line to the line citing it, and a cluster per subproof. Invalid lines
+/// are red; lines the conclusion does not depend on are grey and dashed.
+pub fn proof_to_dot(proof: &Proof) -> String {
+    let tracker = DependencyTracker::from_proof(proof);
+    let used = conclusion_line(proof)
+        .map(|line| {
+            let mut used = tracker.all_dependencies(line);
+            used.insert(line);
+            used
+        })
+        .unwrap_or_default();
+
+    let mut lines = vec![
+        "digraph proof {".to_string(),
+        "  node [shape=box, fontname=\"monospace\"];".to_string(),
+    ];
+    emit_scope(proof, None, 1, &mut lines, &|line| {
+        let label = format!(
+            "{}. {}\\n{}",
+            line.line_number,
+            escape(&line.formula.display_string()),
+            escape(&line.justification.display_string())
+        );
+        let style = if !line.is_valid {
+            ", color=red, fontcolor=red"
+        } else if !used.contains(&line.line_number) {
+            ", style=dashed, color=gray50, fontcolor=gray50"
+        } else {
+            ""
+        };
+        format!("l{} [label=\"{}\"{}];", line.line_number, label, style)
+    });
+    for line in &proof.lines {
+        let mut cited: Vec<usize> = tracker.direct_dependencies(line.line_number).into_iter().collect();
+        cited.sort();
+        for from in cited {
+            lines.push(format!("  l{} -> l{};", from, line.line_number));
+        }
+    }
+    lines.push("}".to_string());
+    lines.join("\n")
+}
+
+/// Emit the lines whose innermost scope is `scope`, then each subproof
+/// opened in it as a nested cluster.
+fn emit_scope(
+    proof: &Proof,
+    scope: Option<&ProofScope>,
+    indent: usize,
+    lines: &mut Vec<String>,
+    node: &dyn Fn(&ProofLine) -> String,
+) {
+    let pad = "  ".repeat(indent);
+    let scope_id = scope.map(|s| s.id.as_str());
+    for line in proof.lines.iter().filter(|l| l.scope_id.as_deref() == scope_id) {
+        lines.push(format!("{}{}", pad, node(line)));
+    }
+    for child in proof.scope_manager.all_scopes().iter().filter(|s| s.parent_scope_id.as_deref() == scope_id) {
+        let end = child.end_line.map(|e| e.to_string()).unwrap_or_else(|| "…".to_string());
+        lines.push(format!("{}subgraph \"cluster_{}\" {{", pad, escape(&child.id)));
+        lines.push(format!(
+            "{}  label=\"{} {}-{}\";",
+            pad,
+            escape(child.technique.abbreviation()),
+            child.start_line,
+            end
+        ));
+        emit_scope(proof, Some(child), indent + 1, lines, node);
+        lines.push(format!("{}}}", pad));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::rules::InferenceRule;
+    use crate::models::{Difficulty, Justification, Theorem};
+    use crate::services::verifier::ProofVerifier;
+
+    fn parse(s: &str) -> Formula {
+        Formula::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_formula_sharing() {
+        let formula = parse("(P & Q) -> (P & Q)");
+        let tree = formulas_to_dot(&[("Conclusion".to_string(), &formula)], false);
+        let dag = formulas_to_dot(&[("Conclusion".to_string(), &formula)], true);
+        assert_eq!(tree.matches("label=\"·\"").count(), 2);
+        assert_eq!(dag.matches("label=\"·\"").count(), 1);
+        assert_eq!(dag.matches(" -> ").count(), 4);
+        assert!(dag.contains("label=\"Conclusion\""));
+    }
+
+    #[test]
+    fn test_proof_graph_marks_invalid_and_unused_lines() {
+        let theorem = Theorem::new(
+            vec![parse("P -> Q"), parse("P"), parse("R")],
+            parse("Q"),
+            Difficulty::Easy,
+            None,
+            None,
+        );
+        let mut proof = Proof::new(theorem);
+        proof.add_line(parse("Q"), Justification::Inference {
+            rule: InferenceRule::ModusPonens,
+            lines: vec![1, 2],
+        });
+        proof.add_line(parse("P"), Justification::Inference {
+            rule: InferenceRule::ModusTollens,
+            lines: vec![1, 3],
+        });
+        ProofVerifier::verify_proof(&mut proof);
+        let dot = proof_to_dot(&proof);
+        assert!(dot.contains("l1 -> l4;") && dot.contains("l2 -> l4;"));
+        assert!(dot.lines().any(|l| l.contains("l5 [") && l.contains("color=red")));
+        assert!(dot.lines().any(|l| l.contains("l3 [") && l.contains("gray50")));
+        assert!(!dot.lines().any(|l| l.contains("l4 [") && l.contains("color=")));
+    }
+
+    #[test]
+    fn test_proof_tree_and_subproof_clusters() {
+        let mt = ProofNode::derivation(
+            parse("~P"),
+            "MT",
+            vec![ProofNode::premise(parse("P -> Q")), ProofNode::assumption(parse("~Q"))],
+            None,
+        );
+        let tree = ProofTree::new(ProofNode::derivation(
+            parse("~Q -> ~P"),
+            "CP",
+            vec![ProofNode::assumption(parse("~Q")), mt],
+            Some(parse("~Q")),
+        ));
+        let dot = proof_tree_to_dot(&tree);
+        assert_eq!(dot.matches("style=dashed]").count(), 3);
+
+        let theorem = Theorem::new(tree.premises(), tree.conclusion().clone(), Difficulty::Easy, None, None);
+        let mut proof = tree.to_proof(theorem).unwrap();
+        ProofVerifier::verify_proof(&mut proof);
+        let dot = proof_to_dot(&proof);
+        assert!(dot.contains("subgraph \"cluster_scope-1\""));
+        assert!(dot.contains("label=\"CP 2-3\""));
+    }
+}
@@ -0,0 +1,108 @@
+//! Equivalence-proof tasks: show that formula A can be rewritten into B.
+//!
+//! The obfuscation pipeline turns a wrapped base argument A into an
+//! equivalent formula B by single replacements. Recording each replacement
+//! gives a task (A, B) with a reference proof: a chain of equivalence steps,
+//! each citing the line before it. Answers are checked with
+//! `ProofVerifier::verify_equivalence_proof`, which allows replacement only.
+
+use crate::models::rules::EquivalenceRule;
+use crate::models::Formula;
+use super::proof_tree::{ProofNode, ProofTree};
+use super::verifier::ProofVerifier;
+
+/// One replacement: `rule` applied to a single subformula of the previous
+/// formula, giving `result`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquivalenceStep {
+    pub rule: EquivalenceRule,
+    pub result: Formula,
+}
+
+/// A pair of equivalent formulas with the replacements leading from one to the other
+#[derive(Debug, Clone)]
+pub struct EquivalencePair {
+    /// The formula the proof starts from (its only premise)
+    pub source: Formula,
+    /// The formula to reach (the conclusion)
+    pub target: Formula,
+    /// Replacements from `source` to `target`, in order
+    pub steps: Vec<EquivalenceStep>,
+    /// Semantic class id of the base argument `source` wraps
+    pub base_class: String,
+}
+
+impl EquivalencePair {
+    /// Premises of the task as a theorem (`target` is the conclusion).
+    pub fn premises(&self) -> Vec<Formula> {
+        vec![self.source.clone()]
+    }
+
+    /// The recorded steps as a proof tree: one derivation per step, named by
+    /// the rule's abbreviation, with the previous step (or the premise) as
+    /// its only child. `ProofTree::to_proof` turns it into equivalence lines.
+    pub fn solution(&self) -> ProofTree {
+        let root = self.steps.iter().fold(ProofNode::premise(self.source.clone()), |previous, step| {
+            ProofNode::derivation(step.result.clone(), step.rule.abbreviation(), vec![previous], None)
+        });
+        ProofTree::new(root)
+    }
+
+    /// Whether the steps really lead from `source` to `target`, each one a
+    /// valid single replacement.
+    pub fn is_consistent(&self) -> bool {
+        let mut current = &self.source;
+        for step in &self.steps {
+            if !ProofVerifier::is_valid_replacement(current, &step.result, step.rule) {
+                return false;
+            }
+            current = &step.result;
+        }
+        *current == self.target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::models::theorem::{DifficultySpec, DifficultyTier};
+    use crate::services::obfuscate_gen::ObfuscateGenerator;
+    use crate::services::truth_table::are_equivalent_dynamic;
+
+    #[test]
+    fn test_solutions_verify_as_equivalence_proofs() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for tier in [DifficultyTier::Easy, DifficultyTier::Hard, DifficultyTier::Expert] {
+            let spec = DifficultySpec::from_tier(tier);
+            for _ in 0..5 {
+                let (theorem, pair) = ObfuscateGenerator::generate_equivalence(&spec, Some(tier), &mut rng);
+                assert!(pair.is_consistent());
+                assert_ne!(pair.source, pair.target);
+                assert!(are_equivalent_dynamic(&pair.source, &pair.target));
+
+                let solution = pair.solution();
+                assert_eq!(solution.fragment_count, pair.steps.len());
+                let mut proof = solution.to_proof(theorem).unwrap();
+                ProofVerifier::verify_equivalence_proof(&mut proof);
+                assert!(proof.lines.iter().all(|l| l.is_valid), "{}", solution.pretty_print());
+                assert!(proof.check_complete());
+            }
+        }
+    }
+
+    #[test]
+    fn test_steps_follow_rule_weights() {
+        let mut spec = DifficultySpec::from_tier(DifficultyTier::Medium);
+        spec.gnarly_combos = Some(false);
+        spec.rule_weights = Some(EquivalenceRule::all().into_iter()
+            .map(|rule| (rule, if rule == EquivalenceRule::Commutation { 1.0 } else { 0.0 }))
+            .collect());
+        let mut rng = StdRng::seed_from_u64(7);
+        let (_, pair) = ObfuscateGenerator::generate_equivalence(&spec, None, &mut rng);
+        assert!(pair.is_consistent());
+        assert!(!pair.steps.is_empty());
+        assert!(pair.steps.iter().all(|step| step.rule == EquivalenceRule::Commutation));
+    }
+}
@@ -0,0 +1,538 @@
+//! Import of external problems: TPTP files and plain-text argument lists.
+//!
+//! TPTP problems must be propositional: `fof` or `cnf` formulas over
+//! lower-case atoms, without quantifiers, terms or equality. Atoms are
+//! renamed to the trainer's upper-case convention (`p` → `P`, the inverse of
+//! the TPTP export). In the text format each argument is written as
+//! `premise, premise, ... / conclusion` in the trainer's formula syntax.
+//! Premises may also stand on their own lines before the line with the `/`.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::models::Formula;
+use super::proof_tree::DegenerateProofError;
+use super::truth_table::{entails_dynamic, validate_theorem_dynamic};
+
+/// Widest problem the dynamic truth tables can validate
+pub const MAX_IMPORT_ATOMS: usize = 20;
+
+/// An argument read from an external file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedProblem {
+    pub premises: Vec<Formula>,
+    pub conclusion: Formula,
+    /// Line of the problem (text) or of its conjecture (TPTP)
+    pub line: usize,
+    /// Trainer atom of each source atom that had to be renamed
+    pub atoms: BTreeMap<String, String>,
+}
+
+/// Why a file cannot be imported
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+fn error<T>(line: usize, message: impl Into<String>) -> Result<T, ImportError> {
+    Err(ImportError { line, message: message.into() })
+}
+
+/// Why an imported problem is not benchmarked
+#[derive(Debug, Clone)]
+pub enum ImportRejection {
+    /// More atoms than the truth tables can check
+    TooManyAtoms(usize),
+    /// The argument fails a check of `validate_theorem`
+    Degenerate(DegenerateProofError),
+}
+
+impl std::fmt::Display for ImportRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportRejection::TooManyAtoms(count) => {
+                write!(f, "{} atoms; at most {} can be validated", count, MAX_IMPORT_ATOMS)
+            }
+            ImportRejection::Degenerate(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Run the generator's degeneracy checks on an imported argument, after
+/// checking that it is valid at all. An argument without premises only
+/// needs a tautological conclusion.
+pub fn validate_imported(premises: &[Formula], conclusion: &Formula) -> Result<(), ImportRejection> {
+    let atoms: BTreeSet<String> = premises.iter()
+        .chain(std::iter::once(conclusion))
+        .flat_map(|f| f.atoms())
+        .collect();
+    if atoms.len() > MAX_IMPORT_ATOMS {
+        return Err(ImportRejection::TooManyAtoms(atoms.len()));
+    }
+    if !entails_dynamic(premises, conclusion) {
+        return Err(ImportRejection::Degenerate(DegenerateProofError::InvalidTheorem));
+    }
+    if premises.is_empty() {
+        return Ok(());
+    }
+    validate_theorem_dynamic(premises, conclusion).map_err(ImportRejection::Degenerate)
+}
+
+// ─── Plain text ─────────────────────────────────────────────────────────────
+
+/// Symbols separating the premises from the conclusion
+const TEXT_SEPARATORS: [&str; 3] = ["/", "∴", "⊢"];
+
+/// Parse a text file of arguments. Blank lines and lines starting with `%`
+/// or `//` are ignored.
+pub fn parse_text_problems(text: &str) -> Result<Vec<ImportedProblem>, ImportError> {
+    let mut problems = Vec::new();
+    let mut pending: Vec<Formula> = Vec::new();
+    let mut start = 0;
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        let content = raw.trim();
+        if content.is_empty() || content.starts_with('%') || content.starts_with("//") {
+            continue;
+        }
+        if pending.is_empty() {
+            start = line;
+        }
+        let split = TEXT_SEPARATORS.iter()
+            .filter_map(|sep| content.find(sep).map(|at| (at, sep.len())))
+            .min();
+        let Some((at, len)) = split else {
+            pending.extend(parse_text_formulas(content, line)?);
+            continue;
+        };
+        pending.extend(parse_text_formulas(&content[..at], line)?);
+        let conclusion = parse_text_formula(&content[at + len..], line)?;
+        problems.push(ImportedProblem {
+            premises: std::mem::take(&mut pending),
+            conclusion,
+            line: start,
+            atoms: BTreeMap::new(),
+        });
+    }
+    if !pending.is_empty() {
+        return error(start, "premises without a conclusion (expected '/ conclusion')");
+    }
+    Ok(problems)
+}
+
+fn parse_text_formulas(list: &str, line: usize) -> Result<Vec<Formula>, ImportError> {
+    if list.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    list.split(',').map(|item| parse_text_formula(item, line)).collect()
+}
+
+fn parse_text_formula(item: &str, line: usize) -> Result<Formula, ImportError> {
+    let item = item.trim();
+    if item.is_empty() {
+        return error(line, "empty formula");
+    }
+    Formula::parse(item).map_err(|e| ImportError { line, message: format!("'{}': {}", item, e) })
+}
+
+// ─── TPTP ───────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Lower word, single-quoted name or integer
+    Word(String),
+    /// Upper word: a variable, which propositional problems do not have
+    Variable(String),
+    /// `$true`, `$false`, ...
+    Defined(String),
+    Punct(&'static str),
+}
+
+/// Punctuation and connectives, longest first
+const TPTP_PUNCT: [&str; 17] = [
+    "<=>", "<~>", "=>", "<=", "~|", "~&", "!=", "(", ")", "[", "]", ",", ".", ":", "~", "&", "|",
+];
+
+/// Split `text` into tokens with their lines, dropping comments.
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, ImportError> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '\n' {
+            line += 1;
+        }
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        } else if c == '%' {
+            rest = rest.find('\n').map_or("", |end| &rest[end..]);
+        } else if let Some(body) = rest.strip_prefix("/*") {
+            let Some(end) = body.find("*/") else {
+                return error(line, "unterminated comment");
+            };
+            line += body[..end].matches('\n').count();
+            rest = &body[end + 2..];
+        } else if c == '\'' {
+            let Some(end) = rest[1..].find('\'') else {
+                return error(line, "unterminated quoted name");
+            };
+            tokens.push((Token::Word(rest[1..end + 1].to_string()), line));
+            rest = &rest[end + 2..];
+        } else if c.is_ascii_alphanumeric() || c == '$' {
+            let end = rest[1..].find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).map_or(rest.len(), |i| i + 1);
+            let word = rest[..end].to_string();
+            let token = if c == '$' {
+                Token::Defined(word)
+            } else if c.is_ascii_uppercase() {
+                Token::Variable(word)
+            } else {
+                Token::Word(word)
+            };
+            tokens.push((token, line));
+            rest = &rest[end..];
+        } else if let Some(punct) = TPTP_PUNCT.iter().find(|p| rest.starts_with(**p)) {
+            tokens.push((Token::Punct(punct), line));
+            rest = &rest[punct.len()..];
+        } else if c == '!' || c == '?' {
+            return error(line, "quantifiers are not propositional");
+        } else if c == '=' {
+            return error(line, "equality is not propositional");
+        } else {
+            return error(line, format!("unexpected character '{}'", c));
+        }
+    }
+    Ok(tokens)
+}
+
+struct TptpParser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl TptpParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens.get(self.pos).or(self.tokens.last()).map_or(1, |(_, line)| *line)
+    }
+
+    fn next(&mut self) -> Result<Token, ImportError> {
+        let token = self.peek().cloned();
+        self.pos += 1;
+        token.map_or_else(|| error(self.line(), "unexpected end of file"), Ok)
+    }
+
+    fn expect(&mut self, punct: &'static str) -> Result<(), ImportError> {
+        let line = self.line();
+        match self.next()? {
+            Token::Punct(p) if p == punct => Ok(()),
+            other => error(line, format!("expected '{}', found {}", punct, describe(&other))),
+        }
+    }
+
+    fn word(&mut self) -> Result<String, ImportError> {
+        let line = self.line();
+        match self.next()? {
+            Token::Word(word) => Ok(word),
+            other => error(line, format!("expected a name, found {}", describe(&other))),
+        }
+    }
+
+    /// Skip to the `)` closing the annotated formula, over balanced brackets.
+    fn skip_annotations(&mut self) -> Result<(), ImportError> {
+        let mut depth = 0;
+        loop {
+            match self.next()? {
+                Token::Punct("(") | Token::Punct("[") => depth += 1,
+                Token::Punct("]") => depth -= 1,
+                Token::Punct(")") if depth == 0 => return Ok(()),
+                Token::Punct(")") => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+
+    /// A binary formula, or a unitary one. `&` and `|` chain; other
+    /// connectives need brackets to combine, as in TPTP.
+    fn formula(&mut self) -> Result<Formula, ImportError> {
+        let mut left = self.unitary()?;
+        let mut chained: Option<&'static str> = None;
+        while let Some(Token::Punct(op)) = self.peek().cloned() {
+            if !matches!(op, "&" | "|" | "=>" | "<=" | "<=>" | "<~>" | "~|" | "~&") {
+                break;
+            }
+            if let Some(previous) = chained {
+                if previous != op || !matches!(op, "&" | "|") {
+                    return error(self.line(), format!("'{}' after '{}' needs brackets", op, previous));
+                }
+            }
+            self.pos += 1;
+            let right = self.unitary()?;
+            left = match op {
+                "&" => Formula::And(Box::new(left), Box::new(right)),
+                "|" => Formula::Or(Box::new(left), Box::new(right)),
+                "=>" => Formula::Implies(Box::new(left), Box::new(right)),
+                "<=" => Formula::Implies(Box::new(right), Box::new(left)),
+                "<=>" => Formula::Biconditional(Box::new(left), Box::new(right)),
+                "<~>" => Formula::Biconditional(Box::new(left), Box::new(right)).negate(),
+                "~|" => Formula::Or(Box::new(left), Box::new(right)).negate(),
+                _ => Formula::And(Box::new(left), Box::new(right)).negate(),
+            };
+            chained = Some(op);
+        }
+        Ok(left)
+    }
+
+    fn unitary(&mut self) -> Result<Formula, ImportError> {
+        let line = self.line();
+        match self.next()? {
+            Token::Punct("~") => Ok(self.unitary()?.negate()),
+            Token::Punct("(") => {
+                let inner = self.formula()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Token::Word(name) => {
+                if self.peek() == Some(&Token::Punct("(")) {
+                    return error(line, format!("'{}' has arguments; only propositional atoms are supported", name));
+                }
+                if self.peek() == Some(&Token::Punct("!=")) {
+                    return error(line, "equality is not propositional");
+                }
+                Ok(Formula::Atom(name))
+            }
+            Token::Defined(word) if word == "$false" => Ok(Formula::Contradiction),
+            Token::Defined(word) if word == "$true" => Ok(Formula::Contradiction.negate()),
+            Token::Variable(name) => error(line, format!("variable '{}' in a propositional problem", name)),
+            other => error(line, format!("expected a formula, found {}", describe(&other))),
+        }
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Word(word) | Token::Variable(word) | Token::Defined(word) => format!("'{}'", word),
+        Token::Punct(p) => format!("'{}'", p),
+    }
+}
+
+/// Parse a propositional TPTP problem. Axioms, hypotheses, definitions,
+/// lemmas and theorems become premises and the conjecture the conclusion.
+/// Without a conjecture, a single negated conjecture is negated back;
+/// several (as in CNF refutation problems) become premises with the
+/// conclusion ⊥.
+pub fn parse_tptp_problem(text: &str) -> Result<ImportedProblem, ImportError> {
+    let mut parser = TptpParser { tokens: tokenize(text)?, pos: 0 };
+    let mut premises = Vec::new();
+    let mut conjecture: Option<(Formula, usize)> = None;
+    let mut negated: Vec<(Formula, usize)> = Vec::new();
+
+    while parser.peek().is_some() {
+        let line = parser.line();
+        let language = parser.word()?;
+        parser.expect("(")?;
+        if language == "include" {
+            return error(line, "include directives are not supported; inline the axioms");
+        }
+        if language != "fof" && language != "cnf" {
+            return error(line, format!("'{}' formulas are not supported; use fof or cnf", language));
+        }
+        parser.word()?;
+        parser.expect(",")?;
+        let role_line = parser.line();
+        let role = parser.word()?;
+        parser.expect(",")?;
+        let formula = parser.formula()?;
+        match parser.next()? {
+            Token::Punct(")") => {}
+            Token::Punct(",") => parser.skip_annotations()?,
+            other => return error(parser.line(), format!("expected ')', found {}", describe(&other))),
+        }
+        parser.expect(".")?;
+
+        match role.as_str() {
+            "axiom" | "hypothesis" | "definition" | "assumption" | "lemma" | "theorem" | "corollary" | "plain" => {
+                premises.push(formula);
+            }
+            "conjecture" if conjecture.is_some() => return error(line, "more than one conjecture"),
+            "conjecture" => conjecture = Some((formula, line)),
+            "negated_conjecture" => negated.push((formula, line)),
+            other => return error(role_line, format!("unsupported role '{}'", other)),
+        }
+    }
+
+    let (conclusion, line) = match (conjecture, negated.len()) {
+        (Some(_), n) if n > 0 => return error(negated[0].1, "both a conjecture and a negated conjecture"),
+        (Some(conjecture), _) => conjecture,
+        (None, 0) => return error(parser.line(), "no conjecture"),
+        (None, 1) => {
+            let (formula, line) = negated.remove(0);
+            let conclusion = match formula {
+                Formula::Not(inner) => *inner,
+                other => other.negate(),
+            };
+            (conclusion, line)
+        }
+        (None, _) => {
+            let line = negated[0].1;
+            premises.extend(negated.into_iter().map(|(formula, _)| formula));
+            (Formula::Contradiction, line)
+        }
+    };
+
+    let atoms: BTreeSet<String> = premises.iter()
+        .chain(std::iter::once(&conclusion))
+        .flat_map(|f| f.atoms())
+        .collect();
+    let names = trainer_atom_names(&atoms);
+    Ok(ImportedProblem {
+        premises: premises.iter().map(|f| rename_atoms(f, &names)).collect(),
+        conclusion: rename_atoms(&conclusion, &names),
+        line,
+        atoms: names,
+    })
+}
+
+/// Trainer names for TPTP atoms: upper case, `_prime` back to `'`, other
+/// characters replaced by `_`, and a numeric suffix where two would clash.
+/// Only renamed atoms are listed.
+pub fn trainer_atom_names(atoms: &BTreeSet<String>) -> BTreeMap<String, String> {
+    let mut taken = HashSet::new();
+    let mut names = BTreeMap::new();
+    for atom in atoms {
+        let upper = atom.strip_suffix("_prime").map_or_else(|| atom.to_uppercase(), |stem| stem.to_uppercase() + "'");
+        let mut base: String = upper.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '\'' { c } else { '_' })
+            .collect();
+        if base.is_empty() {
+            base = "A".to_string();
+        }
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while !taken.insert(name.clone()) {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        names.insert(atom.clone(), name);
+    }
+    names.retain(|atom, name| atom != name);
+    names
+}
+
+/// `formula` with every atom in `names` renamed at once
+fn rename_atoms(formula: &Formula, names: &BTreeMap<String, String>) -> Formula {
+    let rename = |f: &Formula| Box::new(rename_atoms(f, names));
+    match formula {
+        Formula::Atom(name) => Formula::Atom(names.get(name).unwrap_or(name).clone()),
+        Formula::Contradiction => Formula::Contradiction,
+        Formula::Not(inner) => Formula::Not(rename(inner)),
+        Formula::And(l, r) => Formula::And(rename(l), rename(r)),
+        Formula::Or(l, r) => Formula::Or(rename(l), rename(r)),
+        Formula::Implies(l, r) => Formula::Implies(rename(l), rename(r)),
+        Formula::Biconditional(l, r) => Formula::Biconditional(rename(l), rename(r)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Formula {
+        Formula::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_text_problems() {
+        let text = "% modus ponens\nP -> Q, P / Q\n\nP | Q\n~P\n∴ Q\n/ P -> P\n";
+        let problems = parse_text_problems(text).unwrap();
+        assert_eq!(problems.len(), 3);
+        assert_eq!(problems[0].premises, vec![parse("P -> Q"), parse("P")]);
+        assert_eq!(problems[1].premises, vec![parse("P | Q"), parse("~P")]);
+        assert_eq!(problems[1].conclusion, parse("Q"));
+        assert_eq!(problems[1].line, 4);
+        assert!(problems[2].premises.is_empty());
+
+        let err = parse_text_problems("P -> Q\nP\n").unwrap_err();
+        assert_eq!(err.line, 1);
+        assert_eq!(parse_text_problems("P, / Q").unwrap_err().message, "empty formula");
+    }
+
+    #[test]
+    fn test_tptp_problem_round_trips_export() {
+        let text = "% File     : easy-1\n\
+                    fof(premise_1, axiom, ( p => ( q & ~ r ) )).\n\
+                    fof(premise_2, axiom, p, file('x.p')).\n\
+                    /* block\n comment */\n\
+                    fof(conclusion, conjecture, ~ r).\n";
+        let problem = parse_tptp_problem(text).unwrap();
+        assert_eq!(problem.premises, vec![parse("P -> (Q & ~R)"), parse("P")]);
+        assert_eq!(problem.conclusion, parse("~R"));
+        assert_eq!(problem.line, 6);
+        assert_eq!(problem.atoms["q"], "Q");
+    }
+
+    #[test]
+    fn test_tptp_connectives_and_roles() {
+        let text = "fof(a, hypothesis, (p <= q) & $true & (p <~> r)).\n\
+                    cnf(c1, negated_conjecture, ~ p | q).\n";
+        let problem = parse_tptp_problem(text).unwrap();
+        assert_eq!(problem.premises, vec![parse("((Q -> P) & ~#) & ~(P <-> R)")]);
+        assert_eq!(problem.conclusion, parse("~(~P | Q)"));
+
+        let refutation = parse_tptp_problem("cnf(a, negated_conjecture, p).\ncnf(b, negated_conjecture, ~ p).\n").unwrap();
+        assert_eq!(refutation.premises, vec![parse("P"), parse("~P")]);
+        assert_eq!(refutation.conclusion, Formula::Contradiction);
+    }
+
+    #[test]
+    fn test_tptp_rejects_non_propositional() {
+        let cases = [
+            ("fof(a, conjecture, ! [X] : p(X)).", "quantifiers"),
+            ("fof(a, conjecture, p(a)).", "arguments"),
+            ("fof(a, conjecture, p => q => r).", "needs brackets"),
+            ("fof(a, axiom, p).", "no conjecture"),
+            ("include('Axioms/SYN.ax').", "include"),
+            ("fof(a, conjecture, X).", "variable"),
+        ];
+        for (text, message) in cases {
+            let err = parse_tptp_problem(text).unwrap_err();
+            assert!(err.message.contains(message), "{}: {}", text, err);
+        }
+    }
+
+    #[test]
+    fn test_atom_names_are_distinct() {
+        let atoms: BTreeSet<String> = ["p", "p_prime", "ab", "aB", "t1"].iter().map(|s| s.to_string()).collect();
+        let names = trainer_atom_names(&atoms);
+        assert_eq!(names["p"], "P");
+        assert_eq!(names["p_prime"], "P'");
+        assert_eq!(names["t1"], "T1");
+        assert_ne!(names["ab"], names["aB"]);
+    }
+
+    #[test]
+    fn test_validate_imported() {
+        assert!(validate_imported(&[parse("P -> Q"), parse("P")], &parse("Q")).is_ok());
+        assert!(validate_imported(&[], &parse("P -> P")).is_ok());
+        assert!(matches!(
+            validate_imported(&[parse("P"), parse("~P")], &Formula::Contradiction),
+            Err(ImportRejection::Degenerate(DegenerateProofError::ContradictoryPremises))
+        ));
+        assert!(matches!(
+            validate_imported(&[parse("P")], &parse("Q")),
+            Err(ImportRejection::Degenerate(DegenerateProofError::InvalidTheorem))
+        ));
+        assert!(matches!(
+            validate_imported(&[], &parse("P")),
+            Err(ImportRejection::Degenerate(DegenerateProofError::InvalidTheorem))
+        ));
+    }
+}
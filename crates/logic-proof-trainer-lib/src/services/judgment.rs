@@ -0,0 +1,301 @@
+//! Validity judgment: near-miss invalid arguments and countermodels.
+//!
+//! Every generated theorem is valid, so a model can score well by always
+//! trying to prove. A judgment set mixes in near misses: valid arguments with
+//! one small change (a connective, a conditional's direction, a negation, an
+//! atom or a dropped premise) that breaks the entailment. Invalidity is
+//! confirmed by finding a countermodel, which is also how an answer of
+//! "invalid" is checked.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::models::{Formula, PathStep};
+use super::truth_table::{compute_truth_table_over, DynTruthTable};
+
+/// Most atoms a countermodel search enumerates (2^20 rows)
+pub const MAX_JUDGMENT_ATOMS: usize = 20;
+
+/// Perturbations drawn per near miss before giving up
+pub const MAX_NEAR_MISS_ATTEMPTS: usize = 100;
+
+/// How a near miss differs from the valid argument it was made from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Perturbation {
+    /// One binary connective replaced by another
+    Connective,
+    /// The sides of one conditional swapped
+    Converse,
+    /// One negation added or removed
+    Negation,
+    /// One atom occurrence replaced by another atom of the argument
+    Atom,
+    /// One premise left out
+    DropPremise,
+}
+
+impl Perturbation {
+    pub fn all() -> Vec<Perturbation> {
+        vec![
+            Perturbation::Connective,
+            Perturbation::Converse,
+            Perturbation::Negation,
+            Perturbation::Atom,
+            Perturbation::DropPremise,
+        ]
+    }
+}
+
+/// An invalid argument one perturbation away from a valid one
+#[derive(Debug, Clone)]
+pub struct NearMiss {
+    pub premises: Vec<Formula>,
+    pub conclusion: Formula,
+    pub perturbation: Perturbation,
+    /// Assignment making every premise true and the conclusion false
+    pub countermodel: BTreeMap<String, bool>,
+}
+
+/// Why no near miss was produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NearMissError {
+    /// The argument has more atoms than a countermodel search enumerates
+    TooManyAtoms { atoms: usize },
+    /// No perturbation tried broke the entailment with consistent premises
+    NotFound,
+}
+
+impl std::fmt::Display for NearMissError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NearMissError::TooManyAtoms { atoms } => write!(
+                f,
+                "the argument has {} atoms; countermodels are searched over at most {}",
+                atoms, MAX_JUDGMENT_ATOMS
+            ),
+            NearMissError::NotFound => write!(
+                f,
+                "no perturbation made the argument invalid in {} attempts",
+                MAX_NEAR_MISS_ATTEMPTS
+            ),
+        }
+    }
+}
+
+/// Why an assignment is not a countermodel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CountermodelError {
+    /// An atom of the argument has no value
+    MissingAtom(String),
+    /// The premise at this index is false
+    PremiseFalse(usize),
+    ConclusionTrue,
+}
+
+impl std::fmt::Display for CountermodelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CountermodelError::MissingAtom(atom) => write!(f, "Atom {} has no value", atom),
+            CountermodelError::PremiseFalse(i) => write!(f, "Premise {} is false under the assignment", i + 1),
+            CountermodelError::ConclusionTrue => write!(f, "The conclusion is true under the assignment"),
+        }
+    }
+}
+
+/// Make an invalid argument by one random perturbation of a valid one. The
+/// result has consistent premises (its countermodel satisfies them all).
+pub fn near_miss(premises: &[Formula], conclusion: &Formula, rng: &mut impl Rng) -> Result<NearMiss, NearMissError> {
+    let atoms = argument_atoms(premises, conclusion);
+    if atoms.len() > MAX_JUDGMENT_ATOMS {
+        return Err(NearMissError::TooManyAtoms { atoms: atoms.len() });
+    }
+    let kinds = Perturbation::all();
+    for _attempt in 0..MAX_NEAR_MISS_ATTEMPTS {
+        let perturbation = kinds[rng.gen_range(0..kinds.len())];
+        let Some((premises, conclusion)) = perturb(premises, conclusion, perturbation, &atoms, rng) else {
+            continue;
+        };
+        if let Some(countermodel) = countermodel(&premises, &conclusion) {
+            return Ok(NearMiss { premises, conclusion, perturbation, countermodel });
+        }
+    }
+    Err(NearMissError::NotFound)
+}
+
+/// Apply `perturbation` at a random place, or None if it has nowhere to apply.
+fn perturb(
+    premises: &[Formula],
+    conclusion: &Formula,
+    perturbation: Perturbation,
+    atoms: &[String],
+    rng: &mut impl Rng,
+) -> Option<(Vec<Formula>, Formula)> {
+    if perturbation == Perturbation::DropPremise {
+        if premises.len() < 2 {
+            return None;
+        }
+        let mut premises = premises.to_vec();
+        premises.remove(rng.gen_range(0..premises.len()));
+        return Some((premises, conclusion.clone()));
+    }
+
+    // Every other perturbation rewrites one node of one formula
+    let mut formulas: Vec<Formula> = premises.to_vec();
+    formulas.push(conclusion.clone());
+    let target = rng.gen_range(0..formulas.len());
+    let formula = &formulas[target];
+    let sites: Vec<(Vec<PathStep>, &Formula)> = formula.subformulas_with_paths()
+        .into_iter()
+        .filter(|(_, sub)| match perturbation {
+            Perturbation::Connective => matches!(
+                sub,
+                Formula::And(..) | Formula::Or(..) | Formula::Implies(..) | Formula::Biconditional(..)
+            ),
+            Perturbation::Converse => matches!(sub, Formula::Implies(..)),
+            Perturbation::Atom => matches!(sub, Formula::Atom(_)) && atoms.len() > 1,
+            _ => true,
+        })
+        .collect();
+    if sites.is_empty() {
+        return None;
+    }
+    let (path, sub) = &sites[rng.gen_range(0..sites.len())];
+    let replacement = match (perturbation, sub) {
+        (Perturbation::Connective, Formula::And(l, r) | Formula::Or(l, r) | Formula::Implies(l, r) | Formula::Biconditional(l, r)) => {
+            let (l, r) = (l.clone(), r.clone());
+            let options = [
+                Formula::And(l.clone(), r.clone()),
+                Formula::Or(l.clone(), r.clone()),
+                Formula::Implies(l.clone(), r.clone()),
+                Formula::Biconditional(l, r),
+            ];
+            let others: Vec<&Formula> = options.iter().filter(|f| f.main_connective() != sub.main_connective()).collect();
+            others[rng.gen_range(0..others.len())].clone()
+        }
+        (Perturbation::Converse, Formula::Implies(l, r)) => Formula::Implies(r.clone(), l.clone()),
+        (Perturbation::Negation, Formula::Not(inner)) => (**inner).clone(),
+        (Perturbation::Negation, _) => Formula::Not(Box::new((*sub).clone())),
+        (Perturbation::Atom, Formula::Atom(name)) => {
+            let others: Vec<&String> = atoms.iter().filter(|a| *a != name).collect();
+            Formula::Atom(others[rng.gen_range(0..others.len())].clone())
+        }
+        _ => unreachable!("sites are filtered to match the perturbation"),
+    };
+    formulas[target] = formula.replace_at_path(path, &replacement);
+    let conclusion = formulas.pop().expect("conclusion was pushed last");
+    Some((formulas, conclusion))
+}
+
+/// An assignment under which every premise is true and the conclusion false,
+/// if the argument is invalid. Atoms are those of the argument; at most
+/// `MAX_JUDGMENT_ATOMS` of them (None for wider arguments).
+pub fn countermodel(premises: &[Formula], conclusion: &Formula) -> Option<BTreeMap<String, bool>> {
+    let atoms = argument_atoms(premises, conclusion);
+    if atoms.len() > MAX_JUDGMENT_ATOMS {
+        return None;
+    }
+    let num_vars = atoms.len().max(1);
+    let counterexamples = premises.iter()
+        .fold(DynTruthTable::tautology(num_vars as u8), |acc, p| acc.and(&compute_truth_table_over(p, &atoms)))
+        .and(&compute_truth_table_over(conclusion, &atoms).not());
+    let row = (0..1usize << num_vars).find(|&row| counterexamples.get(row))?;
+    // Row 0 is all-true; variable i is false where bit (n-1-i) of the row is set
+    Some(atoms.iter()
+        .enumerate()
+        .map(|(i, atom)| (atom.clone(), row >> (num_vars - 1 - i) & 1 == 0))
+        .collect())
+}
+
+/// Check that `assignment` is a countermodel to the argument. Values for
+/// atoms outside the argument are ignored.
+pub fn check_countermodel(
+    premises: &[Formula],
+    conclusion: &Formula,
+    assignment: &BTreeMap<String, bool>,
+) -> Result<(), CountermodelError> {
+    if let Some(atom) = argument_atoms(premises, conclusion).into_iter().find(|a| !assignment.contains_key(a)) {
+        return Err(CountermodelError::MissingAtom(atom));
+    }
+    if let Some(i) = premises.iter().position(|p| p.evaluate(assignment) != Some(true)) {
+        return Err(CountermodelError::PremiseFalse(i));
+    }
+    if conclusion.evaluate(assignment) != Some(false) {
+        return Err(CountermodelError::ConclusionTrue);
+    }
+    Ok(())
+}
+
+fn argument_atoms(premises: &[Formula], conclusion: &Formula) -> Vec<String> {
+    premises.iter()
+        .chain(std::iter::once(conclusion))
+        .flat_map(|f| f.atoms())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::truth_table::entails_dynamic;
+
+    fn parse(s: &str) -> Formula {
+        Formula::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_near_misses_are_invalid() {
+        let mut rng = rand::thread_rng();
+        let arguments = [
+            (vec![parse("P -> Q"), parse("P")], parse("Q")),
+            (vec![parse("A | B"), parse("A -> C"), parse("B -> C")], parse("C")),
+            (vec![], parse("((P -> Q) & (Q -> R)) -> (P -> R)")),
+        ];
+        let mut seen = BTreeSet::new();
+        for (premises, conclusion) in &arguments {
+            for _ in 0..30 {
+                let miss = near_miss(premises, conclusion, &mut rng).unwrap();
+                assert!(!entails_dynamic(&miss.premises, &miss.conclusion));
+                assert_eq!(check_countermodel(&miss.premises, &miss.conclusion, &miss.countermodel), Ok(()));
+                if miss.perturbation == Perturbation::DropPremise {
+                    assert_eq!(miss.premises.len(), premises.len() - 1);
+                } else {
+                    assert_eq!(miss.premises.len(), premises.len());
+                }
+                seen.insert(format!("{:?}", miss.perturbation));
+            }
+        }
+        assert!(seen.len() >= 4, "perturbations used: {:?}", seen);
+    }
+
+    #[test]
+    fn test_countermodel() {
+        // Affirming the consequent: P false, Q true
+        let premises = [parse("P -> Q"), parse("Q")];
+        let model = countermodel(&premises, &parse("P")).unwrap();
+        assert_eq!(model, BTreeMap::from([("P".to_string(), false), ("Q".to_string(), true)]));
+        assert_eq!(countermodel(&premises[..1], &parse("~Q -> ~P")), None);
+    }
+
+    #[test]
+    fn test_check_countermodel_errors() {
+        let premises = [parse("P -> Q"), parse("Q")];
+        let conclusion = parse("P");
+        let assign = |pairs: &[(&str, bool)]| pairs.iter().map(|(a, v)| (a.to_string(), *v)).collect();
+        assert_eq!(
+            check_countermodel(&premises, &conclusion, &assign(&[("P", false)])),
+            Err(CountermodelError::MissingAtom("Q".to_string()))
+        );
+        assert_eq!(
+            check_countermodel(&premises, &conclusion, &assign(&[("P", false), ("Q", false)])),
+            Err(CountermodelError::PremiseFalse(1))
+        );
+        assert_eq!(
+            check_countermodel(&premises, &conclusion, &assign(&[("P", true), ("Q", true)])),
+            Err(CountermodelError::ConclusionTrue)
+        );
+        assert_eq!(check_countermodel(&premises, &conclusion, &assign(&[("P", false), ("Q", true), ("R", true)])), Ok(()));
+    }
+}
@@ -0,0 +1,233 @@
+//! LaTeX export: formulas and theorems in math mode, proofs as Fitch
+//! diagrams (`fitch.sty`'s `nd` environment) and proof trees as
+//! `bussproofs` derivations.
+//!
+//! Formulas keep the notation of `Formula::display_string` (⊃, ∨, ·, ≡, ~
+//! and the () [] {} bracket hierarchy), so typeset proofs read like the
+//! trainer's own.
+
+use crate::models::{Formula, Justification, Proof};
+use super::proof_tree::{is_nesting_rule, ProofNode, ProofTree};
+
+/// Packages the output needs
+pub const LATEX_PACKAGES: &str = "amssymb, fitch, bussproofs";
+
+/// bussproofs has inference commands for at most five premises
+const MAX_BUSSPROOFS_CHILDREN: usize = 5;
+
+/// Why a proof tree cannot be typeset
+#[derive(Debug, Clone, PartialEq)]
+pub enum LatexError {
+    /// A derivation has more children than bussproofs can draw
+    TooManyChildren { rule: String, count: usize },
+}
+
+impl std::fmt::Display for LatexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LatexError::TooManyChildren { rule, count } => write!(
+                f,
+                "{} has {} premises; bussproofs draws at most {}",
+                rule, count, MAX_BUSSPROOFS_CHILDREN
+            ),
+        }
+    }
+}
+
+/// `formula` in LaTeX math mode (without the surrounding `$`).
+pub fn formula_to_latex(formula: &Formula) -> String {
+    let mut out = String::new();
+    for c in formula.display_string().chars() {
+        match c {
+            '~' => out.push_str("{\\sim}"),
+            '.' => out.push_str("\\cdot"),
+            '∨' => out.push_str("\\lor"),
+            '⊃' => out.push_str("\\supset"),
+            '≡' => out.push_str("\\equiv"),
+            '⊥' => out.push_str("\\bot"),
+            '{' => out.push_str("\\{"),
+            '}' => out.push_str("\\}"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escape text-mode special characters.
+pub fn escape_latex_text(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// The argument as a displayed sequent, `premises ⊢ conclusion`.
+pub fn theorem_to_latex(premises: &[Formula], conclusion: &Formula) -> String {
+    let premises: Vec<String> = premises.iter().map(formula_to_latex).collect();
+    let turnstile = if premises.is_empty() { "\\vdash " } else { " \\vdash " };
+    format!("\\[ {}{}{} \\]", premises.join(",\\; "), turnstile, formula_to_latex(conclusion))
+}
+
+/// `proof` as a Fitch diagram. Premises and assumptions are hypotheses;
+/// a subproof opens where the scope manager has a scope start, and closes
+/// when the lines' depth drops below it, so two subproofs in a row (as in
+/// a case split) get separate bars. Invalid lines carry a comment with the
+/// verifier's message.
+pub fn proof_to_fitch(proof: &Proof) -> String {
+    let scopes = proof.scope_manager.all_scopes();
+    let mut lines = vec!["\\begin{nd}".to_string()];
+    let mut open = 0;
+    for line in &proof.lines {
+        let starts_scope = scopes.iter().any(|s| s.start_line == line.line_number);
+        let keep = if starts_scope { line.depth.saturating_sub(1) } else { line.depth };
+        while open > keep {
+            lines.push(format!("{}\\close", "  ".repeat(open)));
+            open -= 1;
+        }
+        while open < line.depth {
+            open += 1;
+            lines.push(format!("{}\\open", "  ".repeat(open)));
+        }
+        let command = match line.justification {
+            Justification::Premise | Justification::Assumption { .. } => "hypo",
+            _ => "have",
+        };
+        let mut text = format!(
+            "{}\\{} {{{}}} {{{}}} \\by{{{}}}{{}}",
+            "  ".repeat(open + 1),
+            command,
+            line.line_number,
+            formula_to_latex(&line.formula),
+            escape_latex_text(&line.justification.display_string())
+        );
+        if !line.is_valid {
+            let message = line.validation_message.as_deref().unwrap_or("invalid");
+            text.push_str(&format!(" % invalid: {}", message.replace('\n', " ")));
+        }
+        lines.push(text);
+    }
+    while open > 0 {
+        lines.push(format!("{}\\close", "  ".repeat(open)));
+        open -= 1;
+    }
+    lines.push("\\end{nd}".to_string());
+    lines.join("\n")
+}
+
+/// `tree` as a bussproofs derivation. Premises are axioms, assumptions used
+/// inside a subproof are bracketed, and each derivation is labelled with its
+/// rule. A subproof rule's own assumption children are left out, as they are
+/// discharged there: CP draws from its body alone, a case split from the
+/// disjunction and its two cases.
+pub fn proof_tree_to_bussproofs(tree: &ProofTree) -> Result<String, LatexError> {
+    let mut lines = vec!["\\begin{prooftree}".to_string()];
+    emit_node(&tree.root, &mut lines)?;
+    lines.push("\\end{prooftree}".to_string());
+    Ok(lines.join("\n"))
+}
+
+fn emit_node(node: &ProofNode, lines: &mut Vec<String>) -> Result<(), LatexError> {
+    match node {
+        ProofNode::Premise(f) => lines.push(format!("\\AxiomC{{${}$}}", formula_to_latex(f))),
+        ProofNode::Assumption(f) => lines.push(format!("\\AxiomC{{$[{}]$}}", formula_to_latex(f))),
+        ProofNode::Derivation { result, rule, children, .. } => {
+            let children: Vec<&ProofNode> = children.iter()
+                .filter(|child| !(is_nesting_rule(rule) && child.is_assumption()))
+                .collect();
+            let command = match children.len() {
+                0 => {
+                    lines.push("\\AxiomC{}".to_string());
+                    "UnaryInfC"
+                }
+                1 => "UnaryInfC",
+                2 => "BinaryInfC",
+                3 => "TrinaryInfC",
+                4 => "QuaternaryInfC",
+                5 => "QuinaryInfC",
+                count => return Err(LatexError::TooManyChildren { rule: rule.clone(), count }),
+            };
+            for child in children {
+                emit_node(child, lines)?;
+            }
+            lines.push(format!("\\RightLabel{{\\scriptsize {}}}", escape_latex_text(rule)));
+            lines.push(format!("\\{}{{${}$}}", command, formula_to_latex(result)));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Difficulty, Theorem};
+    use crate::services::verifier::ProofVerifier;
+
+    fn parse(s: &str) -> Formula {
+        Formula::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_formula_notation() {
+        assert_eq!(formula_to_latex(&parse("~P -> (Q | R)")), "{\\sim}P \\supset (Q \\lor R)");
+        assert_eq!(
+            theorem_to_latex(&[parse("P -> Q"), parse("P")], &parse("Q")),
+            "\\[ P \\supset Q,\\; P \\vdash Q \\]"
+        );
+    }
+
+    /// P ⊃ Q ⊢ ~Q ⊃ ~P by CP with MT inside
+    fn contraposition() -> ProofTree {
+        let mt = ProofNode::derivation(
+            parse("~P"),
+            "MT",
+            vec![ProofNode::premise(parse("P -> Q")), ProofNode::assumption(parse("~Q"))],
+            None,
+        );
+        ProofTree::new(ProofNode::derivation(
+            parse("~Q -> ~P"),
+            "CP",
+            vec![ProofNode::assumption(parse("~Q")), mt],
+            Some(parse("~Q")),
+        ))
+    }
+
+    #[test]
+    fn test_fitch_scope_bars() {
+        let tree = contraposition();
+        let theorem = Theorem::new(tree.premises(), tree.conclusion().clone(), Difficulty::Easy, None, None);
+        let mut proof = tree.to_proof(theorem).unwrap();
+        ProofVerifier::verify_proof(&mut proof);
+        let fitch = proof_to_fitch(&proof);
+        assert_eq!(fitch.matches("\\open").count(), 1);
+        assert_eq!(fitch.matches("\\close").count(), 1);
+        assert_eq!(fitch.matches("\\hypo").count(), 2);
+        assert!(fitch.contains("\\by{MT 1, 2}{}"));
+        assert!(!fitch.contains("invalid"));
+    }
+
+    #[test]
+    fn test_bussproofs_tree() {
+        let latex = proof_tree_to_bussproofs(&contraposition()).unwrap();
+        let expected = [
+            "\\begin{prooftree}",
+            "\\AxiomC{$P \\supset Q$}",
+            "\\AxiomC{$[{\\sim}Q]$}",
+            "\\RightLabel{\\scriptsize MT}",
+            "\\BinaryInfC{${\\sim}P$}",
+            "\\RightLabel{\\scriptsize CP}",
+            "\\UnaryInfC{${\\sim}Q \\supset {\\sim}P$}",
+            "\\end{prooftree}",
+        ];
+        assert_eq!(latex, expected.join("\n"));
+    }
+}
@@ -0,0 +1,608 @@
+//! Lean 4 export: theorems as `theorem` statements over `Prop` variables and
+//! verified proofs as tactic scripts.
+//!
+//! Every step of a proof becomes a `have` justified by a lemma of the `PB`
+//! namespace in `LEAN_PRELUDE`: one lemma per form of each inference and
+//! replacement rule. Replacement inside a larger formula is lifted with the
+//! prelude's congruence lemmas, and subproofs become nested tactic blocks. A
+//! step the verifier accepted but Lean rejects points at an unsound check.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::models::{Formula, Justification, Proof, Theorem};
+use crate::models::rules::{EquivalenceRule, InferenceRule, ProofTechnique};
+
+/// The lemma library every exported proof uses. All proofs are in Lean 4
+/// core, classical where the rule needs it.
+pub const LEAN_PRELUDE: &str = r#"-- PropBench rule library: one lemma per rule form
+namespace PB
+
+-- Inference rules
+theorem mp {p q : Prop} (h : p → q) (hp : p) : q := h hp
+theorem mt {p q : Prop} (h : p → q) (hnq : ¬q) : ¬p := fun hp => hnq (h hp)
+theorem ds_left {p q : Prop} (h : p ∨ q) (hnp : ¬p) : q := h.elim (fun hp => absurd hp hnp) id
+theorem ds_right {p q : Prop} (h : p ∨ q) (hnq : ¬q) : p := h.elim id (fun hq => absurd hq hnq)
+theorem simp_left {p q : Prop} (h : p ∧ q) : p := h.1
+theorem simp_right {p q : Prop} (h : p ∧ q) : q := h.2
+theorem conj {p q : Prop} (hp : p) (hq : q) : p ∧ q := ⟨hp, hq⟩
+theorem hs {p q r : Prop} (h1 : p → q) (h2 : q → r) : p → r := fun hp => h2 (h1 hp)
+theorem add_left {p q : Prop} (hp : p) : p ∨ q := Or.inl hp
+theorem add_right {p q : Prop} (hq : q) : p ∨ q := Or.inr hq
+theorem cd {p q r s : Prop} (h : p ∨ q) (h1 : p → r) (h2 : q → s) : r ∨ s :=
+  h.elim (fun hp => Or.inl (h1 hp)) (fun hq => Or.inr (h2 hq))
+theorem neg_e {p : Prop} (hp : p) (hnp : ¬p) : False := hnp hp
+
+-- Indirect proof and the contradictions closing it
+theorem ip {p : Prop} (h : ¬p → False) : p := Classical.byContradiction h
+theorem contra_and {p : Prop} (h : p ∧ ¬p) : False := h.2 h.1
+theorem contra_and_rev {p : Prop} (h : ¬p ∧ p) : False := h.1 h.2
+
+-- Replacement rules
+theorem dn {p : Prop} : p ↔ ¬¬p := ⟨fun hp hn => hn hp, fun hnn => Classical.byContradiction hnn⟩
+theorem dem_and {p q : Prop} : ¬(p ∧ q) ↔ ¬p ∨ ¬q :=
+  ⟨fun h => (Classical.em p).elim (fun hp => Or.inr (fun hq => h ⟨hp, hq⟩)) Or.inl,
+   fun h hpq => h.elim (fun hnp => hnp hpq.1) (fun hnq => hnq hpq.2)⟩
+theorem dem_or {p q : Prop} : ¬(p ∨ q) ↔ ¬p ∧ ¬q :=
+  ⟨fun h => ⟨fun hp => h (Or.inl hp), fun hq => h (Or.inr hq)⟩, fun h hpq => hpq.elim h.1 h.2⟩
+theorem comm_and {p q : Prop} : p ∧ q ↔ q ∧ p := ⟨fun h => ⟨h.2, h.1⟩, fun h => ⟨h.2, h.1⟩⟩
+theorem comm_or {p q : Prop} : p ∨ q ↔ q ∨ p := ⟨fun h => h.elim Or.inr Or.inl, fun h => h.elim Or.inr Or.inl⟩
+theorem assoc_and {p q r : Prop} : (p ∧ q) ∧ r ↔ p ∧ (q ∧ r) :=
+  ⟨fun h => ⟨h.1.1, h.1.2, h.2⟩, fun h => ⟨⟨h.1, h.2.1⟩, h.2.2⟩⟩
+theorem assoc_or {p q r : Prop} : (p ∨ q) ∨ r ↔ p ∨ (q ∨ r) :=
+  ⟨fun h => h.elim (fun hpq => hpq.elim Or.inl (fun hq => Or.inr (Or.inl hq))) (fun hr => Or.inr (Or.inr hr)),
+   fun h => h.elim (fun hp => Or.inl (Or.inl hp)) (fun hqr => hqr.elim (fun hq => Or.inl (Or.inr hq)) Or.inr)⟩
+theorem dist_and_left {p q r : Prop} : p ∧ (q ∨ r) ↔ (p ∧ q) ∨ (p ∧ r) :=
+  ⟨fun h => h.2.elim (fun hq => Or.inl ⟨h.1, hq⟩) (fun hr => Or.inr ⟨h.1, hr⟩),
+   fun h => h.elim (fun hpq => ⟨hpq.1, Or.inl hpq.2⟩) (fun hpr => ⟨hpr.1, Or.inr hpr.2⟩)⟩
+theorem dist_and_right {p q r : Prop} : (q ∨ r) ∧ p ↔ (q ∧ p) ∨ (r ∧ p) :=
+  ⟨fun h => h.1.elim (fun hq => Or.inl ⟨hq, h.2⟩) (fun hr => Or.inr ⟨hr, h.2⟩),
+   fun h => h.elim (fun hqp => ⟨Or.inl hqp.1, hqp.2⟩) (fun hrp => ⟨Or.inr hrp.1, hrp.2⟩)⟩
+theorem dist_or_left {p q r : Prop} : p ∨ (q ∧ r) ↔ (p ∨ q) ∧ (p ∨ r) :=
+  ⟨fun h => h.elim (fun hp => ⟨Or.inl hp, Or.inl hp⟩) (fun hqr => ⟨Or.inr hqr.1, Or.inr hqr.2⟩),
+   fun h => h.1.elim Or.inl (fun hq => h.2.elim Or.inl (fun hr => Or.inr ⟨hq, hr⟩))⟩
+theorem dist_or_right {p q r : Prop} : (q ∧ r) ∨ p ↔ (q ∨ p) ∧ (r ∨ p) :=
+  ⟨fun h => h.elim (fun hqr => ⟨Or.inl hqr.1, Or.inl hqr.2⟩) (fun hp => ⟨Or.inr hp, Or.inr hp⟩),
+   fun h => h.1.elim (fun hq => h.2.elim (fun hr => Or.inl ⟨hq, hr⟩) Or.inr) Or.inr⟩
+theorem contra {p q : Prop} : (p → q) ↔ (¬q → ¬p) :=
+  ⟨fun h hnq hp => hnq (h hp), fun h hp => Classical.byContradiction (fun hnq => h hnq hp)⟩
+theorem impl {p q : Prop} : (p → q) ↔ (¬p ∨ q) :=
+  ⟨fun h => (Classical.em p).elim (fun hp => Or.inr (h hp)) Or.inl,
+   fun h hp => h.elim (fun hnp => absurd hp hnp) id⟩
+theorem exp {p q r : Prop} : (p ∧ q → r) ↔ (p → q → r) :=
+  ⟨fun h hp hq => h ⟨hp, hq⟩, fun h hpq => h hpq.1 hpq.2⟩
+theorem taut_and {p : Prop} : p ↔ p ∧ p := ⟨fun h => ⟨h, h⟩, fun h => h.1⟩
+theorem taut_or {p : Prop} : p ↔ p ∨ p := ⟨Or.inl, fun h => h.elim id id⟩
+theorem equiv {p q : Prop} : (p ↔ q) ↔ ((p → q) ∧ (q → p)) := ⟨fun h => ⟨h.1, h.2⟩, fun h => ⟨h.1, h.2⟩⟩
+
+-- Replacement inside a larger formula
+theorem congr_not {a b : Prop} (h : a ↔ b) : ¬a ↔ ¬b := ⟨fun na hb => na (h.2 hb), fun nb ha => nb (h.1 ha)⟩
+theorem congr_and {a b c d : Prop} (h1 : a ↔ c) (h2 : b ↔ d) : a ∧ b ↔ c ∧ d :=
+  ⟨fun h => ⟨h1.1 h.1, h2.1 h.2⟩, fun h => ⟨h1.2 h.1, h2.2 h.2⟩⟩
+theorem congr_or {a b c d : Prop} (h1 : a ↔ c) (h2 : b ↔ d) : a ∨ b ↔ c ∨ d :=
+  ⟨fun h => h.elim (fun x => Or.inl (h1.1 x)) (fun y => Or.inr (h2.1 y)),
+   fun h => h.elim (fun x => Or.inl (h1.2 x)) (fun y => Or.inr (h2.2 y))⟩
+theorem congr_imp {a b c d : Prop} (h1 : a ↔ c) (h2 : b ↔ d) : (a → b) ↔ (c → d) :=
+  ⟨fun f x => h2.1 (f (h1.2 x)), fun g x => h2.2 (g (h1.1 x))⟩
+theorem congr_iff {a b c d : Prop} (h1 : a ↔ c) (h2 : b ↔ d) : (a ↔ b) ↔ (c ↔ d) :=
+  ⟨fun e => ⟨fun x => h2.1 (e.1 (h1.2 x)), fun y => h1.1 (e.2 (h2.2 y))⟩,
+   fun e => ⟨fun x => h2.2 (e.1 (h1.1 x)), fun y => h1.2 (e.2 (h2.1 y))⟩⟩
+
+end PB"#;
+
+/// Prelude lemma of each inference rule form: cited formulas (in the
+/// lemma's argument order) and conclusion, over the schematic atoms p..s.
+const INFERENCE_LEMMAS: [(InferenceRule, &str, &[&str], &str); 12] = [
+    (InferenceRule::ModusPonens, "mp", &["p -> q", "p"], "q"),
+    (InferenceRule::ModusTollens, "mt", &["p -> q", "~q"], "~p"),
+    (InferenceRule::DisjunctiveSyllogism, "ds_left", &["p | q", "~p"], "q"),
+    (InferenceRule::DisjunctiveSyllogism, "ds_right", &["p | q", "~q"], "p"),
+    (InferenceRule::Simplification, "simp_left", &["p & q"], "p"),
+    (InferenceRule::Simplification, "simp_right", &["p & q"], "q"),
+    (InferenceRule::Conjunction, "conj", &["p", "q"], "p & q"),
+    (InferenceRule::HypotheticalSyllogism, "hs", &["p -> q", "q -> r"], "p -> r"),
+    (InferenceRule::Addition, "add_left", &["p"], "p | q"),
+    (InferenceRule::Addition, "add_right", &["q"], "p | q"),
+    (InferenceRule::ConstructiveDilemma, "cd", &["p | q", "p -> r", "q -> s"], "r | s"),
+    (InferenceRule::Contradiction, "neg_e", &["p", "~p"], "#"),
+];
+
+/// Prelude lemma of each replacement rule form, as `left ↔ right`
+const EQUIVALENCE_LEMMAS: [(EquivalenceRule, &str, &str, &str); 17] = [
+    (EquivalenceRule::DoubleNegation, "dn", "p", "~~p"),
+    (EquivalenceRule::DeMorgan, "dem_and", "~(p & q)", "~p | ~q"),
+    (EquivalenceRule::DeMorgan, "dem_or", "~(p | q)", "~p & ~q"),
+    (EquivalenceRule::Commutation, "comm_and", "p & q", "q & p"),
+    (EquivalenceRule::Commutation, "comm_or", "p | q", "q | p"),
+    (EquivalenceRule::Association, "assoc_and", "(p & q) & r", "p & (q & r)"),
+    (EquivalenceRule::Association, "assoc_or", "(p | q) | r", "p | (q | r)"),
+    (EquivalenceRule::Distribution, "dist_and_left", "p & (q | r)", "(p & q) | (p & r)"),
+    (EquivalenceRule::Distribution, "dist_and_right", "(q | r) & p", "(q & p) | (r & p)"),
+    (EquivalenceRule::Distribution, "dist_or_left", "p | (q & r)", "(p | q) & (p | r)"),
+    (EquivalenceRule::Distribution, "dist_or_right", "(q & r) | p", "(q | p) & (r | p)"),
+    (EquivalenceRule::Contraposition, "contra", "p -> q", "~q -> ~p"),
+    (EquivalenceRule::Implication, "impl", "p -> q", "~p | q"),
+    (EquivalenceRule::Exportation, "exp", "(p & q) -> r", "p -> (q -> r)"),
+    (EquivalenceRule::Tautology, "taut_and", "p", "p & p"),
+    (EquivalenceRule::Tautology, "taut_or", "p", "p | p"),
+    (EquivalenceRule::Equivalence, "equiv", "p <-> q", "(p -> q) & (q -> p)"),
+];
+
+/// Words atoms cannot be named in Lean: keywords, and names the output
+/// uses that a variable would shadow
+const LEAN_RESERVED: [&str; 30] = [
+    "at", "by", "do", "fun", "have", "show", "from", "if", "then", "else", "let", "in", "match",
+    "with", "open", "theorem", "def", "end", "where", "Type", "Prop", "Sort", "PB", "this",
+    "True", "False", "Not", "And", "Or", "Iff",
+];
+
+/// Why a proof cannot be translated
+#[derive(Debug, Clone, PartialEq)]
+pub enum LeanError {
+    /// The line failed verification
+    InvalidLine(usize),
+    /// The line's rule has no prelude lemma matching its formulas
+    UnmatchedStep { line: usize, rule: String },
+    /// The subproof opened at the line is never closed
+    UnclosedSubproof(usize),
+    /// No line outside subproofs is the conclusion
+    MissingConclusion,
+}
+
+impl std::fmt::Display for LeanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LeanError::InvalidLine(line) => write!(f, "line {} does not verify", line),
+            LeanError::UnmatchedStep { line, rule } => {
+                write!(f, "line {}: no {} lemma matches the cited formulas", line, rule)
+            }
+            LeanError::UnclosedSubproof(line) => write!(f, "the subproof at line {} is never closed", line),
+            LeanError::MissingConclusion => write!(f, "the proof never derives the conclusion"),
+        }
+    }
+}
+
+/// Lean names for `atoms`: the atom itself where it is an identifier, else
+/// prefixed with `a_`, with a numeric suffix where two would clash.
+pub fn lean_atom_names(atoms: &BTreeSet<String>) -> BTreeMap<String, String> {
+    let mut taken = HashSet::new();
+    let mut names = BTreeMap::new();
+    for atom in atoms {
+        let hypothesis = atom.strip_prefix('h').is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+        let mut base = atom.clone();
+        if !atom.starts_with(|c: char| c.is_ascii_alphabetic()) || LEAN_RESERVED.contains(&atom.as_str()) || hypothesis {
+            base = format!("a_{}", atom);
+        }
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while !taken.insert(name.clone()) {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        names.insert(atom.clone(), name);
+    }
+    names
+}
+
+/// A Lean declaration name for the theorem `id`
+pub fn lean_theorem_name(id: &str) -> String {
+    let body: String = id.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    format!("thm_{}", body)
+}
+
+/// `formula` in Lean syntax, with binary connectives bracketed below the top.
+pub fn formula_to_lean(formula: &Formula, names: &BTreeMap<String, String>) -> String {
+    let inner = |f: &Formula| bracketed(f, names);
+    match formula {
+        Formula::Atom(name) => names.get(name).cloned().unwrap_or_else(|| name.clone()),
+        Formula::Contradiction => "False".to_string(),
+        Formula::Not(f) => format!("¬{}", inner(f)),
+        Formula::And(l, r) => format!("{} ∧ {}", inner(l), inner(r)),
+        Formula::Or(l, r) => format!("{} ∨ {}", inner(l), inner(r)),
+        Formula::Implies(l, r) => format!("{} → {}", inner(l), inner(r)),
+        Formula::Biconditional(l, r) => format!("{} ↔ {}", inner(l), inner(r)),
+    }
+}
+
+/// `formula` in Lean syntax, bracketed when it has a binary connective
+fn bracketed(formula: &Formula, names: &BTreeMap<String, String>) -> String {
+    match formula {
+        Formula::Atom(_) | Formula::Contradiction | Formula::Not(_) => formula_to_lean(formula, names),
+        _ => format!("({})", formula_to_lean(formula, names)),
+    }
+}
+
+/// `theorem name (P Q : Prop) (h1 : premise) ... : conclusion`
+fn statement(name: &str, theorem: &Theorem, atoms: &BTreeMap<String, String>) -> String {
+    let mut parts = vec![format!("theorem {}", name)];
+    if !atoms.is_empty() {
+        let variables: Vec<&str> = atoms.values().map(|n| n.as_str()).collect();
+        parts.push(format!("({} : Prop)", variables.join(" ")));
+    }
+    for (i, premise) in theorem.premises.iter().enumerate() {
+        parts.push(format!("(h{} : {})", i + 1, formula_to_lean(premise, atoms)));
+    }
+    format!("{} : {}", parts.join(" "), formula_to_lean(&theorem.conclusion, atoms))
+}
+
+fn theorem_atoms<'a>(formulas: impl Iterator<Item = &'a Formula>) -> BTreeMap<String, String> {
+    lean_atom_names(&formulas.flat_map(|f| f.atoms()).collect())
+}
+
+/// `theorem` as a Lean statement left to prove (`sorry`).
+pub fn theorem_to_lean(name: &str, theorem: &Theorem) -> String {
+    let atoms = theorem_atoms(theorem.premises.iter().chain(std::iter::once(&theorem.conclusion)));
+    format!("{} := by\n  sorry", statement(name, theorem, &atoms))
+}
+
+/// A verified `proof` as a Lean theorem proved by a tactic script. Line `n`
+/// becomes hypothesis `hn`; the premises are the theorem's hypotheses.
+pub fn proof_to_lean(name: &str, proof: &Proof) -> Result<String, LeanError> {
+    if let Some(line) = proof.lines.iter().find(|l| !l.is_valid) {
+        return Err(LeanError::InvalidLine(line.line_number));
+    }
+    let theorem = &proof.theorem;
+    let atoms = theorem_atoms(
+        theorem.premises.iter()
+            .chain(std::iter::once(&theorem.conclusion))
+            .chain(proof.lines.iter().map(|l| &l.formula)),
+    );
+    let last = proof.lines.iter()
+        .rev()
+        .find(|l| l.depth == 0 && l.formula == theorem.conclusion)
+        .ok_or(LeanError::MissingConclusion)?;
+
+    let mut out = vec![format!("{} := by", statement(name, theorem, &atoms))];
+    let translator = Translator { proof, atoms: &atoms };
+    translator.block(0, proof.lines.len(), 1, &mut out)?;
+    out.push(format!("  exact h{}", last.line_number));
+    Ok(out.join("\n"))
+}
+
+struct Translator<'a> {
+    proof: &'a Proof,
+    atoms: &'a BTreeMap<String, String>,
+}
+
+impl Translator<'_> {
+    fn lean(&self, formula: &Formula) -> String {
+        formula_to_lean(formula, self.atoms)
+    }
+
+    fn formula(&self, line: usize) -> &Formula {
+        &self.proof.lines[line - 1].formula
+    }
+
+    /// Translate lines `from..to` (0-based indices) at `indent`.
+    fn block(&self, from: usize, to: usize, indent: usize, out: &mut Vec<String>) -> Result<(), LeanError> {
+        let pad = "  ".repeat(indent);
+        let mut i = from;
+        while i < to {
+            let line = &self.proof.lines[i];
+            let n = line.line_number;
+            let have = format!("{}have h{} : {} :=", pad, n, self.lean(&line.formula));
+            match &line.justification {
+                Justification::Premise => {
+                    let premises = &self.proof.theorem.premises;
+                    if premises.get(i) != Some(&line.formula) {
+                        let j = premises.iter().position(|p| *p == line.formula)
+                            .ok_or(LeanError::InvalidLine(n))?;
+                        out.push(format!("{} h{}", have, j + 1));
+                    }
+                }
+                Justification::Inference { rule, lines } => {
+                    let term = self.inference(*rule, lines, &line.formula)
+                        .ok_or_else(|| LeanError::UnmatchedStep { line: n, rule: rule.abbreviation().to_string() })?;
+                    out.push(format!("{} {}", have, term));
+                }
+                Justification::Equivalence { rule, line: cited } => {
+                    let from = self.formula(*cited);
+                    let iff = rewrite(*rule, from, &line.formula)
+                        .ok_or_else(|| LeanError::UnmatchedStep { line: n, rule: rule.abbreviation().to_string() })?;
+                    out.push(format!(
+                        "{} (show {} ↔ {} from {}).mp h{}",
+                        have, bracketed(from, self.atoms), bracketed(&line.formula, self.atoms), iff, cited
+                    ));
+                }
+                Justification::Assumption { technique } => {
+                    let close = self.proof.lines.iter().position(|l| matches!(
+                        l.justification,
+                        Justification::SubproofConclusion { subproof_start, .. } if subproof_start == n
+                    ));
+                    let Some(close) = close.filter(|&c| c > i && c < to) else {
+                        return Err(LeanError::UnclosedSubproof(n));
+                    };
+                    let conclusion = &self.proof.lines[close];
+                    let Justification::SubproofConclusion { subproof_end, .. } = conclusion.justification else {
+                        unreachable!("found by its justification");
+                    };
+                    if subproof_end != close {
+                        return Err(LeanError::UnclosedSubproof(n));
+                    }
+                    out.push(format!("{}have h{} : {} := by", pad, conclusion.line_number, self.lean(&conclusion.formula)));
+                    let inner = "  ".repeat(indent + 1);
+                    if *technique == ProofTechnique::IndirectProof && line.formula.is_negation() {
+                        out.push(format!("{}apply PB.ip", inner));
+                    }
+                    out.push(format!("{}intro h{}", inner, n));
+                    self.block(i + 1, subproof_end, indent + 1, out)?;
+                    let end = self.formula(subproof_end);
+                    let exact = match technique {
+                        ProofTechnique::ConditionalProof => format!("h{}", subproof_end),
+                        ProofTechnique::IndirectProof => match end {
+                            Formula::And(l, r) if r.negated_inner() == Some(l.as_ref()) => {
+                                format!("PB.contra_and h{}", subproof_end)
+                            }
+                            Formula::And(..) => format!("PB.contra_and_rev h{}", subproof_end),
+                            _ => format!("h{}", subproof_end),
+                        },
+                    };
+                    out.push(format!("{}exact {}", inner, exact));
+                    i = close + 1;
+                    continue;
+                }
+                Justification::SubproofConclusion { subproof_start, .. } => {
+                    return Err(LeanError::UnclosedSubproof(*subproof_start));
+                }
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+
+    /// The lemma application deriving `conclusion` from the cited lines,
+    /// trying the lemma forms of `rule` over every order of the citations.
+    fn inference(&self, rule: InferenceRule, cited: &[usize], conclusion: &Formula) -> Option<String> {
+        for (_, lemma, premises, result) in INFERENCE_LEMMAS.iter().filter(|(r, ..)| *r == rule) {
+            if premises.len() != cited.len() {
+                continue;
+            }
+            for order in permutations(cited.len()) {
+                let mut bindings = HashMap::new();
+                let result = schema(result);
+                let matched = bind(&result, conclusion, &mut bindings)
+                    && premises.iter().zip(&order).all(|(p, &k)| bind(&schema(p), self.formula(cited[k]), &mut bindings));
+                if matched {
+                    let args: Vec<String> = order.iter().map(|&k| format!("h{}", cited[k])).collect();
+                    return Some(format!("PB.{} {}", lemma, args.join(" ")));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// All orders of `0..n`
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![Vec::new()];
+    }
+    let mut result = Vec::new();
+    for rest in permutations(n - 1) {
+        for at in 0..=rest.len() {
+            let mut order = rest.clone();
+            order.insert(at, n - 1);
+            result.push(order);
+        }
+    }
+    result
+}
+
+fn schema(text: &str) -> Formula {
+    Formula::parse(text).expect("lemma schemas parse")
+}
+
+/// Match `formula` against `pattern`, whose atoms are schematic variables,
+/// extending `bindings` consistently.
+fn bind<'a>(pattern: &Formula, formula: &'a Formula, bindings: &mut HashMap<String, &'a Formula>) -> bool {
+    match (pattern, formula) {
+        (Formula::Atom(var), _) => match bindings.get(var) {
+            Some(bound) => *bound == formula,
+            None => {
+                bindings.insert(var.clone(), formula);
+                true
+            }
+        },
+        (Formula::Contradiction, Formula::Contradiction) => true,
+        (Formula::Not(p), Formula::Not(f)) => bind(p, f, bindings),
+        (Formula::And(pl, pr), Formula::And(fl, fr))
+        | (Formula::Or(pl, pr), Formula::Or(fl, fr))
+        | (Formula::Implies(pl, pr), Formula::Implies(fl, fr))
+        | (Formula::Biconditional(pl, pr), Formula::Biconditional(fl, fr)) => {
+            bind(pl, fl, bindings) && bind(pr, fr, bindings)
+        }
+        _ => false,
+    }
+}
+
+/// A proof of `from ↔ to` by `rule`: a lemma where it applies to the whole
+/// formula, else congruence over the parts that differ. This covers
+/// replacing one occurrence of a subformula and replacing all of them.
+fn rewrite(rule: EquivalenceRule, from: &Formula, to: &Formula) -> Option<String> {
+    if from == to {
+        return Some("Iff.rfl".to_string());
+    }
+    for (_, lemma, left, right) in EQUIVALENCE_LEMMAS.iter().filter(|(r, ..)| *r == rule) {
+        let (left, right) = (schema(left), schema(right));
+        let mut bindings = HashMap::new();
+        if bind(&left, from, &mut bindings) && bind(&right, to, &mut bindings) {
+            return Some(format!("PB.{}", lemma));
+        }
+        let mut bindings = HashMap::new();
+        if bind(&left, to, &mut bindings) && bind(&right, from, &mut bindings) {
+            return Some(format!("Iff.symm PB.{}", lemma));
+        }
+    }
+    let arg = |term: String| if term.contains(' ') { format!("({})", term) } else { term };
+    let pair = |congr: &str, l1, r1, l2, r2| {
+        Some(format!("PB.{} {} {}", congr, arg(rewrite(rule, l1, l2)?), arg(rewrite(rule, r1, r2)?)))
+    };
+    match (from, to) {
+        (Formula::Not(a), Formula::Not(b)) => Some(format!("PB.congr_not {}", arg(rewrite(rule, a, b)?))),
+        (Formula::And(l1, r1), Formula::And(l2, r2)) => pair("congr_and", l1, r1, l2, r2),
+        (Formula::Or(l1, r1), Formula::Or(l2, r2)) => pair("congr_or", l1, r1, l2, r2),
+        (Formula::Implies(l1, r1), Formula::Implies(l2, r2)) => pair("congr_imp", l1, r1, l2, r2),
+        (Formula::Biconditional(l1, r1), Formula::Biconditional(l2, r2)) => pair("congr_iff", l1, r1, l2, r2),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Difficulty;
+    use crate::services::{ProofNode, ProofTree, ProofVerifier};
+
+    fn parse(s: &str) -> Formula {
+        Formula::parse(s).unwrap()
+    }
+
+    fn theorem(premises: &[&str], conclusion: &str) -> Theorem {
+        Theorem::new(premises.iter().map(|p| parse(p)).collect(), parse(conclusion), Difficulty::Easy, None, None)
+    }
+
+    #[test]
+    fn test_statement() {
+        let text = theorem_to_lean("thm_easy_1", &theorem(&["P -> (Q & ~R)", "~~P"], "~R | #"));
+        assert_eq!(
+            text,
+            "theorem thm_easy_1 (P Q R : Prop) (h1 : P → (Q ∧ ¬R)) (h2 : ¬¬P) : ¬R ∨ False := by\n  sorry"
+        );
+        assert_eq!(lean_theorem_name("v1-001"), "thm_v1_001");
+    }
+
+    #[test]
+    fn test_atom_names() {
+        let atoms: BTreeSet<String> = ["P", "h1", "fun", "False", "1", "a_1"].iter().map(|s| s.to_string()).collect();
+        let names = lean_atom_names(&atoms);
+        assert_eq!(names["P"], "P");
+        assert_eq!(names["h1"], "a_h1");
+        assert_eq!(names["fun"], "a_fun");
+        assert_eq!(names["False"], "a_False");
+        assert_ne!(names["1"], names["a_1"]);
+    }
+
+    #[test]
+    fn test_inference_steps_match_any_citation_order() {
+        let mut proof = Proof::new(theorem(&["P", "P -> Q", "~R"], "Q & ~R"));
+        proof.add_line(parse("Q"), Justification::Inference { rule: InferenceRule::ModusPonens, lines: vec![1, 2] });
+        proof.add_line(parse("Q & ~R"), Justification::Inference { rule: InferenceRule::Conjunction, lines: vec![4, 3] });
+        ProofVerifier::verify_proof(&mut proof);
+        let lean = proof_to_lean("thm", &proof).unwrap();
+        assert!(lean.contains("  have h4 : Q := PB.mp h2 h1\n"));
+        assert!(lean.contains("  have h5 : Q ∧ ¬R := PB.conj h4 h3\n"));
+        assert!(lean.ends_with("  exact h5"));
+    }
+
+    #[test]
+    fn test_replacement_inside_formula() {
+        let mut proof = Proof::new(theorem(&["~(P & Q) -> R"], "(~P | ~Q) -> R"));
+        proof.add_line(parse("(~P | ~Q) -> R"), Justification::Equivalence { rule: EquivalenceRule::DeMorgan, line: 1 });
+        proof.add_line(parse("~(P & Q) -> R"), Justification::Equivalence { rule: EquivalenceRule::DeMorgan, line: 2 });
+        ProofVerifier::verify_proof(&mut proof);
+        let lean = proof_to_lean("thm", &proof).unwrap();
+        assert!(lean.contains("(show (¬(P ∧ Q) → R) ↔ ((¬P ∨ ¬Q) → R) from PB.congr_imp PB.dem_and Iff.rfl).mp h1"));
+        assert!(lean.contains("from PB.congr_imp (Iff.symm PB.dem_and) Iff.rfl).mp h2"));
+
+        let all = rewrite(EquivalenceRule::DoubleNegation, &parse("P & (P | Q)"), &parse("~~P & (~~P | Q)"));
+        assert_eq!(all.unwrap(), "PB.congr_and PB.dn (PB.congr_or PB.dn Iff.rfl)");
+        assert_eq!(rewrite(EquivalenceRule::Commutation, &parse("P & Q"), &parse("P | Q")), None);
+    }
+
+    #[test]
+    fn test_subproofs_become_nested_blocks() {
+        // P ⊃ Q ⊢ ~Q ⊃ ~P: CP around an IP
+        let mut proof = Proof::new(theorem(&["P -> Q"], "~Q -> ~P"));
+        proof.open_subproof(parse("~Q"), ProofTechnique::ConditionalProof);
+        proof.open_subproof(parse("P"), ProofTechnique::IndirectProof);
+        proof.add_line(parse("Q"), Justification::Inference { rule: InferenceRule::ModusPonens, lines: vec![1, 3] });
+        proof.add_line(parse("Q & ~Q"), Justification::Inference { rule: InferenceRule::Conjunction, lines: vec![4, 2] });
+        proof.close_subproof(parse("~P"), ProofTechnique::IndirectProof);
+        proof.close_subproof(parse("~Q -> ~P"), ProofTechnique::ConditionalProof);
+        ProofVerifier::verify_proof(&mut proof);
+        let lean = proof_to_lean("thm", &proof).unwrap();
+        let expected = [
+            "theorem thm (P Q : Prop) (h1 : P → Q) : ¬Q → ¬P := by",
+            "  have h7 : ¬Q → ¬P := by",
+            "    intro h2",
+            "    have h6 : ¬P := by",
+            "      intro h3",
+            "      have h4 : Q := PB.mp h1 h3",
+            "      have h5 : Q ∧ ¬Q := PB.conj h4 h2",
+            "      exact PB.contra_and h5",
+            "    exact h6",
+            "  exact h7",
+        ];
+        assert_eq!(lean, expected.join("\n"));
+    }
+
+    #[test]
+    fn test_indirect_proof_of_unnegated_conclusion() {
+        let tree = ProofTree::new(ProofNode::derivation(
+            parse("P"),
+            "IP",
+            vec![
+                ProofNode::assumption(parse("~P")),
+                ProofNode::derivation(
+                    parse("#"),
+                    "NegE",
+                    vec![ProofNode::premise(parse("~~P")), ProofNode::assumption(parse("~P"))],
+                    None,
+                ),
+            ],
+            Some(parse("~P")),
+        ));
+        let mut proof = tree.to_proof(theorem(&["~~P"], "P")).unwrap();
+        ProofVerifier::verify_proof(&mut proof);
+        let lean = proof_to_lean("thm", &proof).unwrap();
+        assert!(lean.contains("apply PB.ip\n    intro h2\n"));
+        assert!(lean.contains("PB.neg_e h2 h1"));
+    }
+
+    #[test]
+    fn test_invalid_proofs_are_not_translated() {
+        let mut proof = Proof::new(theorem(&["P -> Q", "Q"], "P"));
+        proof.add_line(parse("P"), Justification::Inference { rule: InferenceRule::ModusPonens, lines: vec![1, 2] });
+        ProofVerifier::verify_proof(&mut proof);
+        assert_eq!(proof_to_lean("thm", &proof), Err(LeanError::InvalidLine(3)));
+
+        let proof = Proof::new(theorem(&["P", "Q"], "P & Q"));
+        assert_eq!(proof_to_lean("thm", &proof), Err(LeanError::MissingConclusion));
+    }
+
+    /// Needs `lean` on the PATH: `cargo test -p logic-proof-trainer -- --ignored lean`.
+    #[test]
+    #[ignore]
+    fn test_lean_accepts_exported_proofs() {
+        use crate::services::{ProofTreeGenerator, TreeGenConfig};
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut text = LEAN_PRELUDE.to_string();
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut count = 0;
+        for (difficulty, cp, case_split) in [(30, false, false), (30, true, false), (30, false, true), (60, true, true), (90, false, false)] {
+            let mut config = TreeGenConfig::for_difficulty_value(difficulty);
+            config.require_forces_cp = cp;
+            config.require_forces_case_split = case_split;
+            let mut gen = ProofTreeGenerator::new(config);
+            for _ in 0..3 {
+                let tree = gen.generate_with_rng(&mut rng);
+                let thm = Theorem::new(tree.premises(), tree.conclusion().clone(), Difficulty::Easy, None, None);
+                let mut proof = tree.to_proof(thm).unwrap();
+                ProofVerifier::verify_proof(&mut proof);
+                count += 1;
+                text.push_str(&format!("\n{}\n", proof_to_lean(&format!("thm_{}", count), &proof).unwrap()));
+            }
+        }
+
+        let path = std::env::temp_dir().join(format!("propbench_lean_{}.lean", std::process::id()));
+        std::fs::write(&path, text).unwrap();
+        let output = std::process::Command::new("lean").arg(&path).output().expect("lean not found");
+        std::fs::remove_file(&path).ok();
+        assert!(
+            output.status.success(),
+            "lean rejected the export:\n{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
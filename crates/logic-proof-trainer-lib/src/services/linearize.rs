@@ -0,0 +1,367 @@
+//! Flattening of `ProofTree`s into line-numbered `Proof`s.
+//!
+//! Every derivation node becomes one or more proof lines, subproof rules open
+//! and close scopes, and leaves are resolved to existing premise or assumption
+//! lines. The result can be checked with `ProofVerifier`, which makes the
+//! tree generators' output usable as reference proofs.
+//!
+//! Equivalence rules, named by abbreviation, become one replacement line.
+//!
+//! Rules without a direct counterpart in the line-based system are expanded:
+//! - `CaseSplit` becomes two conditional subproofs, CD, then Taut.
+//! - A subproof whose last line is not the formula it proves gets that formula
+//!   restated via Taut (`B` to `B · B` to `B`), since there is no reiteration rule.
+
+use crate::models::{
+    Formula, Justification, Proof, Theorem,
+    rules::{EquivalenceRule, InferenceRule, ProofTechnique},
+};
+use super::proof_tree::{ProofNode, ProofTree};
+
+/// Why a proof tree could not be linearised
+#[derive(Debug, Clone)]
+pub enum LinearizeError {
+    /// The rule name has no line-based counterpart
+    UnknownRule(String),
+    /// A derivation has the wrong number or shape of children
+    MalformedNode { rule: String, reason: String },
+    /// A premise leaf that is not among the theorem's premises
+    MissingPremise(Formula),
+    /// An assumption leaf outside any subproof that assumes it
+    UnboundAssumption(Formula),
+}
+
+impl std::fmt::Display for LinearizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinearizeError::UnknownRule(rule) => write!(f, "Unknown proof tree rule '{}'", rule),
+            LinearizeError::MalformedNode { rule, reason } => write!(f, "Malformed {} node: {}", rule, reason),
+            LinearizeError::MissingPremise(formula) => {
+                write!(f, "Premise {} is not a premise of the theorem", formula.display_string())
+            }
+            LinearizeError::UnboundAssumption(formula) => {
+                write!(f, "Assumption {} is used outside its subproof", formula.display_string())
+            }
+        }
+    }
+}
+
+/// Map a tree rule name to the inference rule it applies, if it is a plain inference.
+fn inference_rule(rule: &str) -> Option<InferenceRule> {
+    match rule {
+        "MP" => Some(InferenceRule::ModusPonens),
+        "MT" => Some(InferenceRule::ModusTollens),
+        "HS" => Some(InferenceRule::HypotheticalSyllogism),
+        "DS" => Some(InferenceRule::DisjunctiveSyllogism),
+        "Simp" => Some(InferenceRule::Simplification),
+        "Conj" => Some(InferenceRule::Conjunction),
+        "Add" => Some(InferenceRule::Addition),
+        "CD" => Some(InferenceRule::ConstructiveDilemma),
+        "NegE" => Some(InferenceRule::Contradiction),
+        _ => None,
+    }
+}
+
+/// Map a tree rule name to the equivalence rule it applies to its single
+/// child, by abbreviation (as equivalence-proof solutions name them).
+fn equivalence_rule(rule: &str) -> Option<EquivalenceRule> {
+    EquivalenceRule::all().into_iter().find(|equivalence| equivalence.abbreviation() == rule)
+}
+
+impl ProofTree {
+    /// Linearise this tree into a proof of `theorem`. The theorem's premises
+    /// must include every premise leaf (e.g. a theorem built from `premises()`).
+    pub fn to_proof(&self, theorem: Theorem) -> Result<Proof, LinearizeError> {
+        let mut linearizer = Linearizer {
+            proof: Proof::new(theorem),
+            open_assumptions: Vec::new(),
+        };
+        let line = linearizer.emit(&self.root)?;
+        linearizer.conclude(line, self.root.formula());
+        Ok(linearizer.proof)
+    }
+}
+
+struct Linearizer {
+    proof: Proof,
+    /// Assumptions of the currently open subproofs with their line numbers, innermost last
+    open_assumptions: Vec<(Formula, usize)>,
+}
+
+impl Linearizer {
+    /// Emit lines for `node` and return the line number establishing its formula.
+    fn emit(&mut self, node: &ProofNode) -> Result<usize, LinearizeError> {
+        match node {
+            ProofNode::Premise(formula) => self.premise_line(formula)
+                .ok_or_else(|| LinearizeError::MissingPremise(formula.clone())),
+            ProofNode::Assumption(formula) => self.open_assumptions.iter().rev()
+                .find(|(assumed, _)| assumed == formula)
+                .map(|(_, line)| *line)
+                .or_else(|| self.premise_line(formula))
+                .ok_or_else(|| LinearizeError::UnboundAssumption(formula.clone())),
+            ProofNode::Derivation { result, rule, children, .. } => {
+                if let Some(inference) = inference_rule(rule) {
+                    if children.len() != inference.premise_count() {
+                        return Err(malformed(rule, format!(
+                            "expected {} children, found {}", inference.premise_count(), children.len()
+                        )));
+                    }
+                    let mut lines = Vec::with_capacity(children.len());
+                    for child in children {
+                        lines.push(self.emit(child)?);
+                    }
+                    return Ok(self.add(result.clone(), Justification::Inference { rule: inference, lines }));
+                }
+                if let Some(equivalence) = equivalence_rule(rule) {
+                    let [child] = children.as_slice() else {
+                        return Err(malformed(rule, format!("expected 1 child, found {}", children.len())));
+                    };
+                    let line = self.emit(child)?;
+                    return Ok(self.add(result.clone(), Justification::Equivalence { rule: equivalence, line }));
+                }
+                match rule.as_str() {
+                    "CP" => {
+                        let [assumption, body] = children.as_slice() else {
+                            return Err(malformed(rule, "expected an assumption and a subproof"));
+                        };
+                        self.subproof(assumption.formula(), body, ProofTechnique::ConditionalProof, result)
+                    }
+                    "IP" | "NegIntro" => {
+                        let [assumption, body] = children.as_slice() else {
+                            return Err(malformed(rule, "expected an assumption and a subproof"));
+                        };
+                        self.subproof(assumption.formula(), body, ProofTechnique::IndirectProof, result)
+                    }
+                    "CaseSplit" => self.case_split(rule, children, result),
+                    _ => Err(LinearizeError::UnknownRule(rule.clone())),
+                }
+            }
+        }
+    }
+
+    /// `[assume left, derive C], [assume right, derive C]` from `left ∨ right`,
+    /// as CD over the two discharged conditionals followed by Taut on `C ∨ C`.
+    fn case_split(&mut self, rule: &str, children: &[ProofNode], result: &Formula) -> Result<usize, LinearizeError> {
+        let [disjunction, left, left_case, right, right_case] = children else {
+            return Err(malformed(rule, "expected a disjunction and two assumption/case pairs"));
+        };
+        let expected = Formula::Or(Box::new(left.formula().clone()), Box::new(right.formula().clone()));
+        if *disjunction.formula() != expected {
+            return Err(malformed(rule, format!(
+                "disjunction {} does not match the cases", disjunction.formula().display_string()
+            )));
+        }
+
+        let disjunction_line = self.emit(disjunction)?;
+        let left_line = self.subproof(left.formula(), left_case, ProofTechnique::ConditionalProof,
+            &Formula::Implies(Box::new(left.formula().clone()), Box::new(result.clone())))?;
+        let right_line = self.subproof(right.formula(), right_case, ProofTechnique::ConditionalProof,
+            &Formula::Implies(Box::new(right.formula().clone()), Box::new(result.clone())))?;
+
+        let both = self.add(
+            Formula::Or(Box::new(result.clone()), Box::new(result.clone())),
+            Justification::Inference {
+                rule: InferenceRule::ConstructiveDilemma,
+                lines: vec![disjunction_line, left_line, right_line],
+            },
+        );
+        Ok(self.add(result.clone(), Justification::Equivalence { rule: EquivalenceRule::Tautology, line: both }))
+    }
+
+    /// Open a subproof on `assumption`, prove `body` inside it and close it
+    /// with `technique`, restating the closed formula as `result` via DN if
+    /// the technique produces the other polarity (NegIntro on a negation).
+    fn subproof(
+        &mut self,
+        assumption: &Formula,
+        body: &ProofNode,
+        technique: ProofTechnique,
+        result: &Formula,
+    ) -> Result<usize, LinearizeError> {
+        let start = self.proof.open_subproof(assumption.clone(), technique).line_number;
+        self.open_assumptions.push((assumption.clone(), start));
+        let body_line = self.emit(body)?;
+        let end = self.conclude(body_line, body.formula());
+        self.open_assumptions.pop();
+
+        let derived = self.proof.lines[end - 1].formula.clone();
+        let closed = technique.get_conclusion(assumption, &derived)
+            .ok_or_else(|| malformed(technique.abbreviation(), format!(
+                "subproof ends in {}, not a contradiction", derived.display_string()
+            )))?;
+        let line = self.proof.close_subproof(closed.clone(), technique)
+            .expect("subproof was just opened")
+            .line_number;
+
+        if closed == *result {
+            Ok(line)
+        } else {
+            Ok(self.add(result.clone(), Justification::Equivalence { rule: EquivalenceRule::DoubleNegation, line }))
+        }
+    }
+
+    /// Make `formula` (established at `line`) the last line, restating it if needed.
+    fn conclude(&mut self, line: usize, formula: &Formula) -> usize {
+        if line == self.proof.current_line_number() {
+            return line;
+        }
+        let doubled = self.add(
+            Formula::And(Box::new(formula.clone()), Box::new(formula.clone())),
+            Justification::Equivalence { rule: EquivalenceRule::Tautology, line },
+        );
+        self.add(formula.clone(), Justification::Equivalence { rule: EquivalenceRule::Tautology, line: doubled })
+    }
+
+    fn premise_line(&self, formula: &Formula) -> Option<usize> {
+        self.proof.lines.iter()
+            .find(|l| matches!(l.justification, Justification::Premise) && l.formula == *formula)
+            .map(|l| l.line_number)
+    }
+
+    fn add(&mut self, formula: Formula, justification: Justification) -> usize {
+        self.proof.add_line(formula, justification).line_number
+    }
+}
+
+fn malformed(rule: &str, reason: impl Into<String>) -> LinearizeError {
+    LinearizeError::MalformedNode { rule: rule.to_string(), reason: reason.into() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::theorem::Difficulty;
+    use crate::services::tree_gen::{FallbackTemplates, ProofTreeGenerator, TreeGenConfig};
+    use crate::services::verifier::ProofVerifier;
+
+    fn atom(name: &str) -> Formula {
+        Formula::Atom(name.to_string())
+    }
+
+    fn not(f: Formula) -> Formula {
+        Formula::Not(Box::new(f))
+    }
+
+    fn implies(a: Formula, b: Formula) -> Formula {
+        Formula::Implies(Box::new(a), Box::new(b))
+    }
+
+    /// Linearise `tree`, verify every line and require the proof to be complete.
+    fn assert_verifies(tree: &ProofTree) {
+        let theorem = Theorem::new(tree.premises(), tree.conclusion().clone(), Difficulty::Medium, None, None);
+        let mut proof = tree.to_proof(theorem)
+            .unwrap_or_else(|e| panic!("{}\n{}", e, tree.pretty_print()));
+        ProofVerifier::verify_proof(&mut proof);
+        let invalid: Vec<String> = proof.lines.iter()
+            .filter(|l| !l.is_valid)
+            .map(|l| format!("{}: {} [{}] {}", l.line_number, l.formula.display_string(),
+                l.justification.display_string(), l.validation_message.clone().unwrap_or_default()))
+            .collect();
+        assert!(invalid.is_empty(), "invalid lines {:?}\n{}", invalid, tree.pretty_print());
+        assert!(proof.check_complete(), "proof incomplete\n{}", tree.pretty_print());
+    }
+
+    #[test]
+    fn test_modus_ponens_tree() {
+        let tree = ProofTree::new(ProofNode::derivation(
+            atom("Q"),
+            "MP",
+            vec![ProofNode::premise(implies(atom("P"), atom("Q"))), ProofNode::premise(atom("P"))],
+            None,
+        ));
+        let theorem = Theorem::new(tree.premises(), atom("Q"), Difficulty::Easy, None, None);
+        let proof = tree.to_proof(theorem).unwrap();
+        assert_eq!(proof.lines.len(), 3);
+        assert!(matches!(proof.lines[2].justification,
+            Justification::Inference { rule: InferenceRule::ModusPonens, ref lines } if *lines == vec![1, 2]));
+        assert_verifies(&tree);
+    }
+
+    #[test]
+    fn test_reiterates_premise_inside_subproof() {
+        // Q ⊢ P ⊃ Q: the CP body is the premise itself
+        let tree = ProofTree::new(ProofNode::derivation(
+            implies(atom("P"), atom("Q")),
+            "CP",
+            vec![ProofNode::assumption(atom("P")), ProofNode::premise(atom("Q"))],
+            Some(atom("P")),
+        ));
+        assert_verifies(&tree);
+    }
+
+    #[test]
+    fn test_neg_intro_on_negation_restated() {
+        // ~P ⊃ Q, ~P ⊃ ~Q ⊢ ~~P via NegIntro on ~P
+        let contra = ProofNode::derivation(Formula::Contradiction, "NegE", vec![
+            ProofNode::derivation(atom("Q"), "MP", vec![
+                ProofNode::premise(implies(not(atom("P")), atom("Q"))),
+                ProofNode::assumption(not(atom("P"))),
+            ], None),
+            ProofNode::derivation(not(atom("Q")), "MP", vec![
+                ProofNode::premise(implies(not(atom("P")), not(atom("Q")))),
+                ProofNode::assumption(not(atom("P"))),
+            ], None),
+        ], None);
+        let tree = ProofTree::new(ProofNode::derivation(
+            not(not(atom("P"))),
+            "NegIntro",
+            vec![ProofNode::assumption(not(atom("P"))), contra],
+            Some(not(atom("P"))),
+        ));
+        assert_verifies(&tree);
+    }
+
+    #[test]
+    fn test_unknown_rule_rejected() {
+        let tree = ProofTree::new(ProofNode::derivation(atom("P"), "Magic", vec![], None));
+        let theorem = Theorem::new(vec![], atom("P"), Difficulty::Easy, None, None);
+        assert!(matches!(tree.to_proof(theorem), Err(LinearizeError::UnknownRule(_))));
+    }
+
+    #[test]
+    fn test_fallback_templates_verify() {
+        let (a, b, c, d, e) = (atom("P"), atom("Q"), atom("R"), atom("S"), atom("T"));
+        let templates = vec![
+            FallbackTemplates::build_case_split_variant_1(&a, &b, &c, &d),
+            FallbackTemplates::build_case_split_variant_2(&a, &b, &c, &d),
+            FallbackTemplates::build_case_split_variant_3(&a, &b, &c, &d),
+            FallbackTemplates::build_case_split_variant_4(&a, &b, &c, &d, &e),
+            FallbackTemplates::build_cp_case_split_variant_1(&a, &b, &c, &d),
+            FallbackTemplates::build_cp_variant_1(&a, &b, &c, &d),
+            FallbackTemplates::build_cp_variant_2(&a, &b, &c),
+            FallbackTemplates::build_cp_variant_3(&a, &b, &c, &d),
+            FallbackTemplates::build_basic_variant_1(&a, &b, &c),
+            FallbackTemplates::build_basic_variant_2(&a, &b, &c),
+            FallbackTemplates::build_basic_variant_3(&a, &b, &c),
+        ];
+        for tree in &templates {
+            assert_verifies(tree);
+        }
+    }
+
+    #[test]
+    fn test_generated_trees_verify() {
+        for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard, Difficulty::Expert] {
+            let mut generator = ProofTreeGenerator::with_difficulty(difficulty);
+            for _ in 0..10 {
+                assert_verifies(&generator.generate());
+            }
+        }
+    }
+
+    #[test]
+    fn test_generated_technique_trees_verify() {
+        let forced = [(true, false, false), (false, true, false), (false, false, true), (true, true, false)];
+        for (cp, case_split, ip) in forced {
+            let mut config = TreeGenConfig::for_difficulty_value(60);
+            config.require_forces_cp = cp;
+            config.require_forces_case_split = case_split;
+            config.require_forces_ip = ip;
+            config.max_nesting = config.max_nesting.max(2);
+            let mut generator = ProofTreeGenerator::new(config);
+            for _ in 0..5 {
+                assert_verifies(&generator.generate());
+            }
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! Structural and proof-theoretic metrics for a single theorem.
+//!
+//! Used by the `analyze` CLI command to describe what a generated theorem
+//! actually looks like, independently of the spec that produced it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::models::Formula;
+use super::obfuscate_gen::ObfuscateGenerator;
+use super::proof_search::minimum_proof_steps;
+use super::truth_table::{forces_case_split, forces_cp, forces_ip};
+
+/// Atoms the u32 truth-table engine (and therefore proof search and the
+/// forcing checks) handles exactly. Other atoms alias to P's column.
+const STANDARD_ATOMS: [&str; 5] = ["P", "Q", "R", "S", "T"];
+
+/// Proof search is only attempted on theorems at most this many nodes in total.
+pub const MAX_SEARCH_NODES: usize = 40;
+
+/// Depth bound passed to `minimum_proof_steps`.
+pub const MAX_SEARCH_DEPTH: usize = 6;
+
+/// Count of each connective across a theorem's premises and conclusion.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectiveHistogram {
+    pub not: usize,
+    pub and: usize,
+    pub or: usize,
+    pub implies: usize,
+    pub biconditional: usize,
+    pub contradiction: usize,
+}
+
+impl ConnectiveHistogram {
+    fn add(&mut self, formula: &Formula) {
+        match formula {
+            Formula::Atom(_) => {}
+            Formula::Contradiction => self.contradiction += 1,
+            Formula::Not(inner) => {
+                self.not += 1;
+                self.add(inner);
+            }
+            Formula::And(l, r) => {
+                self.and += 1;
+                self.add(l);
+                self.add(r);
+            }
+            Formula::Or(l, r) => {
+                self.or += 1;
+                self.add(l);
+                self.add(r);
+            }
+            Formula::Implies(l, r) => {
+                self.implies += 1;
+                self.add(l);
+                self.add(r);
+            }
+            Formula::Biconditional(l, r) => {
+                self.biconditional += 1;
+                self.add(l);
+                self.add(r);
+            }
+        }
+    }
+}
+
+/// Metrics for one theorem. Sizes are summed over premises and conclusion;
+/// depths are the maximum over them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TheoremMetrics {
+    pub node_count: usize,
+    pub depth: usize,
+    pub atom_count: usize,
+    pub connectives: ConnectiveHistogram,
+    /// Most `~` on any root-to-leaf path (not necessarily consecutive)
+    pub negation_depth: usize,
+    /// Length of the bracketed ASCII rendering shown to models
+    pub ascii_length: usize,
+    /// Rough token estimate: one token per atom, connective or bracket
+    pub estimated_tokens: usize,
+    /// Equivalence rewrites applicable at any position (see `find_applicable_rules`)
+    pub applicable_rewrites: usize,
+    /// Shortest proof found by backward search; None if not tractable or not found
+    pub min_proof_steps: Option<usize>,
+    /// Forcing checks; None when the theorem uses atoms outside P..T
+    pub forces_cp: Option<bool>,
+    pub forces_case_split: Option<bool>,
+    pub forces_ip: Option<bool>,
+}
+
+impl TheoremMetrics {
+    /// Compute all metrics for `premises ⊢ conclusion`.
+    pub fn compute(premises: &[Formula], conclusion: &Formula) -> Self {
+        let formulas: Vec<&Formula> = premises.iter().chain(std::iter::once(conclusion)).collect();
+
+        let atoms: HashSet<String> = formulas.iter().flat_map(|f| f.atoms()).collect();
+        let mut connectives = ConnectiveHistogram::default();
+        for f in &formulas {
+            connectives.add(f);
+        }
+
+        let ascii: Vec<String> = formulas.iter().map(|f| f.ascii_string_bracketed()).collect();
+        let node_count = formulas.iter().map(|f| f.node_count()).sum();
+
+        let standard = atoms.iter().all(|a| STANDARD_ATOMS.contains(&a.as_str()));
+        let min_proof_steps = if standard && node_count <= MAX_SEARCH_NODES {
+            minimum_proof_steps(premises, conclusion, MAX_SEARCH_DEPTH)
+        } else {
+            None
+        };
+
+        Self {
+            node_count,
+            depth: formulas.iter().map(|f| f.depth()).max().unwrap_or(0),
+            atom_count: atoms.len(),
+            connectives,
+            negation_depth: formulas.iter().map(|f| negation_depth(f)).max().unwrap_or(0),
+            ascii_length: ascii.iter().map(|s| s.len()).sum(),
+            estimated_tokens: ascii.iter().map(|s| estimate_tokens(s)).sum(),
+            applicable_rewrites: formulas.iter().map(|f| applicable_rewrites(f)).sum(),
+            min_proof_steps,
+            forces_cp: standard.then(|| forces_cp(premises, conclusion)),
+            forces_case_split: standard.then(|| forces_case_split(premises)),
+            forces_ip: standard.then(|| forces_ip(premises, conclusion)),
+        }
+    }
+}
+
+/// Most negations on any path from the root to a leaf.
+pub fn negation_depth(formula: &Formula) -> usize {
+    match formula {
+        Formula::Atom(_) | Formula::Contradiction => 0,
+        Formula::Not(inner) => 1 + negation_depth(inner),
+        Formula::And(l, r)
+        | Formula::Or(l, r)
+        | Formula::Implies(l, r)
+        | Formula::Biconditional(l, r) => negation_depth(l).max(negation_depth(r)),
+    }
+}
+
+/// Number of (position, rule, result) rewrites the obfuscator could apply.
+pub fn applicable_rewrites(formula: &Formula) -> usize {
+    formula.subformulas_with_paths().iter()
+        .map(|(_, sub)| ObfuscateGenerator::find_applicable_rules(sub).len())
+        .sum()
+}
+
+/// Estimate tokens in an ASCII formula: each atom name, connective (`<>`
+/// counts once) and bracket is one token; whitespace is free.
+pub fn estimate_tokens(ascii: &str) -> usize {
+    let mut count = 0;
+    let mut chars = ascii.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        if c.is_alphanumeric() && c != 'v' {
+            while chars.peek().is_some_and(|n| n.is_alphanumeric() && *n != 'v') {
+                chars.next();
+            }
+        } else if c == '<' && chars.peek() == Some(&'>') {
+            chars.next();
+        }
+        count += 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atom(name: &str) -> Formula {
+        Formula::Atom(name.to_string())
+    }
+
+    fn not(f: Formula) -> Formula {
+        Formula::Not(Box::new(f))
+    }
+
+    fn and(a: Formula, b: Formula) -> Formula {
+        Formula::And(Box::new(a), Box::new(b))
+    }
+
+    fn implies(a: Formula, b: Formula) -> Formula {
+        Formula::Implies(Box::new(a), Box::new(b))
+    }
+
+    #[test]
+    fn test_structural_metrics() {
+        // ~(P . ~Q) > R
+        let f = implies(not(and(atom("P"), not(atom("Q")))), atom("R"));
+        let m = TheoremMetrics::compute(&[], &f);
+        assert_eq!(m.node_count, 7);
+        assert_eq!(m.depth, 4);
+        assert_eq!(m.atom_count, 3);
+        assert_eq!(m.negation_depth, 2);
+        assert_eq!(m.connectives, ConnectiveHistogram { not: 2, and: 1, implies: 1, ..Default::default() });
+        assert_eq!(m.ascii_length, f.ascii_string_bracketed().len());
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens("P"), 1);
+        assert_eq!(estimate_tokens("(P . Q) v ~R"), 8);
+        assert_eq!(estimate_tokens("P <> Q"), 3);
+        assert_eq!(estimate_tokens("A1 > B12"), 3);
+    }
+
+    #[test]
+    fn test_applicable_rewrites_counts_subformulas() {
+        let p = atom("P");
+        let conj = and(atom("P"), atom("Q"));
+        // The conjunction admits at least commutation at the root, on top of
+        // whatever applies to its atoms.
+        assert!(applicable_rewrites(&conj) > applicable_rewrites(&p));
+    }
+
+    #[test]
+    fn test_proof_metrics_for_modus_ponens() {
+        let premises = vec![implies(atom("P"), atom("Q")), atom("P")];
+        let m = TheoremMetrics::compute(&premises, &atom("Q"));
+        assert_eq!(m.min_proof_steps, Some(1));
+        assert_eq!(m.forces_cp, Some(false));
+        assert_eq!(m.forces_ip, Some(false));
+    }
+
+    #[test]
+    fn test_non_standard_atoms_skip_search() {
+        let premises = vec![implies(atom("A"), atom("B")), atom("A")];
+        let m = TheoremMetrics::compute(&premises, &atom("B"));
+        assert_eq!(m.min_proof_steps, None);
+        assert_eq!(m.forces_cp, None);
+        assert_eq!(m.forces_case_split, None);
+    }
+}
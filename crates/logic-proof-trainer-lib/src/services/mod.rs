@@ -7,6 +7,24 @@ pub mod tree_gen;
 pub mod truth_table;
 pub mod proof_search;
 pub mod obfuscate_gen;
+pub mod semantic_class;
+pub mod metrics;
+pub mod linearize;
+pub mod quality;
+pub mod budget;
+pub mod distractors;
+pub mod chain_gen;
+pub mod judgment;
+pub mod equivalence_proof;
+pub mod satisfiability;
+pub mod truth_table_task;
+pub mod natural_language;
+pub mod latex;
+pub mod dot;
+pub mod tptp;
+pub mod dimacs;
+pub mod import;
+pub mod lean;
 
 pub use verifier::*;
 pub use generator::*;
@@ -17,3 +35,21 @@ pub use tree_gen::*;
 pub use truth_table::*;
 pub use proof_search::*;
 pub use obfuscate_gen::*;
+pub use semantic_class::*;
+pub use metrics::*;
+pub use linearize::*;
+pub use quality::*;
+pub use budget::*;
+pub use distractors::*;
+pub use chain_gen::*;
+pub use judgment::*;
+pub use equivalence_proof::*;
+pub use satisfiability::*;
+pub use truth_table_task::*;
+pub use natural_language::*;
+pub use latex::*;
+pub use dot::*;
+pub use tptp::*;
+pub use dimacs::*;
+pub use import::*;
+pub use lean::*;
@@ -0,0 +1,237 @@
+//! Natural-language rendering: theorems as English word problems.
+//!
+//! Atoms become sentences from a lexicon, connectives English phrasings.
+//! Every compound opens with its own marker ("both", "either", "if", "it is
+//! not the case that", "it is the case that") and separates its parts with
+//! a keyword ("and", "or", "then", "if and only if"), so the wording is
+//! prefix notation in words: it reads back one way only, with no brackets.
+//! That holds as long as no lexicon sentence uses a keyword, which
+//! `Lexicon::new` checks.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::Formula;
+
+/// Words that mark or separate compounds and may not appear in a sentence
+pub const RESERVED_WORDS: [&str; 8] = ["both", "either", "and", "or", "if", "then", "only", "case"];
+
+/// Sentences of the default lexicon, one for each atom letter
+const ENGLISH_SENTENCES: [(&str, &str); 26] = [
+    ("P", "it is raining"),
+    ("Q", "the streets are wet"),
+    ("R", "the match is cancelled"),
+    ("S", "the museum is open"),
+    ("T", "the train is late"),
+    ("A", "the alarm rings"),
+    ("B", "the bakery sells bread"),
+    ("C", "the cat is asleep"),
+    ("D", "the door is locked"),
+    ("E", "the experiment succeeds"),
+    ("F", "the fire is lit"),
+    ("G", "the garden needs water"),
+    ("H", "the hotel is full"),
+    ("I", "the inspector arrives"),
+    ("J", "the judge is present"),
+    ("K", "the kettle is boiling"),
+    ("L", "the lights are on"),
+    ("M", "the meeting runs long"),
+    ("N", "the newspaper is delivered"),
+    ("O", "the office is closed"),
+    ("U", "the umbrella is packed"),
+    ("V", "the vote is postponed"),
+    ("W", "the wind is strong"),
+    ("X", "the exam is difficult"),
+    ("Y", "the yard is muddy"),
+    ("Z", "the zoo is crowded"),
+];
+
+/// Why a lexicon cannot be used
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexiconError {
+    /// An atom of the formula has no sentence
+    Unmapped(String),
+    /// An atom's sentence is blank
+    Empty(String),
+    /// An atom's sentence uses a reserved word
+    Reserved { atom: String, word: String },
+    /// Two atoms have the same sentence
+    Duplicate { first: String, second: String },
+}
+
+impl std::fmt::Display for LexiconError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LexiconError::Unmapped(atom) => write!(f, "Atom {} has no sentence in the lexicon", atom),
+            LexiconError::Empty(atom) => write!(f, "The sentence for {} is empty", atom),
+            LexiconError::Reserved { atom, word } => {
+                write!(f, "The sentence for {} uses '{}', which marks connectives", atom, word)
+            }
+            LexiconError::Duplicate { first, second } => write!(f, "{} and {} have the same sentence", first, second),
+        }
+    }
+}
+
+/// Sentences standing for atoms
+#[derive(Debug, Clone)]
+pub struct Lexicon {
+    sentences: BTreeMap<String, String>,
+}
+
+impl Default for Lexicon {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+impl Lexicon {
+    /// A lexicon from atom-sentence pairs. Sentences are trimmed and must be
+    /// distinct and free of reserved words.
+    pub fn new(sentences: BTreeMap<String, String>) -> Result<Self, LexiconError> {
+        let mut seen: BTreeMap<String, String> = BTreeMap::new();
+        let mut trimmed = BTreeMap::new();
+        for (atom, sentence) in sentences {
+            let sentence = sentence.trim().to_string();
+            if sentence.is_empty() {
+                return Err(LexiconError::Empty(atom));
+            }
+            let words: HashSet<String> = sentence
+                .split(|c: char| !c.is_alphanumeric() && c != '\'')
+                .map(|w| w.to_lowercase())
+                .collect();
+            if let Some(word) = RESERVED_WORDS.iter().find(|w| words.contains(**w)) {
+                return Err(LexiconError::Reserved { atom, word: word.to_string() });
+            }
+            if let Some(first) = seen.insert(sentence.to_lowercase(), atom.clone()) {
+                return Err(LexiconError::Duplicate { first, second: atom });
+            }
+            trimmed.insert(atom, sentence);
+        }
+        Ok(Self { sentences: trimmed })
+    }
+
+    /// Everyday English sentences for the atom letters A-Z
+    pub fn english() -> Self {
+        Self {
+            sentences: ENGLISH_SENTENCES.iter().map(|(a, s)| (a.to_string(), s.to_string())).collect(),
+        }
+    }
+
+    pub fn sentence(&self, atom: &str) -> Result<&str, LexiconError> {
+        self.sentences.get(atom).map(String::as_str).ok_or_else(|| LexiconError::Unmapped(atom.to_string()))
+    }
+}
+
+/// A theorem in words
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NaturalTheorem {
+    /// One sentence per premise
+    pub premises: Vec<String>,
+    pub conclusion: String,
+    /// The whole argument as a word problem
+    pub problem: String,
+}
+
+/// `formula` as an English clause, lower case and without a full stop.
+pub fn verbalize(formula: &Formula, lexicon: &Lexicon) -> Result<String, LexiconError> {
+    Ok(match formula {
+        Formula::Atom(name) => lexicon.sentence(name)?.to_string(),
+        Formula::Not(inner) => format!("it is not the case that {}", verbalize(inner, lexicon)?),
+        Formula::And(l, r) => format!("both {} and {}", verbalize(l, lexicon)?, verbalize(r, lexicon)?),
+        Formula::Or(l, r) => format!("either {} or {}", verbalize(l, lexicon)?, verbalize(r, lexicon)?),
+        Formula::Implies(l, r) => format!("if {}, then {}", verbalize(l, lexicon)?, verbalize(r, lexicon)?),
+        Formula::Biconditional(l, r) => format!(
+            "it is the case that {} if and only if {}",
+            verbalize(l, lexicon)?,
+            verbalize(r, lexicon)?
+        ),
+        Formula::Contradiction => "a contradiction holds".to_string(),
+    })
+}
+
+/// `premises` ⊢ `conclusion` as a word problem: the premises as numbered
+/// sentences, then the conclusion to show.
+pub fn verbalize_argument(premises: &[Formula], conclusion: &Formula, lexicon: &Lexicon) -> Result<NaturalTheorem, LexiconError> {
+    let premises: Vec<String> = premises.iter()
+        .map(|p| verbalize(p, lexicon).map(|clause| sentence_case(&clause)))
+        .collect::<Result<_, _>>()?;
+    let conclusion = verbalize(conclusion, lexicon)?;
+    let problem = if premises.is_empty() {
+        format!("Without any assumptions, show that {}.", conclusion)
+    } else {
+        let numbered: Vec<String> = premises.iter().enumerate().map(|(i, p)| format!("{}. {}", i + 1, p)).collect();
+        format!("Assume the following.\n{}\nShow that {}.", numbered.join("\n"), conclusion)
+    };
+    Ok(NaturalTheorem { premises, conclusion: sentence_case(&conclusion), problem })
+}
+
+/// Capitalise the first letter and end with a full stop
+fn sentence_case(clause: &str) -> String {
+    let mut chars = clause.chars();
+    match chars.next() {
+        Some(first) => format!("{}{}.", first.to_uppercase(), chars.as_str()),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Formula {
+        Formula::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_grouping_is_in_the_wording() {
+        let lexicon = Lexicon::english();
+        assert_eq!(
+            verbalize(&parse("(P & Q) | R"), &lexicon).unwrap(),
+            "either both it is raining and the streets are wet or the match is cancelled"
+        );
+        assert_eq!(
+            verbalize(&parse("P & (Q | R)"), &lexicon).unwrap(),
+            "both it is raining and either the streets are wet or the match is cancelled"
+        );
+        assert_eq!(
+            verbalize(&parse("~(P -> Q)"), &lexicon).unwrap(),
+            "it is not the case that if it is raining, then the streets are wet"
+        );
+        assert_ne!(
+            verbalize(&parse("(P <-> Q) <-> R"), &lexicon).unwrap(),
+            verbalize(&parse("P <-> (Q <-> R)"), &lexicon).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_word_problem() {
+        let premises = vec![parse("P -> Q"), parse("P")];
+        let natural = verbalize_argument(&premises, &parse("Q"), &Lexicon::english()).unwrap();
+        assert_eq!(natural.premises[1], "It is raining.");
+        assert_eq!(natural.conclusion, "The streets are wet.");
+        assert_eq!(
+            natural.problem,
+            "Assume the following.\n1. If it is raining, then the streets are wet.\n2. It is raining.\nShow that the streets are wet."
+        );
+    }
+
+    #[test]
+    fn test_lexicon_checks() {
+        let lexicon = |pairs: &[(&str, &str)]| {
+            Lexicon::new(pairs.iter().map(|(a, s)| (a.to_string(), s.to_string())).collect())
+        };
+        assert!(Lexicon::new(Lexicon::english().sentences).is_ok());
+        assert!(lexicon(&[("P", "the sun shines")]).is_ok());
+        assert_eq!(
+            lexicon(&[("P", "Sam and Kim agree")]).unwrap_err(),
+            LexiconError::Reserved { atom: "P".to_string(), word: "and".to_string() }
+        );
+        assert!(matches!(lexicon(&[("P", "it snows"), ("Q", "It snows ")]), Err(LexiconError::Duplicate { .. })));
+        assert_eq!(lexicon(&[("P", " ")]).unwrap_err(), LexiconError::Empty("P".to_string()));
+        assert_eq!(
+            verbalize(&parse("P & Q"), &lexicon(&[("P", "it snows")]).unwrap()),
+            Err(LexiconError::Unmapped("Q".to_string()))
+        );
+    }
+}
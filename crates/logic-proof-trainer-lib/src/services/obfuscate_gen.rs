@@ -7,12 +7,22 @@
 //!
 //! Key insight: Semantic validity is GUARANTEED because equivalence
 //! transformations preserve truth tables.
+//!
+//! The argument pipeline (`generate_argument_with_spec`) skips step 2 and
+//! rewrites each premise and the conclusion separately, so the theorem keeps
+//! its premises. Entailment is preserved for the same reason.
 
 use rand::Rng;
+use std::collections::BTreeMap;
 use crate::models::Formula;
 use crate::models::rules::equivalence::EquivalenceRule;
-use crate::models::theorem::{BaseComplexity, Difficulty, DifficultySpec, DifficultyTier, Theme, Theorem};
-use crate::services::truth_table::{is_tautology, is_tautology_dynamic};
+use crate::models::theorem::{BaseComplexity, Difficulty, DifficultySpec, DifficultyTier, TargetSize, Theme, Theorem};
+use crate::services::truth_table::{are_equivalent_dynamic, is_tautology, is_tautology_dynamic, validate_theorem_dynamic};
+use crate::services::proof_tree::DegenerateProofError;
+use crate::services::semantic_class::argument_class;
+use crate::services::quality::{check_target_size, QualityGateError, QualityGates, Rejection, RejectionStats};
+use crate::services::budget::{BudgetClock, BudgetPolicy, GenerationBudget};
+use crate::services::equivalence_proof::{EquivalencePair, EquivalenceStep};
 
 /// Configuration for obfuscation generation
 #[derive(Debug, Clone)]
@@ -27,6 +37,10 @@ pub struct ObfuscateConfig {
     pub bridge_atoms: usize,
     /// Whether to force multi-rule transformation chains (gnarly combos)
     pub gnarly_combos: bool,
+    /// Per-rule weight overrides for random rewrites (0 = excluded)
+    pub rule_weights: BTreeMap<EquivalenceRule, f64>,
+    /// Custom gnarly combo chains; None = the built-in ones
+    pub combo_chains: Option<Vec<Vec<EquivalenceRule>>>,
 }
 
 impl ObfuscateConfig {
@@ -82,6 +96,8 @@ impl ObfuscateConfig {
             substitution_depth,
             bridge_atoms: 0,
             gnarly_combos: d >= 85,
+            rule_weights: BTreeMap::new(),
+            combo_chains: None,
         }
     }
 
@@ -105,10 +121,22 @@ impl ObfuscateConfig {
             difficulty_value,
             substitution_depth: spec.substitution_depth as usize,
             bridge_atoms: spec.bridge_atoms.unwrap_or(0) as usize,
-            gnarly_combos: spec.gnarly_combos.unwrap_or(difficulty_value >= 85),
+            gnarly_combos: spec.gnarly_combos.unwrap_or(spec.combo_chains.is_some() || difficulty_value >= 85),
+            rule_weights: spec.rule_weights.clone().unwrap_or_default(),
+            combo_chains: spec.combo_chains.clone(),
         }
     }
 
+    /// Weight of `rule` when a random rewrite is drawn. Distribution and
+    /// Equivalence duplicate entire subtrees, causing exponential growth, so
+    /// they are de-weighted unless overridden.
+    pub fn rule_weight(&self, rule: EquivalenceRule) -> f64 {
+        self.rule_weights.get(&rule).copied().unwrap_or(match rule {
+            EquivalenceRule::Distribution | EquivalenceRule::Equivalence => 0.2,
+            _ => 1.0,
+        })
+    }
+
     /// Derive a legacy 1-100 difficulty value from a DifficultySpec.
     ///
     /// Uses total effective transforms (`passes * transforms_per_pass`) plus a
@@ -147,6 +175,52 @@ impl ObfuscateConfig {
 const MAX_FORMULA_DEPTH: usize = 100;
 const MAX_FORMULA_NODES: usize = 20_000;
 
+/// Base arguments drawn by the premise-bearing pipeline before giving up.
+/// Substitutions over few atoms are often constant (e.g. `S ⊃ S`), so at
+/// substitution depth 3+ most draws are degenerate; this keeps the chance of
+/// exhausting the budget negligible.
+pub const MAX_ARGUMENT_ATTEMPTS: usize = 200;
+
+/// Extra passes, then single rewrites, tried when fitting a formula into its
+/// `TargetSize`.
+const MAX_SIZE_PASSES: usize = 50;
+const MAX_SIZE_STEPS: usize = 500;
+
+/// Output of one pipeline run, before any quality gates are applied.
+/// Tautology-format candidates have no premises.
+struct Candidate {
+    premises: Vec<Formula>,
+    conclusion: Formula,
+    base_class: String,
+    /// Equivalence rewrites applied across all passes; 0 when they cancel
+    /// out and leave the base form unchanged
+    transforms: usize,
+}
+
+impl Candidate {
+    fn node_count(&self) -> usize {
+        self.premises.iter().chain(std::iter::once(&self.conclusion)).map(|f| f.node_count()).sum()
+    }
+
+    /// Spec-generated theorem: tier metadata when a tier is given, otherwise
+    /// the fixed Expert/100 labelling used for custom specs.
+    fn into_theorem(self, tier: Option<DifficultyTier>) -> Theorem {
+        let mut theorem = match tier {
+            Some(tier) => Theorem::from_tier(self.premises, self.conclusion, tier, Some(Theme::Equivalence)),
+            None => Theorem::with_difficulty_value(
+                self.premises,
+                self.conclusion,
+                Difficulty::Expert,
+                100,
+                Some(Theme::Equivalence),
+                None,
+            ),
+        };
+        theorem.base_class = Some(self.base_class);
+        theorem
+    }
+}
+
 /// Build an atom pool of `n` unique atom names.
 /// 1-5: P, Q, R, S, T
 /// 6+: extend with A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, U, V, W, X, Y, Z
@@ -169,17 +243,6 @@ pub fn build_atom_pool(n: u8) -> Vec<String> {
     pool
 }
 
-/// Count total nodes in a formula tree.
-fn node_count(formula: &Formula) -> usize {
-    match formula {
-        Formula::Atom(_) | Formula::Contradiction => 1,
-        Formula::Not(inner) => 1 + node_count(inner),
-        Formula::And(l, r) | Formula::Or(l, r) | Formula::Implies(l, r) | Formula::Biconditional(l, r) => {
-            1 + node_count(l) + node_count(r)
-        }
-    }
-}
-
 /// Base argument forms for generating simple valid theorems
 #[derive(Debug, Clone, Copy)]
 enum BaseForm {
@@ -276,17 +339,34 @@ impl BaseForm {
 /// Main generator struct
 pub struct ObfuscateGenerator {
     config: ObfuscateConfig,
+    /// Checked inside the rewrite and substitution loops, which stop early
+    /// once it is spent
+    clock: BudgetClock,
+    /// The formulas rewritten are premises and conclusions kept separate
+    /// rather than one wrapped tautology
+    keep_premises: bool,
 }
 
 impl ObfuscateGenerator {
     pub fn new(config: ObfuscateConfig) -> Self {
-        Self { config }
+        Self { config, clock: BudgetClock::unlimited(), keep_premises: false }
+    }
+
+    /// A generator limited by `budget`, whose clock starts now.
+    fn with_budget(config: ObfuscateConfig, budget: &GenerationBudget) -> Self {
+        Self { config, clock: BudgetClock::start(budget), keep_premises: false }
+    }
+
+    /// Rewrite premises and conclusions separately (see `check_equivalent`).
+    fn keeping_premises(self, keep_premises: bool) -> Self {
+        Self { keep_premises, ..self }
     }
 
     /// Generate an obfuscated theorem (tautology format)
     pub fn generate(&self, rng: &mut impl Rng) -> Theorem {
         // Layer 1: Generate simple base theorem
         let (premises, conclusion) = self.generate_base_theorem(rng);
+        let base_class = argument_class(&premises, &conclusion);
 
         // Layer 3: Apply atom substitutions for high difficulty
         // This replaces simple atoms (P, Q, R) with complex formulas (A∨~B, C.D, E⊃F)
@@ -306,42 +386,28 @@ impl ObfuscateGenerator {
         debug_assert!(is_tautology(&obfuscated), "Obfuscated formula should still be a tautology");
 
         // Return as tautology (no premises)
-        Theorem::with_difficulty_value(
+        let mut theorem = Theorem::with_difficulty_value(
             vec![], // No premises - it's a tautology
             obfuscated,
             self.config.difficulty,
             self.config.difficulty_value,
             Some(Theme::Equivalence),
             None,
-        )
+        );
+        theorem.base_class = Some(base_class);
+        theorem
     }
 
     /// Generate an obfuscated theorem using a DifficultySpec (multi-pass pipeline).
     pub fn generate_with_spec(spec: &DifficultySpec, rng: &mut impl Rng) -> Theorem {
-        let formula = Self::run_spec_pipeline(spec, rng);
-
-        Theorem::with_difficulty_value(
-            vec![],
-            formula,
-            Difficulty::Expert,
-            100,
-            Some(Theme::Equivalence),
-            None,
-        )
+        Self::new(ObfuscateConfig::from_spec(spec)).run_spec_pipeline(spec, rng).into_theorem(None)
     }
 
     /// Generate an obfuscated theorem for a specific DifficultyTier.
     /// Sets the `tier` field on the returned Theorem.
     pub fn generate_with_tier(tier: DifficultyTier, rng: &mut impl Rng) -> Theorem {
         let spec = DifficultySpec::from_tier(tier);
-        let formula = Self::run_spec_pipeline(&spec, rng);
-
-        Theorem::from_tier(
-            vec![],
-            formula,
-            tier,
-            Some(Theme::Equivalence),
-        )
+        Self::new(ObfuscateConfig::from_spec(&spec)).run_spec_pipeline(&spec, rng).into_theorem(Some(tier))
     }
 
     /// Generate an obfuscated theorem for a specific DifficultyTier using a
@@ -349,34 +415,29 @@ impl ObfuscateGenerator {
     /// parameters while the tier determines the theorem's metadata (difficulty
     /// label and tier field).
     pub fn generate_with_tier_spec(tier: DifficultyTier, spec: &DifficultySpec, rng: &mut impl Rng) -> Theorem {
-        let formula = Self::run_spec_pipeline(spec, rng);
-
-        Theorem::from_tier(
-            vec![],
-            formula,
-            tier,
-            Some(Theme::Equivalence),
-        )
+        Self::new(ObfuscateConfig::from_spec(spec)).run_spec_pipeline(spec, rng).into_theorem(Some(tier))
     }
 
-    /// Core spec-based pipeline: generates a tautology formula from a DifficultySpec.
-    fn run_spec_pipeline(spec: &DifficultySpec, rng: &mut impl Rng) -> Formula {
-        let config = ObfuscateConfig::from_spec(spec);
-        let gen = ObfuscateGenerator::new(config);
+    /// Core spec-based pipeline: generates a tautology formula from a DifficultySpec,
+    /// with the semantic class id of the base argument it was built from and
+    /// the number of rewrites applied. `self` must be configured from `spec`.
+    /// Stops early, with a partial candidate, once the budget is spent.
+    fn run_spec_pipeline(&self, spec: &DifficultySpec, rng: &mut impl Rng) -> Candidate {
 
         // Layer 1: Generate base theorem
         let use_complex = spec.base_complexity == BaseComplexity::Complex;
-        let (premises, conclusion) = gen.generate_base_theorem_with_complexity(rng, use_complex);
+        let (premises, conclusion) = self.generate_base_theorem_with_complexity(rng, use_complex);
+        let base_class = argument_class(&premises, &conclusion);
 
         // Layer 2: Apply substitutions (once, before multi-pass)
         let (premises, conclusion) = if spec.substitution_depth > 0 {
-            gen.apply_substitutions(premises, conclusion, rng)
+            self.apply_substitutions(premises, conclusion, rng)
         } else {
             (premises, conclusion)
         };
 
         // Wrap as conditional tautology
-        let mut formula = gen.wrap_as_conditional(&premises, &conclusion);
+        let mut formula = self.wrap_as_conditional(&premises, &conclusion);
         debug_assert!(
             is_tautology_dynamic(&formula),
             "Initial wrapped formula should be a tautology"
@@ -384,14 +445,26 @@ impl ObfuscateGenerator {
 
         // Multi-pass pipeline
         let max_nodes = spec.max_formula_nodes.unwrap_or(MAX_FORMULA_NODES as u32) as usize;
+        let base = formula.clone();
+        let mut transforms = 0;
         for _pass in 0..spec.passes {
             // Safety check: skip if formula too large
-            if formula.depth() >= MAX_FORMULA_DEPTH || node_count(&formula) >= max_nodes {
+            if formula.depth() >= MAX_FORMULA_DEPTH || formula.node_count() >= max_nodes {
+                break;
+            }
+            if self.clock.spent(std::slice::from_ref(&formula)) {
                 break;
             }
 
-            // Apply transforms for this pass
-            formula = gen.apply_transformations(formula, rng);
+            // Apply transforms for this pass. A pass that overshoots the
+            // target size is redone one rewrite at a time within the band.
+            let (transformed, applied) = self.apply_transformations_counted(formula.clone(), rng);
+            if let Some(target) = spec.target_size.filter(|t| t.measure(std::slice::from_ref(&transformed)) > t.max as usize) {
+                transforms += self.rewrite_within(std::slice::from_mut(&mut formula), &target, self.config.transform_count, rng);
+                break;
+            }
+            formula = transformed;
+            transforms += applied;
 
             debug_assert!(
                 is_tautology_dynamic(&formula),
@@ -399,16 +472,265 @@ impl ObfuscateGenerator {
             );
         }
 
+        if let Some(target) = &spec.target_size {
+            transforms += self.grow_to_size(std::slice::from_mut(&mut formula), target, rng);
+        }
+
         // Defence-in-depth: verify the final formula is still a tautology.
         // The per-pass check above only fires inside the loop and only in debug
         // builds; this catches any issue introduced by the very last pass
         // (including simplify_negations) regardless of where the loop exited.
+        // `QualityGates::check` repeats this in release builds.
         debug_assert!(
             is_tautology_dynamic(&formula),
             "Final formula after all passes must be a tautology"
         );
 
-        formula
+        if formula == base {
+            transforms = 0;
+        }
+        Candidate { premises: vec![], conclusion: formula, base_class, transforms }
+    }
+
+    /// Generate a premise-bearing theorem using a DifficultySpec. Premises and
+    /// conclusion are substituted together, then obfuscated independently.
+    /// Fails if no non-degenerate argument is found within `MAX_ARGUMENT_ATTEMPTS`.
+    pub fn generate_argument_with_spec(spec: &DifficultySpec, rng: &mut impl Rng) -> Result<Theorem, DegenerateProofError> {
+        let candidate = Self::new(ObfuscateConfig::from_spec(spec))
+            .keeping_premises(true)
+            .run_argument_pipeline(spec, rng, &mut RejectionStats::default())?;
+        Ok(candidate.into_theorem(None))
+    }
+
+    /// Premise-bearing counterpart of `generate_with_tier_spec`.
+    pub fn generate_argument_with_tier_spec(
+        tier: DifficultyTier,
+        spec: &DifficultySpec,
+        rng: &mut impl Rng,
+    ) -> Result<Theorem, DegenerateProofError> {
+        let candidate = Self::new(ObfuscateConfig::from_spec(spec))
+            .keeping_premises(true)
+            .run_argument_pipeline(spec, rng, &mut RejectionStats::default())?;
+        Ok(candidate.into_theorem(Some(tier)))
+    }
+
+    /// Generate an equivalence-proof task from a DifficultySpec: the wrapped
+    /// base argument and the formula the spec's passes rewrite it into, with
+    /// every replacement recorded. Draws again (up to `MAX_ARGUMENT_ATTEMPTS`
+    /// times) while the rewrites cancel out. The theorem has the source as
+    /// its only premise and is labelled as `generate_with_tier_spec` would.
+    pub fn generate_equivalence(
+        spec: &DifficultySpec,
+        tier: Option<DifficultyTier>,
+        rng: &mut impl Rng,
+    ) -> (Theorem, EquivalencePair) {
+        let generator = Self::new(ObfuscateConfig::from_spec(spec));
+        let mut pair = generator.run_equivalence_pipeline(spec, rng);
+        for _attempt in 1..MAX_ARGUMENT_ATTEMPTS {
+            if pair.source != pair.target {
+                break;
+            }
+            pair = generator.run_equivalence_pipeline(spec, rng);
+        }
+        let candidate = Candidate {
+            premises: pair.premises(),
+            conclusion: pair.target.clone(),
+            base_class: pair.base_class.clone(),
+            transforms: if pair.source == pair.target { 0 } else { pair.steps.len() },
+        };
+        (candidate.into_theorem(tier), pair)
+    }
+
+    /// Spec-based generation with quality gates: runs the tautology pipeline
+    /// (or the argument pipeline with `keep_premises`) until a candidate
+    /// passes `gates`, at most `gates.max_retries` times. Every rejection,
+    /// including degenerate argument draws, is recorded in `stats`.
+    ///
+    /// All attempts share one `budget`; running over it fails at once with
+    /// `Rejection::OverBudget` (see `generate_budgeted` for the policies).
+    pub fn generate_gated(
+        spec: &DifficultySpec,
+        tier: Option<DifficultyTier>,
+        keep_premises: bool,
+        gates: &QualityGates,
+        budget: &GenerationBudget,
+        stats: &mut RejectionStats,
+        rng: &mut impl Rng,
+    ) -> Result<Theorem, QualityGateError> {
+        let gen = Self::with_budget(ObfuscateConfig::from_spec(spec), budget).keeping_premises(keep_premises);
+        let mut last = Rejection::NotValid;
+        for attempt in 0..gates.max_retries {
+            let candidate = if keep_premises {
+                gen.run_argument_pipeline(spec, rng, stats)
+            } else {
+                Ok(gen.run_spec_pipeline(spec, rng))
+            };
+            // The loops check before each rewrite; the finished candidate counts too
+            let nodes = candidate.as_ref().map_or(0, Candidate::node_count);
+            if gen.clock.spent_nodes(nodes) {
+                let rejection = Rejection::OverBudget(gen.clock.exceeded().expect("a spent budget records why"));
+                stats.record(&rejection);
+                return Err(QualityGateError { attempts: attempt + 1, last: rejection });
+            }
+            let candidate = match candidate {
+                Ok(candidate) => candidate,
+                Err(e) => {
+                    // Each failed draw was already recorded by the pipeline
+                    last = Rejection::Degenerate(e);
+                    continue;
+                }
+            };
+
+            let checked = match &spec.target_size {
+                Some(target) => check_target_size(target, &candidate.premises, &candidate.conclusion)
+                    .and_then(|()| gates.check(&candidate.premises, &candidate.conclusion, candidate.transforms)),
+                None => gates.check(&candidate.premises, &candidate.conclusion, candidate.transforms),
+            };
+            match checked {
+                Ok(()) => {
+                    stats.accepted += 1;
+                    return Ok(candidate.into_theorem(tier));
+                }
+                Err(rejection) => {
+                    stats.record(&rejection);
+                    last = rejection;
+                }
+            }
+        }
+        Err(QualityGateError { attempts: gates.max_retries, last })
+    }
+
+    /// `generate_gated`, applying `budget.policy` when a theorem runs over
+    /// budget: `Retry` starts again with `DifficultySpec::shrunk` (failing
+    /// once the spec cannot shrink), `Skip` returns `None`, `Fail` returns the
+    /// error. On success, returns the theorem with the spec that produced it.
+    /// Retries and skips are counted in `stats`.
+    pub fn generate_budgeted(
+        spec: &DifficultySpec,
+        tier: Option<DifficultyTier>,
+        keep_premises: bool,
+        gates: &QualityGates,
+        budget: &GenerationBudget,
+        stats: &mut RejectionStats,
+        rng: &mut impl Rng,
+    ) -> Result<Option<(Theorem, DifficultySpec)>, QualityGateError> {
+        let mut spec = spec.clone();
+        loop {
+            let err = match Self::generate_gated(&spec, tier, keep_premises, gates, budget, stats, rng) {
+                Ok(theorem) => return Ok(Some((theorem, spec))),
+                Err(err) => err,
+            };
+            if !matches!(err.last, Rejection::OverBudget(_)) {
+                return Err(err);
+            }
+            match budget.policy {
+                BudgetPolicy::Fail => return Err(err),
+                BudgetPolicy::Skip => {
+                    stats.skipped += 1;
+                    return Ok(None);
+                }
+                BudgetPolicy::Retry => match spec.shrunk() {
+                    Some(smaller) => {
+                        stats.shrunk += 1;
+                        spec = smaller;
+                    }
+                    None => return Err(err),
+                },
+            }
+        }
+    }
+
+    /// Spec-based pipeline that keeps the argument form instead of wrapping it.
+    ///
+    /// Each attempt draws a base argument and applies substitutions; the
+    /// result is checked with `validate_theorem_dynamic` and discarded if
+    /// degenerate (single-premise forms, duplicated or tautological premises
+    /// after substitution, ...). Every pass then rewrites each premise and the
+    /// conclusion on its own. Rewrites are equivalences, so the checked
+    /// properties carry over; the final argument is validated again anyway.
+    /// Discarded attempts are recorded in `stats` as degenerate. Once the
+    /// budget is spent the current attempt is abandoned unrecorded and the
+    /// last error returned; `self` must be configured from `spec`.
+    fn run_argument_pipeline(
+        &self,
+        spec: &DifficultySpec,
+        rng: &mut impl Rng,
+        stats: &mut RejectionStats,
+    ) -> Result<Candidate, DegenerateProofError> {
+        let use_complex = spec.base_complexity == BaseComplexity::Complex;
+        let max_nodes = spec.max_formula_nodes.unwrap_or(MAX_FORMULA_NODES as u32) as usize;
+
+        let mut last_error = DegenerateProofError::InvalidTheorem;
+        for _attempt in 0..MAX_ARGUMENT_ATTEMPTS {
+            let (premises, conclusion) = self.generate_base_theorem_with_complexity(rng, use_complex);
+            let base_class = argument_class(&premises, &conclusion);
+
+            let (mut premises, mut conclusion) = if spec.substitution_depth > 0 {
+                self.apply_substitutions(premises, conclusion, rng)
+            } else {
+                (premises, conclusion)
+            };
+            if self.clock.exceeded().is_some() {
+                break;
+            }
+            if let Err(e) = validate_theorem_dynamic(&premises, &conclusion) {
+                stats.record(&Rejection::Degenerate(e.clone()));
+                last_error = e;
+                continue;
+            }
+
+            let (base_premises, base_conclusion) = (premises.clone(), conclusion.clone());
+            let mut transforms = 0;
+            for _pass in 0..spec.passes {
+                let formulas = premises.iter().chain(std::iter::once(&conclusion));
+                let nodes: usize = formulas.clone().map(|f| f.node_count()).sum();
+                let depth = formulas.map(|f| f.depth()).max().unwrap_or(0);
+                if depth >= MAX_FORMULA_DEPTH || nodes >= max_nodes || self.clock.spent_nodes(nodes) {
+                    break;
+                }
+
+                let mut applied = 0;
+                let mut rewritten: Vec<Formula> = premises.iter()
+                    .chain(std::iter::once(&conclusion))
+                    .map(|f| {
+                        let (rewritten, count) = self.apply_transformations_counted(f.clone(), rng);
+                        applied += count;
+                        rewritten
+                    })
+                    .collect();
+                if let Some(target) = spec.target_size.filter(|t| t.measure(&rewritten) > t.max as usize) {
+                    premises.push(conclusion);
+                    transforms += self.rewrite_within(&mut premises, &target, self.config.transform_count, rng);
+                    conclusion = premises.pop().expect("conclusion was just pushed");
+                    break;
+                }
+                conclusion = rewritten.pop().expect("conclusion is always rewritten");
+                premises = rewritten;
+                transforms += applied;
+            }
+
+            if let Some(target) = &spec.target_size {
+                premises.push(conclusion);
+                transforms += self.grow_to_size(&mut premises, target, rng);
+                conclusion = premises.pop().expect("conclusion was just pushed");
+            }
+            if self.clock.exceeded().is_some() {
+                break;
+            }
+            if premises == base_premises && conclusion == base_conclusion {
+                transforms = 0;
+            }
+
+            match validate_theorem_dynamic(&premises, &conclusion) {
+                Ok(()) => return Ok(Candidate { premises, conclusion, base_class, transforms }),
+                Err(e) => {
+                    stats.record(&Rejection::Degenerate(e.clone()));
+                    last_error = e;
+                }
+            }
+        }
+
+        Err(last_error)
     }
 
     /// Generate base theorem with explicit complexity control.
@@ -808,6 +1130,16 @@ impl ObfuscateGenerator {
             substitutions.insert(atom.clone(), replacement);
         }
 
+        // Substitution multiplies sizes, so check the result against the
+        // budget before building it
+        let projected: usize = premises.iter()
+            .chain(std::iter::once(&conclusion))
+            .map(|f| Self::substituted_node_count(f, &substitutions))
+            .sum();
+        if self.clock.spent_nodes(projected) {
+            return (premises, conclusion);
+        }
+
         // Apply substitutions to all premises and conclusion
         let new_premises: Vec<Formula> = premises
             .into_iter()
@@ -962,6 +1294,18 @@ impl ObfuscateGenerator {
         }
     }
 
+    /// Node count `substitute_all` would produce, without building it.
+    fn substituted_node_count(formula: &Formula, subs: &std::collections::HashMap<String, Formula>) -> usize {
+        match formula {
+            Formula::Atom(name) => subs.get(name).map_or(1, |f| f.node_count()),
+            Formula::Contradiction => 1,
+            Formula::Not(inner) => 1 + Self::substituted_node_count(inner, subs),
+            Formula::And(l, r) | Formula::Or(l, r) | Formula::Implies(l, r) | Formula::Biconditional(l, r) => {
+                1 + Self::substituted_node_count(l, subs) + Self::substituted_node_count(r, subs)
+            }
+        }
+    }
+
     /// Apply all substitutions from the map to a formula.
     fn substitute_all(&self, formula: &Formula, subs: &std::collections::HashMap<String, Formula>) -> Formula {
         match formula {
@@ -999,6 +1343,36 @@ impl ObfuscateGenerator {
         }
     }
 
+    /// `run_spec_pipeline` without target sizes, recording each rewrite.
+    /// Stacked negations are collapsed by recorded DN steps rather than
+    /// `simplify_negations`.
+    fn run_equivalence_pipeline(&self, spec: &DifficultySpec, rng: &mut impl Rng) -> EquivalencePair {
+        let use_complex = spec.base_complexity == BaseComplexity::Complex;
+        let (premises, conclusion) = self.generate_base_theorem_with_complexity(rng, use_complex);
+        let base_class = argument_class(&premises, &conclusion);
+        let (premises, conclusion) = if spec.substitution_depth > 0 {
+            self.apply_substitutions(premises, conclusion, rng)
+        } else {
+            (premises, conclusion)
+        };
+        let source = self.wrap_as_conditional(&premises, &conclusion);
+
+        let max_nodes = spec.max_formula_nodes.unwrap_or(MAX_FORMULA_NODES as u32) as usize;
+        let mut steps = Vec::new();
+        let mut formula = source.clone();
+        for _pass in 0..spec.passes {
+            if formula.depth() >= MAX_FORMULA_DEPTH || formula.node_count() >= max_nodes {
+                break;
+            }
+            let (rewritten, _) = self.apply_rewrites(formula, rng, &mut |rule, result| {
+                steps.push(EquivalenceStep { rule, result: result.clone() });
+            });
+            formula = collapse_negations(rewritten, &mut steps);
+        }
+
+        EquivalencePair { source, target: formula, steps, base_class }
+    }
+
     /// Wrap premises and conclusion as a single conditional tautology
     /// (P1 ∧ P2 ∧ ... ∧ Pn) ⊃ C
     fn wrap_as_conditional(&self, premises: &[Formula], conclusion: &Formula) -> Formula {
@@ -1019,10 +1393,32 @@ impl ObfuscateGenerator {
     }
 
     /// Apply random equivalence transformations
-    fn apply_transformations(&self, mut formula: Formula, rng: &mut impl Rng) -> Formula {
+    fn apply_transformations(&self, formula: Formula, rng: &mut impl Rng) -> Formula {
+        self.apply_transformations_counted(formula, rng).0
+    }
+
+    /// `apply_transformations`, also returning how many rewrites were applied
+    /// (gnarly combo steps included). Stops early once the budget is spent.
+    fn apply_transformations_counted(&self, formula: Formula, rng: &mut impl Rng) -> (Formula, usize) {
+        let (formula, applied) = self.apply_rewrites(formula, rng, &mut |_, _| {});
+
+        // Simplification pass: collapse excessive negations (~~~~P → P)
+        (simplify_negations(formula), applied)
+    }
+
+    /// The rewrites of one pass (gnarly combos, then random rewrites), each
+    /// passed to `record` with its result. Returns the rewritten formula and
+    /// the number of rewrites; stops early once the budget is spent.
+    fn apply_rewrites(
+        &self,
+        mut formula: Formula,
+        rng: &mut impl Rng,
+        record: &mut impl FnMut(EquivalenceRule, &Formula),
+    ) -> (Formula, usize) {
         // Force gnarly transformation combos when enabled
+        let mut combo_transforms = 0;
         if self.config.gnarly_combos {
-            formula = self.apply_gnarly_combos(formula, rng);
+            (formula, combo_transforms) = self.apply_gnarly_combos(formula, rng, record);
         }
 
         let mut successful_transforms = 0;
@@ -1030,15 +1426,18 @@ impl ObfuscateGenerator {
         let max_attempts = self.config.transform_count * 10;
 
         while successful_transforms < self.config.transform_count && attempts < max_attempts {
+            if self.clock.spent(std::slice::from_ref(&formula)) {
+                break;
+            }
             attempts += 1;
-            if let Some(transformed) = self.try_apply_random_equivalence(&formula, rng) {
+            if let Some((rule, transformed)) = self.try_apply_random_equivalence(&formula, rng) {
+                record(rule, &transformed);
                 formula = transformed;
                 successful_transforms += 1;
             }
         }
 
-        // Simplification pass: collapse excessive negations (~~~~P → P)
-        simplify_negations(formula)
+        (formula, combo_transforms + successful_transforms)
     }
 
     /// Apply gnarly transformation combos that create especially difficult proofs.
@@ -1046,11 +1445,20 @@ impl ObfuscateGenerator {
     /// - Contraposition + De Morgan chains
     /// - Material Implication + Distribution (creates case splits)
     /// - Exportation + double negation
-    fn apply_gnarly_combos(&self, mut formula: Formula, rng: &mut impl Rng) -> Formula {
+    ///
+    /// A spec's `combo_chains` replace these; rules weighted 0 are skipped.
+    /// Returns the rewritten formula and the number of combo steps that
+    /// applied; each is also passed to `record`.
+    fn apply_gnarly_combos(
+        &self,
+        mut formula: Formula,
+        rng: &mut impl Rng,
+        record: &mut impl FnMut(EquivalenceRule, &Formula),
+    ) -> (Formula, usize) {
         // Pick 1-3 gnarly combos based on difficulty
         let combo_count = if self.config.difficulty_value >= 96 { 3 } else { 2 };
 
-        let gnarly_rules = [
+        let builtin = [
             // Combo 1: Contraposition + De Morgan chain
             vec![EquivalenceRule::Contraposition, EquivalenceRule::DeMorgan],
             // Combo 2: Implication + Distribution (creates case splits)
@@ -1060,6 +1468,7 @@ impl ObfuscateGenerator {
             // Combo 4: Equivalence + De Morgan
             vec![EquivalenceRule::Equivalence, EquivalenceRule::DeMorgan],
         ];
+        let gnarly_rules: &[Vec<EquivalenceRule>] = self.config.combo_chains.as_deref().unwrap_or(&builtin);
 
         // Shuffle and pick combos
         let mut indices: Vec<usize> = (0..gnarly_rules.len()).collect();
@@ -1068,26 +1477,41 @@ impl ObfuscateGenerator {
             indices.swap(i, j);
         }
 
+        let mut applied = 0;
         for i in 0..combo_count.min(indices.len()) {
             let combo = &gnarly_rules[indices[i]];
             for rule in combo {
+                if self.clock.spent(std::slice::from_ref(&formula)) {
+                    return (formula, applied);
+                }
+                if self.config.rule_weight(*rule) <= 0.0 {
+                    continue;
+                }
                 if let Some(transformed) = self.try_apply_specific_rule(&formula, *rule, rng) {
+                    record(*rule, &transformed);
                     formula = transformed;
+                    applied += 1;
                 }
             }
         }
 
-        formula
+        (formula, applied)
     }
 
-    /// Check tautology using the appropriate engine based on atom count.
-    fn check_tautology(&self, formula: &Formula) -> bool {
-        let atoms = formula.atoms();
+    /// Check that a rewrite preserved meaning, using the appropriate engine based
+    /// on atom count. For the wrapped tautology it is enough that the result is
+    /// still a tautology, one truth table; premises and conclusions kept
+    /// separate need a full equivalence check to preserve entailment.
+    fn check_equivalent(&self, before: &Formula, after: &Formula) -> bool {
+        if self.keep_premises {
+            return are_equivalent_dynamic(before, after);
+        }
+        let atoms = after.atoms();
         let standard = ["P", "Q", "R", "S", "T"];
         if atoms.len() <= 5 && atoms.iter().all(|a| standard.contains(&a.as_str())) {
-            is_tautology(formula)
+            is_tautology(after)
         } else {
-            is_tautology_dynamic(formula)
+            is_tautology_dynamic(after)
         }
     }
 
@@ -1113,7 +1537,7 @@ impl ObfuscateGenerator {
                 let equivalent = &equivalents[rng.gen_range(0..equivalents.len())];
                 let result = formula.replace_at_path(path, equivalent);
 
-                if self.check_tautology(&result) {
+                if self.check_equivalent(formula, &result) {
                     return Some(result);
                 }
             }
@@ -1122,10 +1546,68 @@ impl ObfuscateGenerator {
         None
     }
 
+    /// Run extra passes over `formulas` until `target.min` is reached, dropping
+    /// any pass that overshoots `target.max`; single rewrites finish the job
+    /// if the passes run out. Returns the rewrites applied.
+    fn grow_to_size(&self, formulas: &mut [Formula], target: &TargetSize, rng: &mut impl Rng) -> usize {
+        let mut applied = 0;
+        for _pass in 0..MAX_SIZE_PASSES {
+            if target.measure(formulas) >= target.min as usize || self.clock.spent(formulas) {
+                return applied;
+            }
+            let mut count = 0;
+            let rewritten: Vec<Formula> = formulas.iter()
+                .map(|f| {
+                    let (rewritten, n) = self.apply_transformations_counted(f.clone(), rng);
+                    count += n;
+                    rewritten
+                })
+                .collect();
+            if target.measure(&rewritten) <= target.max as usize {
+                formulas.clone_from_slice(&rewritten);
+                applied += count;
+            }
+        }
+        applied + self.rewrite_within(formulas, target, 0, rng)
+    }
+
+    /// Apply single random rewrites to `formulas`, keeping the total within
+    /// `target.max`, until at least `wanted` have applied and `target.min` is
+    /// reached. Shrinking rewrites are skipped while below `target.min`.
+    /// Gives up after `MAX_SIZE_STEPS` tries; returns the rewrites applied.
+    fn rewrite_within(&self, formulas: &mut [Formula], target: &TargetSize, wanted: usize, rng: &mut impl Rng) -> usize {
+        let mut size = target.measure(formulas);
+        let mut applied = 0;
+        for _step in 0..MAX_SIZE_STEPS {
+            if (applied >= wanted && size >= target.min as usize) || formulas.is_empty() {
+                break;
+            }
+            if self.clock.spent(formulas) {
+                break;
+            }
+            let index = rng.gen_range(0..formulas.len());
+            let Some((_, rewritten)) = self.try_apply_random_equivalence(&formulas[index], rng) else {
+                continue;
+            };
+            // Collapse stacked negations as a pass would, so size is not
+            // bought with ~~~~ chains
+            let original = std::mem::replace(&mut formulas[index], simplify_negations(rewritten));
+            let new_size = target.measure(formulas);
+            if new_size > target.max as usize || (size < target.min as usize && new_size < size) {
+                formulas[index] = original;
+            } else {
+                size = new_size;
+                applied += 1;
+            }
+        }
+        applied
+    }
+
     /// Try to apply a random equivalence transformation to a single subformula (positional).
     /// Uses path-based replacement so only the selected occurrence is transformed,
     /// allowing structurally identical subtrees to diverge across passes.
-    fn try_apply_random_equivalence(&self, formula: &Formula, rng: &mut impl Rng) -> Option<Formula> {
+    /// Returns the rule applied with the result.
+    fn try_apply_random_equivalence(&self, formula: &Formula, rng: &mut impl Rng) -> Option<(EquivalenceRule, Formula)> {
         // Get all subformulas with positional paths
         let subformulas = formula.subformulas_with_paths();
         if subformulas.is_empty() {
@@ -1144,39 +1626,31 @@ impl ObfuscateGenerator {
             let (path, subformula) = &subformulas[idx];
 
             // Get all applicable rules for this subformula
-            let applicable = self.find_applicable_rules(subformula);
+            let applicable = Self::find_applicable_rules(subformula);
             if applicable.is_empty() {
                 continue;
             }
 
-            // Weighted selection - de-weight size-exploding rules
-            // Distribution and Equivalence duplicate entire subtrees, causing exponential growth
-            let weights: Vec<f64> = applicable.iter().map(|(rule, _)| {
-                match rule {
-                    EquivalenceRule::Distribution | EquivalenceRule::Equivalence => 0.2,
-                    _ => 1.0,
-                }
-            }).collect();
+            // Weighted selection - size-exploding rules are de-weighted by
+            // default; the spec can reweight or exclude any rule
+            let weights: Vec<f64> = applicable.iter().map(|(rule, _)| self.config.rule_weight(*rule)).collect();
 
             let total_weight: f64 = weights.iter().sum();
-            let mut roll = rng.gen::<f64>() * total_weight;
-            let mut chosen_idx = 0;
-            for (i, &w) in weights.iter().enumerate() {
-                roll -= w;
-                if roll <= 0.0 {
-                    chosen_idx = i;
-                    break;
-                }
+            if total_weight <= 0.0 {
+                continue;
             }
+            let Some(chosen_idx) = pick_weighted(&weights, rng.gen::<f64>() * total_weight) else {
+                continue;
+            };
 
-            let (_rule, equivalent) = &applicable[chosen_idx];
+            let (rule, equivalent) = &applicable[chosen_idx];
 
             // Apply the transformation at this specific position only
             let result = formula.replace_at_path(path, equivalent);
 
-            // Sanity check: the result should still be a tautology
-            if self.check_tautology(&result) {
-                return Some(result);
+            // Sanity check: the rewrite should be an equivalence
+            if self.check_equivalent(formula, &result) {
+                return Some((*rule, result));
             }
             // If not (shouldn't happen), try another
         }
@@ -1184,8 +1658,10 @@ impl ObfuscateGenerator {
         None
     }
 
-    /// Find all rules that can be applied to this formula, with their results
-    fn find_applicable_rules(&self, formula: &Formula) -> Vec<(EquivalenceRule, Formula)> {
+    /// Find all rules that can be applied to this formula, with their results.
+    /// Tautology is contraction-only and DN introduction is capped, matching
+    /// what the obfuscation passes will actually apply.
+    pub fn find_applicable_rules(formula: &Formula) -> Vec<(EquivalenceRule, Formula)> {
         let mut results = Vec::new();
 
         for rule in EquivalenceRule::all() {
@@ -1271,11 +1747,45 @@ fn simplify_negations(formula: Formula) -> Formula {
     }
 }
 
+/// `simplify_negations` as recorded DN steps: removes the outermost `~~` of
+/// each negation stack, one step at a time.
+fn collapse_negations(mut formula: Formula, steps: &mut Vec<EquivalenceStep>) -> Formula {
+    loop {
+        let collapsed = formula.subformulas_with_paths().into_iter().find_map(|(path, subformula)| match subformula {
+            Formula::Not(inner) => match inner.as_ref() {
+                Formula::Not(body) => Some(formula.replace_at_path(&path, body)),
+                _ => None,
+            },
+            _ => None,
+        });
+        let Some(collapsed) = collapsed else {
+            return formula;
+        };
+        steps.push(EquivalenceStep { rule: EquivalenceRule::DoubleNegation, result: collapsed.clone() });
+        formula = collapsed;
+    }
+}
+
+/// Index picked by `roll` in `[0, total weight)`. Rounding can leave `roll`
+/// past the last weight; the pick then falls back to the last index with a
+/// positive weight, so a rule weighted 0 is never chosen.
+fn pick_weighted(weights: &[f64], mut roll: f64) -> Option<usize> {
+    let last = weights.iter().rposition(|&w| w > 0.0)?;
+    for (i, &w) in weights.iter().enumerate() {
+        roll -= w;
+        if w > 0.0 && roll <= 0.0 {
+            return Some(i);
+        }
+    }
+    Some(last)
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::theorem::SizeMetric;
     use crate::services::truth_table::{is_tautology, are_equivalent};
+    use crate::services::budget::{estimated_bytes, BudgetExceeded};
 
     #[test]
     fn test_config_easy() {
@@ -1712,6 +2222,9 @@ mod tests {
             max_formula_depth: None,
             bridge_atoms: None,
             gnarly_combos: None,
+            target_size: None,
+            rule_weights: None,
+            combo_chains: None,
         });
         let gen = ObfuscateGenerator::new(config);
         let mut rng = rand::thread_rng();
@@ -1768,6 +2281,9 @@ mod tests {
             max_formula_depth: None,
             bridge_atoms: Some(2),
             gnarly_combos: None,
+            target_size: None,
+            rule_weights: None,
+            combo_chains: None,
         };
 
         let config = ObfuscateConfig::from_spec(&spec);
@@ -1798,6 +2314,9 @@ mod tests {
             max_formula_depth: None,
             bridge_atoms: Some(0),
             gnarly_combos: None,
+            target_size: None,
+            rule_weights: None,
+            combo_chains: None,
         };
 
         let mut rng = rand::thread_rng();
@@ -1821,4 +2340,187 @@ mod tests {
         assert_eq!(DifficultySpec::from_tier(DifficultyTier::Cosmic).bridge_atoms, Some(2));
         assert_eq!(DifficultySpec::from_tier(DifficultyTier::Mind).bridge_atoms, Some(2));
     }
+
+    #[test]
+    fn test_base_class_recorded() {
+        use crate::models::theorem::DifficultyTier;
+        use crate::services::semantic_class::theorem_class;
+
+        let mut rng = rand::thread_rng();
+        for tier in [DifficultyTier::Easy, DifficultyTier::Hard] {
+            let theorem = ObfuscateGenerator::generate_with_tier(tier, &mut rng);
+            let base_class = theorem.base_class.clone().expect("spec pipeline should record the base class");
+            assert!(base_class.starts_with('n'), "base forms are small enough to canonicalise: {}", base_class);
+            assert_eq!(theorem_class(&theorem), base_class, "an obfuscated tautology is classed by its base argument");
+        }
+    }
+
+    #[test]
+    fn test_argument_pipeline_keeps_premises() {
+        use crate::models::theorem::DifficultyTier;
+        use crate::services::truth_table::validate_theorem_dynamic;
+
+        let mut rng = rand::thread_rng();
+        for tier in [DifficultyTier::Easy, DifficultyTier::Hard, DifficultyTier::Expert] {
+            let spec = DifficultySpec::from_tier(tier);
+            for _ in 0..5 {
+                let theorem = ObfuscateGenerator::generate_argument_with_tier_spec(tier, &spec, &mut rng)
+                    .expect("argument pipeline should find a non-degenerate argument");
+                assert!(theorem.premises.len() >= 2, "single-premise arguments are degenerate");
+                assert!(validate_theorem_dynamic(&theorem.premises, &theorem.conclusion).is_ok());
+                assert_eq!(theorem.tier, Some(tier));
+                assert!(theorem.base_class.is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn test_argument_pipeline_many_variables() {
+        use crate::models::theorem::DifficultyTier;
+        use crate::services::truth_table::validate_theorem_dynamic;
+
+        let spec = DifficultySpec::from_tier(DifficultyTier::Cosmic);
+        let mut rng = rand::thread_rng();
+        let theorem = ObfuscateGenerator::generate_argument_with_spec(&spec, &mut rng)
+            .expect("argument pipeline should find a non-degenerate argument");
+        assert!(validate_theorem_dynamic(&theorem.premises, &theorem.conclusion).is_ok());
+    }
+
+    #[test]
+    fn test_transformations_preserve_non_tautologies() {
+        let config = ObfuscateConfig::for_difficulty_value(50);
+        let gen = ObfuscateGenerator::new(config).keeping_premises(true);
+        let mut rng = rand::thread_rng();
+        let premise = Formula::Implies(
+            Box::new(Formula::Atom("P".to_string())),
+            Box::new(Formula::Or(
+                Box::new(Formula::Atom("Q".to_string())),
+                Box::new(Formula::Atom("R".to_string())),
+            )),
+        );
+        for _ in 0..10 {
+            let transformed = gen.apply_transformations(premise.clone(), &mut rng);
+            assert!(are_equivalent(&premise, &transformed));
+        }
+    }
+
+    #[test]
+    fn test_generate_gated_accepts_and_counts() {
+        let mut rng = rand::thread_rng();
+        let spec = DifficultySpec::from_tier(DifficultyTier::Medium);
+        let gates = QualityGates::default();
+        let mut stats = RejectionStats::default();
+        for _ in 0..5 {
+            let theorem = ObfuscateGenerator::generate_gated(
+                &spec, Some(DifficultyTier::Medium), false, &gates, &GenerationBudget::default(), &mut stats, &mut rng,
+            ).unwrap();
+            assert!(gates.check(&theorem.premises, &theorem.conclusion, 1).is_ok());
+        }
+        assert_eq!(stats.accepted, 5);
+    }
+
+    #[test]
+    fn test_generate_gated_exhausts_retries() {
+        let mut rng = rand::thread_rng();
+        let spec = DifficultySpec::from_tier(DifficultyTier::Easy);
+        let gates = QualityGates { min_nodes: Some(100_000), max_retries: 3, ..QualityGates::default() };
+        let mut stats = RejectionStats::default();
+        let err = ObfuscateGenerator::generate_gated(&spec, None, false, &gates, &GenerationBudget::default(), &mut stats, &mut rng)
+            .unwrap_err();
+        assert_eq!(err.attempts, 3);
+        assert_eq!(stats.accepted, 0);
+        assert_eq!(stats.total_rejected(), 3);
+    }
+
+    #[test]
+    fn test_generate_gated_hits_target_size() {
+        let mut rng = rand::thread_rng();
+        let mut spec = DifficultySpec::from_tier(DifficultyTier::Hard);
+        let target = TargetSize { metric: SizeMetric::Nodes, min: 25, max: 45 };
+        spec.target_size = Some(target);
+        let gates = QualityGates { max_retries: 200, ..QualityGates::default() };
+        let mut stats = RejectionStats::default();
+        for _ in 0..5 {
+            let theorem = ObfuscateGenerator::generate_gated(
+                &spec, Some(DifficultyTier::Hard), false, &gates, &GenerationBudget::default(), &mut stats, &mut rng,
+            ).unwrap();
+            assert!(target.contains(theorem.conclusion.node_count()));
+            assert!(is_tautology_dynamic(&theorem.conclusion));
+        }
+    }
+
+    #[test]
+    fn test_rule_weights_exclude_rules() {
+        // Only Commutation may fire, so no rewrite (combos included) can change the node count
+        let mut weights: BTreeMap<EquivalenceRule, f64> = EquivalenceRule::all().into_iter().map(|r| (r, 0.0)).collect();
+        weights.insert(EquivalenceRule::Commutation, 1.0);
+        let mut spec = DifficultySpec::from_tier(DifficultyTier::Expert);
+        spec.rule_weights = Some(weights);
+        spec.combo_chains = Some(vec![vec![EquivalenceRule::Implication, EquivalenceRule::Commutation]]);
+        let gen = ObfuscateGenerator::new(ObfuscateConfig::from_spec(&spec)).keeping_premises(true);
+        assert!(gen.config.gnarly_combos);
+
+        let mut rng = rand::thread_rng();
+        let formula = Formula::parse("((P & Q) | R) -> (~S | (R & P))").unwrap();
+        for _ in 0..10 {
+            let (rewritten, applied) = gen.apply_transformations_counted(formula.clone(), &mut rng);
+            assert!(applied > 0);
+            assert_eq!(rewritten.node_count(), formula.node_count());
+            assert!(are_equivalent(&formula, &rewritten));
+        }
+    }
+
+    #[test]
+    fn test_pick_weighted_skips_excluded_rules() {
+        let weights = [0.0, 0.3, 0.3, 0.0];
+        assert_eq!(pick_weighted(&weights, 0.0), Some(1));
+        assert_eq!(pick_weighted(&weights, 0.5), Some(2));
+        // A roll left past the total by rounding falls back to the last positive weight
+        assert_eq!(pick_weighted(&weights, 0.6 + 1e-9), Some(2));
+        assert_eq!(pick_weighted(&[0.0, 0.0], 0.0), None);
+    }
+
+    #[test]
+    fn test_budget_policies() {
+        let mut rng = rand::thread_rng();
+        let spec = DifficultySpec::from_tier(DifficultyTier::Marathon);
+        let gates = QualityGates::default();
+        let mut budget = GenerationBudget { max_kilobytes: Some(2), ..GenerationBudget::default() };
+
+        let mut stats = RejectionStats::default();
+        let err = ObfuscateGenerator::generate_budgeted(&spec, None, false, &gates, &budget, &mut stats, &mut rng)
+            .unwrap_err();
+        assert!(matches!(err.last, Rejection::OverBudget(BudgetExceeded::Memory { limit_kilobytes: 2, .. })));
+        assert_eq!(stats.rejected.get("over_memory_budget"), Some(&1));
+
+        budget.policy = BudgetPolicy::Skip;
+        let mut stats = RejectionStats::default();
+        let skipped = ObfuscateGenerator::generate_budgeted(&spec, None, true, &gates, &budget, &mut stats, &mut rng)
+            .unwrap();
+        assert!(skipped.is_none());
+        assert_eq!(stats.skipped, 1);
+
+        budget.policy = BudgetPolicy::Retry;
+        let mut stats = RejectionStats::default();
+        let (theorem, used) = ObfuscateGenerator::generate_budgeted(&spec, None, false, &gates, &budget, &mut stats, &mut rng)
+            .unwrap()
+            .unwrap();
+        assert!(stats.shrunk > 0);
+        assert!(used.substitution_depth < spec.substitution_depth);
+        assert!(estimated_bytes(theorem.conclusion.node_count()) <= 2 * 1024);
+        assert!(is_tautology_dynamic(&theorem.conclusion));
+    }
+
+    #[test]
+    fn test_time_budget_cancels() {
+        let mut rng = rand::thread_rng();
+        let spec = DifficultySpec::from_tier(DifficultyTier::Mind);
+        let budget = GenerationBudget { max_millis: Some(0), ..GenerationBudget::default() };
+        let mut stats = RejectionStats::default();
+        let err = ObfuscateGenerator::generate_gated(
+            &spec, None, false, &QualityGates::default(), &budget, &mut stats, &mut rng,
+        ).unwrap_err();
+        assert_eq!(err.attempts, 1);
+        assert!(matches!(err.last, Rejection::OverBudget(BudgetExceeded::Time { limit_millis: 0 })));
+    }
 }
@@ -186,7 +186,7 @@ impl ProofNode {
 }
 
 /// Check if a rule name represents a nesting (subproof-creating) rule
-fn is_nesting_rule(rule: &str) -> bool {
+pub(crate) fn is_nesting_rule(rule: &str) -> bool {
     matches!(rule, "CP" | "IP" | "NegIntro" | "CaseSplit")
 }
 
@@ -273,8 +273,11 @@ impl std::fmt::Display for DegenerateProofError {
 }
 
 
-/// Represents a complete proof tree with metadata
+/// Represents a complete proof tree with metadata.
+/// Serialized flat (see `FlatProofTree`), so long proofs stay within
+/// serde_json's recursion limit.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "FlatProofTree", try_from = "FlatProofTree")]
 pub struct ProofTree {
     /// The root node of the proof tree
     pub root: ProofNode,
@@ -378,6 +381,83 @@ impl ProofTree {
     }
 }
 
+/// Serialized form of a `ProofTree`: its nodes in post-order, each
+/// derivation citing its children by index, with the root last. The JSON
+/// nests no deeper than the formulas, however long the proof.
+#[derive(Serialize, Deserialize)]
+struct FlatProofTree {
+    nodes: Vec<FlatProofNode>,
+    fragment_count: usize,
+    max_nesting: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+enum FlatProofNode {
+    Premise(Formula),
+    Assumption(Formula),
+    Derivation {
+        result: Formula,
+        rule: String,
+        children: Vec<usize>,
+        assumption: Option<Formula>,
+    },
+}
+
+impl FlatProofTree {
+    /// Append `node`'s subtree in post-order and return the node's index
+    fn push(nodes: &mut Vec<FlatProofNode>, node: ProofNode) -> usize {
+        let flat = match node {
+            ProofNode::Premise(f) => FlatProofNode::Premise(f),
+            ProofNode::Assumption(f) => FlatProofNode::Assumption(f),
+            ProofNode::Derivation { result, rule, children, assumption } => FlatProofNode::Derivation {
+                result,
+                rule,
+                children: children.into_iter().map(|child| Self::push(nodes, child)).collect(),
+                assumption,
+            },
+        };
+        nodes.push(flat);
+        nodes.len() - 1
+    }
+}
+
+impl From<ProofTree> for FlatProofTree {
+    fn from(tree: ProofTree) -> Self {
+        let mut nodes = Vec::new();
+        Self::push(&mut nodes, tree.root);
+        Self { nodes, fragment_count: tree.fragment_count, max_nesting: tree.max_nesting }
+    }
+}
+
+impl TryFrom<FlatProofTree> for ProofTree {
+    type Error = String;
+
+    fn try_from(flat: FlatProofTree) -> Result<Self, Self::Error> {
+        // Every node but the root is the child of exactly one later node
+        let mut built: Vec<Option<ProofNode>> = Vec::with_capacity(flat.nodes.len());
+        for (index, node) in flat.nodes.into_iter().enumerate() {
+            let node = match node {
+                FlatProofNode::Premise(f) => ProofNode::Premise(f),
+                FlatProofNode::Assumption(f) => ProofNode::Assumption(f),
+                FlatProofNode::Derivation { result, rule, children, assumption } => {
+                    let children = children.into_iter()
+                        .map(|child| built.get_mut(child).and_then(Option::take).ok_or_else(|| format!(
+                            "proof node {} cites node {}, which is not an earlier unused node", index, child
+                        )))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    ProofNode::Derivation { result, rule, children, assumption }
+                }
+            };
+            built.push(Some(node));
+        }
+        let root = built.pop().flatten().ok_or("proof tree has no nodes")?;
+        if built.iter().any(Option::is_some) {
+            return Err("proof tree has nodes outside the root's derivation".to_string());
+        }
+        Ok(Self { root, fragment_count: flat.fragment_count, max_nesting: flat.max_nesting })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -744,4 +824,31 @@ mod tests {
         assert!(output.contains("MP"));
         assert!(output.contains("Premise:"));
     }
+
+    #[test]
+    fn test_serialized_flat() {
+        // A chain deeper than serde_json's recursion limit of 128 reads back
+        let pq = Formula::And(Box::new(atom("P")), Box::new(atom("Q")));
+        let qp = Formula::And(Box::new(atom("Q")), Box::new(atom("P")));
+        let mut node = ProofNode::premise(pq.clone());
+        for step in 0..200 {
+            let result = if step % 2 == 0 { qp.clone() } else { pq.clone() };
+            node = ProofNode::derivation(result, "Comm", vec![node], None);
+        }
+        let tree = ProofTree::new(ProofNode::derivation(
+            Formula::And(Box::new(pq.clone()), Box::new(pq)),
+            "Conj",
+            vec![node.clone(), node],
+            None,
+        ));
+        let json = serde_json::to_string(&tree).unwrap();
+        let back: ProofTree = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&back).unwrap(), json);
+        assert_eq!(back.fragment_count, tree.fragment_count);
+        assert_eq!(back.premises(), tree.premises());
+
+        // Children must be earlier nodes, each cited once
+        let bad = r#"{"nodes":[{"Premise":{"Atom":"P"}},{"Derivation":{"result":{"Atom":"P"},"rule":"Conj","children":[0,0],"assumption":null}}],"fragment_count":1,"max_nesting":0}"#;
+        assert!(serde_json::from_str::<ProofTree>(bad).is_err());
+    }
 }
@@ -0,0 +1,397 @@
+//! Acceptance criteria for spec-generated theorems.
+//!
+//! The spec pipeline stops passes early once a formula reaches
+//! `max_formula_nodes`/depth, and small tiers regularly produce shapes such as
+//! `(P . Q) > P` that a model can answer without reasoning. `QualityGates`
+//! states what a generated theorem must satisfy; the generator retries until
+//! one passes and records why the others were rejected in `RejectionStats`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::models::{Formula, TargetSize};
+use super::budget::BudgetExceeded;
+use super::proof_tree::DegenerateProofError;
+use super::truth_table::{is_tautology_dynamic, validate_theorem_dynamic};
+
+/// Default generation attempts per theorem before giving up.
+pub const DEFAULT_MAX_RETRIES: usize = 20;
+
+/// Acceptance criteria for one generated theorem. Node and atom counts are
+/// taken over the premises and conclusion together.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityGates {
+    #[serde(default)]
+    pub min_nodes: Option<usize>,
+    #[serde(default)]
+    pub max_nodes: Option<usize>,
+    /// Distinct atoms that must actually occur
+    #[serde(default)]
+    pub min_atoms: Option<usize>,
+    /// Equivalence rewrites that must have been applied across all passes
+    #[serde(default)]
+    pub min_transforms: Option<usize>,
+    /// Reject shapes that are answerable at a glance (see `is_trivial_shape`)
+    #[serde(default = "default_true")]
+    pub reject_trivial_shape: bool,
+    /// Attempts per theorem, including the accepted one
+    #[serde(default = "default_max_retries")]
+    pub max_retries: usize,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_retries() -> usize {
+    DEFAULT_MAX_RETRIES
+}
+
+impl Default for QualityGates {
+    fn default() -> Self {
+        Self {
+            min_nodes: None,
+            max_nodes: None,
+            min_atoms: Some(2),
+            min_transforms: Some(1),
+            reject_trivial_shape: true,
+            max_retries: DEFAULT_MAX_RETRIES,
+        }
+    }
+}
+
+/// Why a generated theorem was rejected
+#[derive(Debug, Clone)]
+pub enum Rejection {
+    /// A tautology-format theorem whose formula is not a tautology
+    NotValid,
+    /// A premise-bearing theorem that failed `validate_theorem_dynamic`, or a
+    /// tautology-format one whose antecedents are jointly contradictory
+    Degenerate(DegenerateProofError),
+    TooFewNodes { nodes: usize, min: usize },
+    TooManyNodes { nodes: usize, max: usize },
+    TooFewAtoms { atoms: usize, min: usize },
+    TooFewTransforms { applied: usize, min: usize },
+    TrivialShape,
+    /// Outside the spec's `TargetSize` band
+    OffTargetSize { size: usize, min: u32, max: u32 },
+    /// Generation ran over its `GenerationBudget` and was cut short
+    OverBudget(BudgetExceeded),
+}
+
+impl Rejection {
+    /// Stable key used when counting rejections.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Rejection::NotValid => "not_valid",
+            Rejection::Degenerate(_) => "degenerate",
+            Rejection::TooFewNodes { .. } => "too_few_nodes",
+            Rejection::TooManyNodes { .. } => "too_many_nodes",
+            Rejection::TooFewAtoms { .. } => "too_few_atoms",
+            Rejection::TooFewTransforms { .. } => "too_few_transforms",
+            Rejection::TrivialShape => "trivial_shape",
+            Rejection::OffTargetSize { .. } => "off_target_size",
+            Rejection::OverBudget(BudgetExceeded::Time { .. }) => "over_time_budget",
+            Rejection::OverBudget(BudgetExceeded::Memory { .. }) => "over_memory_budget",
+        }
+    }
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rejection::NotValid => write!(f, "formula is not a tautology"),
+            Rejection::Degenerate(e) => write!(f, "{}", e),
+            Rejection::TooFewNodes { nodes, min } => write!(f, "{} nodes, need at least {}", nodes, min),
+            Rejection::TooManyNodes { nodes, max } => write!(f, "{} nodes, allowed at most {}", nodes, max),
+            Rejection::TooFewAtoms { atoms, min } => write!(f, "{} distinct atoms, need at least {}", atoms, min),
+            Rejection::TooFewTransforms { applied, min } => {
+                write!(f, "{} rewrites applied, need at least {}", applied, min)
+            }
+            Rejection::TrivialShape => write!(f, "conclusion is trivially available"),
+            Rejection::OffTargetSize { size, min, max } => {
+                write!(f, "size {} is outside the target band {}-{}", size, min, max)
+            }
+            Rejection::OverBudget(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// No candidate passed the gates within `max_retries` attempts
+#[derive(Debug, Clone)]
+pub struct QualityGateError {
+    pub attempts: usize,
+    pub last: Rejection,
+}
+
+impl std::fmt::Display for QualityGateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no theorem passed the quality gates in {} attempts (last rejection: {})", self.attempts, self.last)
+    }
+}
+
+/// Accepted theorems and rejection counts by `Rejection::key`, plus what the
+/// `BudgetPolicy` did with theorems that ran over budget.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RejectionStats {
+    pub accepted: usize,
+    pub rejected: BTreeMap<String, usize>,
+    /// Retries with a smaller spec
+    #[serde(default)]
+    pub shrunk: usize,
+    /// Theorems left out of the set
+    #[serde(default)]
+    pub skipped: usize,
+}
+
+impl RejectionStats {
+    pub fn record(&mut self, rejection: &Rejection) {
+        *self.rejected.entry(rejection.key().to_string()).or_insert(0) += 1;
+    }
+
+    /// Add another run's counts, e.g. from a parallel worker.
+    pub fn merge(&mut self, other: &RejectionStats) {
+        self.accepted += other.accepted;
+        self.shrunk += other.shrunk;
+        self.skipped += other.skipped;
+        for (key, count) in &other.rejected {
+            *self.rejected.entry(key.clone()).or_insert(0) += count;
+        }
+    }
+
+    pub fn total_rejected(&self) -> usize {
+        self.rejected.values().sum()
+    }
+
+    /// One-line summary, e.g. "20 accepted, 3 rejected (trivial_shape 2, too_few_atoms 1)",
+    /// followed by shrunk/skipped counts when there are any.
+    pub fn summary(&self) -> String {
+        let mut summary = if self.rejected.is_empty() {
+            format!("{} accepted, 0 rejected", self.accepted)
+        } else {
+            let reasons: Vec<String> = self.rejected.iter()
+                .map(|(key, count)| format!("{} {}", key, count))
+                .collect();
+            format!("{} accepted, {} rejected ({})", self.accepted, self.total_rejected(), reasons.join(", "))
+        };
+        if self.shrunk > 0 {
+            summary.push_str(&format!(", {} shrunk", self.shrunk));
+        }
+        if self.skipped > 0 {
+            summary.push_str(&format!(", {} skipped", self.skipped));
+        }
+        summary
+    }
+}
+
+impl QualityGates {
+    /// Check one candidate. An empty `premises` means a tautology-format theorem.
+    /// Validity is always re-checked, independently of debug assertions.
+    pub fn check(&self, premises: &[Formula], conclusion: &Formula, transforms_applied: usize) -> Result<(), Rejection> {
+        if premises.is_empty() {
+            if !is_tautology_dynamic(conclusion) {
+                return Err(Rejection::NotValid);
+            }
+            // (P1 ∧ P2) ⊃ C with unsatisfiable P1 ∧ P2 holds by explosion
+            let (antecedents, _) = curried_antecedents(conclusion);
+            if let Some(assumed) = conjoin(&antecedents) {
+                if is_tautology_dynamic(&Formula::Not(Box::new(assumed))) {
+                    return Err(Rejection::Degenerate(DegenerateProofError::ContradictoryPremises));
+                }
+            }
+        } else {
+            validate_theorem_dynamic(premises, conclusion).map_err(Rejection::Degenerate)?;
+        }
+
+        let formulas = || premises.iter().chain(std::iter::once(conclusion));
+        let nodes: usize = formulas().map(|f| f.node_count()).sum();
+        if let Some(min) = self.min_nodes {
+            if nodes < min {
+                return Err(Rejection::TooFewNodes { nodes, min });
+            }
+        }
+        if let Some(max) = self.max_nodes {
+            if nodes > max {
+                return Err(Rejection::TooManyNodes { nodes, max });
+            }
+        }
+        if let Some(min) = self.min_atoms {
+            let atoms: HashSet<String> = formulas().flat_map(|f| f.atoms()).collect();
+            if atoms.len() < min {
+                return Err(Rejection::TooFewAtoms { atoms: atoms.len(), min });
+            }
+        }
+        if let Some(min) = self.min_transforms {
+            if transforms_applied < min {
+                return Err(Rejection::TooFewTransforms { applied: transforms_applied, min });
+            }
+        }
+        if self.reject_trivial_shape && is_trivial_shape(premises, conclusion) {
+            return Err(Rejection::TrivialShape);
+        }
+        Ok(())
+    }
+}
+
+/// Check a finished theorem against a spec's `TargetSize` band.
+pub fn check_target_size(target: &TargetSize, premises: &[Formula], conclusion: &Formula) -> Result<(), Rejection> {
+    let mut formulas = premises.to_vec();
+    formulas.push(conclusion.clone());
+    let size = target.measure(&formulas);
+    if target.contains(size) {
+        Ok(())
+    } else {
+        Err(Rejection::OffTargetSize { size, min: target.min, max: target.max })
+    }
+}
+
+/// A theorem is trivially shaped when, after currying the conclusion
+/// (`A ⊃ (B ⊃ C)` assumes A and B), its final consequent is a tautology or
+/// literally a premise or assumption, or one of their top-level conjuncts.
+/// Also catches `A ∨ ~A` and disjunctions with a tautological disjunct.
+pub fn is_trivial_shape(premises: &[Formula], conclusion: &Formula) -> bool {
+    let (antecedents, consequent) = curried_antecedents(conclusion);
+    let mut hypotheses: Vec<&Formula> = Vec::new();
+    for formula in premises.iter().chain(antecedents) {
+        if matches!(formula, Formula::And(..)) {
+            hypotheses.push(formula);
+        }
+        collect_conjuncts(formula, &mut hypotheses);
+    }
+
+    if hypotheses.contains(&consequent) {
+        return true;
+    }
+    if let Formula::Or(left, right) = consequent {
+        if is_negation_of(left, right) || is_negation_of(right, left)
+            || is_tautology_dynamic(left) || is_tautology_dynamic(right)
+        {
+            return true;
+        }
+    }
+    // A tautology-format theorem is itself a tautology; only a proper
+    // consequent (or an argument's conclusion) being one is trivial.
+    let proper = !premises.is_empty() || !std::ptr::eq(consequent, conclusion);
+    proper && is_tautology_dynamic(consequent)
+}
+
+/// Split `A ⊃ (B ⊃ C)` into its antecedents `[A, B]` and final consequent `C`.
+fn curried_antecedents(formula: &Formula) -> (Vec<&Formula>, &Formula) {
+    let mut antecedents = Vec::new();
+    let mut consequent = formula;
+    while let Formula::Implies(antecedent, rest) = consequent {
+        antecedents.push(antecedent.as_ref());
+        consequent = rest;
+    }
+    (antecedents, consequent)
+}
+
+fn conjoin(formulas: &[&Formula]) -> Option<Formula> {
+    formulas.iter()
+        .map(|f| (*f).clone())
+        .reduce(|acc, f| Formula::And(Box::new(acc), Box::new(f)))
+}
+
+fn collect_conjuncts<'a>(formula: &'a Formula, out: &mut Vec<&'a Formula>) {
+    match formula {
+        Formula::And(left, right) => {
+            collect_conjuncts(left, out);
+            collect_conjuncts(right, out);
+        }
+        other => out.push(other),
+    }
+}
+
+fn is_negation_of(formula: &Formula, other: &Formula) -> bool {
+    matches!(formula, Formula::Not(inner) if inner.as_ref() == other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atom(name: &str) -> Formula {
+        Formula::Atom(name.to_string())
+    }
+
+    fn not(f: Formula) -> Formula {
+        Formula::Not(Box::new(f))
+    }
+
+    fn and(a: Formula, b: Formula) -> Formula {
+        Formula::And(Box::new(a), Box::new(b))
+    }
+
+    fn or(a: Formula, b: Formula) -> Formula {
+        Formula::Or(Box::new(a), Box::new(b))
+    }
+
+    fn implies(a: Formula, b: Formula) -> Formula {
+        Formula::Implies(Box::new(a), Box::new(b))
+    }
+
+    #[test]
+    fn test_trivial_shapes() {
+        let (p, q) = (atom("P"), atom("Q"));
+        // (P . Q) > P
+        assert!(is_trivial_shape(&[], &implies(and(p.clone(), q.clone()), p.clone())));
+        // P > (Q > P)
+        assert!(is_trivial_shape(&[], &implies(p.clone(), implies(q.clone(), p.clone()))));
+        // Q > (P > P)
+        assert!(is_trivial_shape(&[], &implies(q.clone(), implies(p.clone(), p.clone()))));
+        // ~P v P
+        assert!(is_trivial_shape(&[], &or(not(p.clone()), p.clone())));
+        // ~Q v (P > P)
+        assert!(is_trivial_shape(&[], &or(not(q.clone()), implies(p.clone(), p.clone()))));
+        // P . Q ⊢ Q
+        assert!(is_trivial_shape(&[and(p.clone(), q.clone())], &q));
+        // (P . Q) > (P . Q)
+        assert!(is_trivial_shape(&[], &implies(and(p.clone(), q.clone()), and(p.clone(), q.clone()))));
+        // P . Q ⊢ P . Q
+        assert!(is_trivial_shape(&[and(p.clone(), q.clone())], &and(p.clone(), q.clone())));
+    }
+
+    #[test]
+    fn test_non_trivial_shapes() {
+        let (p, q) = (atom("P"), atom("Q"));
+        // ((P > Q) . P) > Q
+        assert!(!is_trivial_shape(&[], &implies(and(implies(p.clone(), q.clone()), p.clone()), q.clone())));
+        // ~((P > Q) . P) v Q
+        assert!(!is_trivial_shape(&[], &or(not(and(implies(p.clone(), q.clone()), p.clone())), q.clone())));
+        // P > Q, P ⊢ Q
+        assert!(!is_trivial_shape(&[implies(p.clone(), q.clone()), p], &q));
+    }
+
+    #[test]
+    fn test_check_reports_first_failed_gate() {
+        let mp = implies(and(implies(atom("P"), atom("Q")), atom("P")), atom("Q"));
+        let gates = QualityGates { min_nodes: Some(10), ..QualityGates::default() };
+        assert!(matches!(gates.check(&[], &mp, 3), Err(Rejection::TooFewNodes { nodes: 7, min: 10 })));
+        assert!(matches!(QualityGates::default().check(&[], &mp, 0), Err(Rejection::TooFewTransforms { .. })));
+        assert!(QualityGates::default().check(&[], &mp, 3).is_ok());
+        let repeat = implies(and(atom("P"), atom("Q")), and(atom("P"), atom("Q")));
+        assert!(matches!(QualityGates::default().check(&[], &repeat, 3), Err(Rejection::TrivialShape)));
+        // P > (~P > Q): valid only by explosion
+        let explosion = implies(atom("P"), implies(not(atom("P")), atom("Q")));
+        assert!(matches!(QualityGates::default().check(&[], &explosion, 3),
+            Err(Rejection::Degenerate(DegenerateProofError::ContradictoryPremises))));
+        assert!(matches!(QualityGates::default().check(&[], &atom("P"), 3), Err(Rejection::NotValid)));
+    }
+
+    #[test]
+    fn test_stats_summary() {
+        let mut stats = RejectionStats { accepted: 2, ..Default::default() };
+        stats.record(&Rejection::TrivialShape);
+        stats.record(&Rejection::TrivialShape);
+        stats.record(&Rejection::NotValid);
+        assert_eq!(stats.total_rejected(), 3);
+        assert_eq!(stats.summary(), "2 accepted, 3 rejected (not_valid 1, trivial_shape 2)");
+
+        stats.record(&Rejection::OverBudget(BudgetExceeded::Time { limit_millis: 50 }));
+        stats.shrunk = 1;
+        assert_eq!(
+            stats.summary(),
+            "2 accepted, 4 rejected (not_valid 1, over_time_budget 1, trivial_shape 2), 1 shrunk"
+        );
+    }
+}
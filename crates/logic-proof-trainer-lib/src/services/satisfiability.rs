@@ -0,0 +1,217 @@
+//! Satisfiability tasks: find an assignment making every formula of a set
+//! true, or show that none exists.
+//!
+//! Sets are drawn from the same random formulas the proof-tree generator uses
+//! (`ConstructionContext::random_formula`) over at most the five standard
+//! atoms, so the 32-row truth tables decide them. No formula is a tautology
+//! or a contradiction, and an unsatisfiable set of three or more formulas is
+//! not refuted by a single clashing pair.
+
+use std::collections::BTreeMap;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::models::Formula;
+use super::judgment::countermodel;
+use super::obfuscate_gen::build_atom_pool;
+use super::tree_gen::{ConstructionContext, RequiredTechniques, TreeGenConfig};
+use super::truth_table::{compute_truth_table, is_contradiction, is_tautology, premises_consistent};
+
+/// Allowed atom counts (the standard atoms P-T)
+pub const SAT_ATOMS_RANGE: (usize, usize) = (2, 5);
+/// Allowed numbers of formulas per set
+pub const SAT_FORMULAS_RANGE: (usize, usize) = (2, 12);
+/// Allowed formula depths
+pub const SAT_DEPTH_RANGE: (usize, usize) = (1, 6);
+
+/// Sets drawn before giving up
+pub const MAX_SAT_ATTEMPTS: usize = 200;
+/// Formulas drawn per position of a set before the set is abandoned
+const MAX_SAT_DRAWS: usize = 100;
+
+/// Configuration for satisfiability sets
+#[derive(Debug, Clone)]
+pub struct SatConfig {
+    /// Atoms the formulas are drawn over
+    pub atoms: usize,
+    /// Formulas per set
+    pub formulas: usize,
+    /// Maximum depth of each formula
+    pub depth: usize,
+}
+
+impl SatConfig {
+    pub fn new(atoms: usize, formulas: usize, depth: usize) -> Self {
+        Self { atoms, formulas, depth }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value, (min, max)) in [
+            ("atoms", self.atoms, SAT_ATOMS_RANGE),
+            ("formulas", self.formulas, SAT_FORMULAS_RANGE),
+            ("depth", self.depth, SAT_DEPTH_RANGE),
+        ] {
+            if !(min..=max).contains(&value) {
+                return Err(format!("{} must be {}-{}, got {}", name, min, max, value));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A set of formulas, with an assignment satisfying them all if there is one
+#[derive(Debug, Clone)]
+pub struct SatProblem {
+    pub formulas: Vec<Formula>,
+    pub model: Option<BTreeMap<String, bool>>,
+}
+
+impl SatProblem {
+    pub fn is_satisfiable(&self) -> bool {
+        self.model.is_some()
+    }
+}
+
+/// No set with the wanted answer was found
+#[derive(Debug, Clone)]
+pub struct SatError {
+    pub satisfiable: bool,
+}
+
+impl std::fmt::Display for SatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "No {} formula set found after {} attempts",
+            if self.satisfiable { "satisfiable" } else { "unsatisfiable" },
+            MAX_SAT_ATTEMPTS
+        )
+    }
+}
+
+/// Why an assignment does not satisfy a formula set
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssignmentError {
+    /// An atom of the set has no value
+    MissingAtom(String),
+    /// The formula at this index is false
+    FormulaFalse(usize),
+}
+
+impl std::fmt::Display for AssignmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssignmentError::MissingAtom(atom) => write!(f, "Atom {} has no value", atom),
+            AssignmentError::FormulaFalse(i) => write!(f, "Formula {} is false under the assignment", i + 1),
+        }
+    }
+}
+
+/// Draw a set of `config.formulas` distinct formulas, none a tautology or a
+/// contradiction, that is satisfiable or not as asked. All but one formula
+/// are committed through a `ConstructionContext`, which keeps them
+/// consistent; the last one decides the answer.
+pub fn generate_sat_problem(config: &SatConfig, satisfiable: bool, rng: &mut impl Rng) -> Result<SatProblem, SatError> {
+    let tree_config = TreeGenConfig {
+        atom_pool: build_atom_pool(config.atoms as u8),
+        ..TreeGenConfig::for_difficulty_value(50)
+    };
+    for _attempt in 0..MAX_SAT_ATTEMPTS {
+        let mut context = ConstructionContext::new(&tree_config, RequiredTechniques::default());
+        let mut draws = 0;
+        while context.premises.len() + 1 < config.formulas && draws < MAX_SAT_DRAWS * config.formulas {
+            draws += 1;
+            let formula = context.random_formula(rng, config.depth);
+            if !is_tautology(&formula) {
+                context.commit_premise(formula);
+            }
+        }
+        if context.premises.len() + 1 < config.formulas {
+            continue;
+        }
+        let Some(last) = (0..MAX_SAT_DRAWS)
+            .map(|_| context.random_formula(rng, config.depth))
+            .find(|f| fits_last(&context, f, satisfiable))
+        else {
+            continue;
+        };
+
+        let mut formulas = context.premises;
+        formulas.push(last);
+        formulas.shuffle(rng);
+        let model = countermodel(&formulas, &Formula::Contradiction);
+        debug_assert_eq!(model.is_some(), satisfiable);
+        return Ok(SatProblem { formulas, model });
+    }
+    Err(SatError { satisfiable })
+}
+
+/// Whether `formula` can complete the context's formulas: new and neither a
+/// tautology nor a contradiction. An unsatisfiable set of three or more
+/// formulas must not be refuted by any two of them alone.
+fn fits_last(context: &ConstructionContext, formula: &Formula, satisfiable: bool) -> bool {
+    let tt = compute_truth_table(formula);
+    if is_tautology(formula) || is_contradiction(formula) || context.premise_truth_tables.contains(&tt) {
+        return false;
+    }
+    let mut set = context.premises.clone();
+    set.push(formula.clone());
+    if premises_consistent(&set) != satisfiable {
+        return false;
+    }
+    satisfiable
+        || context.premises.len() < 2
+        || context.premises.iter().all(|p| premises_consistent(&[p.clone(), formula.clone()]))
+}
+
+/// Check that `assignment` makes every formula true. Values for atoms outside
+/// the set are ignored.
+pub fn check_assignment(formulas: &[Formula], assignment: &BTreeMap<String, bool>) -> Result<(), AssignmentError> {
+    if let Some(atom) = formulas.iter().flat_map(|f| f.atoms()).find(|a| !assignment.contains_key(a)) {
+        return Err(AssignmentError::MissingAtom(atom));
+    }
+    match formulas.iter().position(|f| f.evaluate(assignment) != Some(true)) {
+        Some(i) => Err(AssignmentError::FormulaFalse(i)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sets_have_the_wanted_answer() {
+        let mut rng = rand::thread_rng();
+        let config = SatConfig::new(3, 4, 3);
+        for satisfiable in [true, false] {
+            for _ in 0..20 {
+                let problem = generate_sat_problem(&config, satisfiable, &mut rng).unwrap();
+                assert_eq!(problem.formulas.len(), 4);
+                assert_eq!(premises_consistent(&problem.formulas), satisfiable);
+                assert_eq!(problem.is_satisfiable(), satisfiable);
+                if let Some(model) = &problem.model {
+                    assert_eq!(check_assignment(&problem.formulas, model), Ok(()));
+                }
+                assert!(problem.formulas.iter().all(|f| !is_tautology(f) && !is_contradiction(f)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_assignment() {
+        let formulas = vec![Formula::parse("P | Q").unwrap(), Formula::parse("~P").unwrap()];
+        let assign = |pairs: &[(&str, bool)]| pairs.iter().map(|(a, v)| (a.to_string(), *v)).collect();
+        assert_eq!(check_assignment(&formulas, &assign(&[("P", false)])), Err(AssignmentError::MissingAtom("Q".to_string())));
+        assert_eq!(check_assignment(&formulas, &assign(&[("P", true), ("Q", true)])), Err(AssignmentError::FormulaFalse(1)));
+        assert_eq!(check_assignment(&formulas, &assign(&[("P", false), ("Q", true)])), Ok(()));
+    }
+
+    #[test]
+    fn test_config_validate() {
+        assert!(SatConfig::new(3, 4, 3).validate().is_ok());
+        assert!(SatConfig::new(6, 4, 3).validate().is_err());
+        assert!(SatConfig::new(3, 1, 3).validate().is_err());
+    }
+}
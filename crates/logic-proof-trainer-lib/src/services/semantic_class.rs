@@ -0,0 +1,276 @@
+use crate::models::{Formula, Theorem};
+use super::truth_table::{compute_truth_table_over, DynTruthTable};
+use std::collections::BTreeSet;
+
+/// Largest number of essential atoms for which a class id is canonical under
+/// atom permutation and atom negation (n! · 2^n relabellings are tried).
+pub const MAX_CANONICAL_ATOMS: usize = 6;
+
+/// Largest number of atoms the truth-table engine is asked to enumerate.
+const MAX_TABLE_ATOMS: usize = 20;
+
+/// Compute the semantic class id of the argument `premises ⊢ conclusion`.
+///
+/// The argument is reduced to the pair of truth vectors (conjunction of the
+/// premises, conclusion). Atoms that affect neither vector are dropped, and for
+/// up to `MAX_CANONICAL_ATOMS` remaining atoms the pair is canonicalised over
+/// every permutation and negation of the atoms (NPN-style, without output
+/// negation since premises and conclusion are not interchangeable).
+///
+/// Id formats:
+/// - `n{k}-{premises:x}-{conclusion:x}`: canonical class over k essential atoms
+/// - `r{k}-{hash:016x}`: too many essential atoms to canonicalise; equal ids
+///   still mean equal truth vectors under alphabetical atom order
+/// - `x-{hash:016x}`: too many atoms for a truth table; syntactic hash only
+pub fn argument_class(premises: &[Formula], conclusion: &Formula) -> String {
+    let atoms: Vec<String> = premises.iter()
+        .chain(std::iter::once(conclusion))
+        .flat_map(|f| f.atoms())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    if atoms.len() > MAX_TABLE_ATOMS {
+        let text: Vec<String> = premises.iter()
+            .chain(std::iter::once(conclusion))
+            .map(|f| f.ascii_string_bracketed())
+            .collect();
+        return format!("x-{:016x}", fnv1a(text.join("\n").as_bytes()));
+    }
+
+    let num_vars = atoms.len().max(1);
+    let conclusion_tt = compute_truth_table_over(conclusion, &atoms);
+    let premises_tt = premises.iter().fold(
+        DynTruthTable::tautology(num_vars as u8),
+        |acc, p| acc.and(&compute_truth_table_over(p, &atoms)),
+    );
+    let table: Vec<(bool, bool)> = (0..1usize << num_vars)
+        .map(|row| (premises_tt.get(row), conclusion_tt.get(row)))
+        .collect();
+
+    let (essential, table) = project_essential(num_vars, &table);
+    if essential <= MAX_CANONICAL_ATOMS {
+        let (p, c) = canonical_pair(essential, &table);
+        format!("n{}-{:x}-{:x}", essential, p, c)
+    } else {
+        let bytes: Vec<u8> = table.iter().map(|&(p, c)| (p as u8) | ((c as u8) << 1)).collect();
+        format!("r{}-{:016x}", essential, fnv1a(&bytes))
+    }
+}
+
+/// Compute the semantic class id of a theorem.
+///
+/// A premise-less theorem is a tautology, so its own truth vectors say
+/// nothing; it is classed by the argument it stands for instead. That is its
+/// recorded `base_class` when it has one (obfuscation rewrites the top
+/// connective freely), otherwise a top-level `A ⊃ B` (the shape produced by
+/// wrapping an argument as a conditional) is read back as the argument `A ⊢ B`.
+/// Either way a wrapped theorem and its premise-bearing original share a class.
+pub fn theorem_class(theorem: &Theorem) -> String {
+    match (theorem.premises.as_slice(), &theorem.conclusion, &theorem.base_class) {
+        ([], _, Some(base_class)) => base_class.clone(),
+        ([], Formula::Implies(antecedent, consequent), None) => {
+            argument_class(&[(**antecedent).clone()], consequent)
+        }
+        _ => argument_class(&theorem.premises, &theorem.conclusion),
+    }
+}
+
+/// Bit in a row index that holds variable `var` among `num_vars` variables.
+/// Matches `DynTruthTable` ordering: variable 0 is the most significant bit.
+fn var_bit(var: usize, num_vars: usize) -> usize {
+    1 << (num_vars - 1 - var)
+}
+
+/// Drop variables that affect neither column. Returns the number of remaining
+/// variables and the table restricted to them.
+fn project_essential(num_vars: usize, table: &[(bool, bool)]) -> (usize, Vec<(bool, bool)>) {
+    let essential: Vec<usize> = (0..num_vars)
+        .filter(|&var| {
+            let bit = var_bit(var, num_vars);
+            (0..table.len()).any(|row| table[row] != table[row ^ bit])
+        })
+        .collect();
+
+    let m = essential.len();
+    let projected = (0..1usize << m)
+        .map(|row| {
+            let old_row = essential.iter().enumerate()
+                .filter(|&(k, _)| row & var_bit(k, m) != 0)
+                .fold(0, |acc, (_, &var)| acc | var_bit(var, num_vars));
+            table[old_row]
+        })
+        .collect();
+    (m, projected)
+}
+
+/// Smallest (premises, conclusion) bit pair over all atom permutations and
+/// negations. Requires `num_vars <= MAX_CANONICAL_ATOMS` so each column fits a u64.
+fn canonical_pair(num_vars: usize, table: &[(bool, bool)]) -> (u64, u64) {
+    let rows = 1usize << num_vars;
+    let mut best: Option<(u64, u64)> = None;
+
+    for perm in permutations(num_vars) {
+        for negation in 0..rows {
+            let mut p = 0u64;
+            let mut c = 0u64;
+            for row in 0..rows {
+                // Flipping a row bit is the same as negating that atom.
+                let relabelled = row ^ negation;
+                let old_row = (0..num_vars)
+                    .filter(|&j| relabelled & var_bit(j, num_vars) != 0)
+                    .fold(0, |acc, j| acc | var_bit(perm[j], num_vars));
+                let (tp, tc) = table[old_row];
+                p |= (tp as u64) << row;
+                c |= (tc as u64) << row;
+            }
+            if best.is_none_or(|b| (p, c) < b) {
+                best = Some((p, c));
+            }
+        }
+    }
+
+    best.unwrap_or((0, 0))
+}
+
+/// All permutations of `0..n` (n is small).
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![vec![]];
+    }
+    let mut result = Vec::new();
+    for perm in permutations(n - 1) {
+        for pos in 0..=perm.len() {
+            let mut next = perm.clone();
+            next.insert(pos, n - 1);
+            result.push(next);
+        }
+    }
+    result
+}
+
+/// 64-bit FNV-1a: stable across runs and toolchains, unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325u64, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::theorem::Difficulty;
+
+    fn atom(name: &str) -> Formula {
+        Formula::Atom(name.to_string())
+    }
+
+    fn not(f: Formula) -> Formula {
+        Formula::Not(Box::new(f))
+    }
+
+    fn and(a: Formula, b: Formula) -> Formula {
+        Formula::And(Box::new(a), Box::new(b))
+    }
+
+    fn or(a: Formula, b: Formula) -> Formula {
+        Formula::Or(Box::new(a), Box::new(b))
+    }
+
+    fn implies(a: Formula, b: Formula) -> Formula {
+        Formula::Implies(Box::new(a), Box::new(b))
+    }
+
+    #[test]
+    fn test_renamed_atoms_share_class() {
+        let mp = argument_class(&[implies(atom("P"), atom("Q")), atom("P")], &atom("Q"));
+        let renamed = argument_class(&[implies(atom("B"), atom("A")), atom("B")], &atom("A"));
+        assert_eq!(mp, renamed);
+        assert!(mp.starts_with("n2-"));
+    }
+
+    #[test]
+    fn test_negated_atoms_share_class() {
+        let mp = argument_class(&[implies(atom("P"), atom("Q")), atom("P")], &atom("Q"));
+        let negated = argument_class(&[implies(not(atom("P")), atom("Q")), not(atom("P"))], &atom("Q"));
+        assert_eq!(mp, negated);
+    }
+
+    #[test]
+    fn test_equivalent_premises_share_class() {
+        let mp = argument_class(&[implies(atom("P"), atom("Q")), atom("P")], &atom("Q"));
+        let rewritten = argument_class(&[or(not(atom("P")), atom("Q")), atom("P")], &atom("Q"));
+        assert_eq!(mp, rewritten);
+    }
+
+    #[test]
+    fn test_different_arguments_differ() {
+        let mp = argument_class(&[implies(atom("P"), atom("Q")), atom("P")], &atom("Q"));
+        let add = argument_class(&[atom("P")], &or(atom("P"), atom("Q")));
+        let hs = argument_class(
+            &[implies(atom("P"), atom("Q")), implies(atom("Q"), atom("R"))],
+            &implies(atom("P"), atom("R")),
+        );
+        assert_ne!(mp, add);
+        assert_ne!(mp, hs);
+        assert_ne!(add, hs);
+    }
+
+    #[test]
+    fn test_syntactically_distinct_rules_can_collapse() {
+        // P ⊃ Q, P and P ∨ Q, ~P both pin the premises to a single row where Q holds
+        let mp = argument_class(&[implies(atom("P"), atom("Q")), atom("P")], &atom("Q"));
+        let ds = argument_class(&[or(atom("P"), atom("Q")), not(atom("P"))], &atom("Q"));
+        let simp = argument_class(&[and(atom("P"), atom("Q"))], &atom("P"));
+        assert_eq!(mp, ds);
+        assert_eq!(mp, simp);
+    }
+
+    #[test]
+    fn test_inessential_atoms_dropped() {
+        let plain = argument_class(&[atom("P")], &atom("P"));
+        let padded = argument_class(&[and(atom("P"), or(atom("R"), not(atom("R"))))], &atom("P"));
+        assert_eq!(plain, padded);
+        assert!(plain.starts_with("n1-"));
+    }
+
+    #[test]
+    fn test_wrapped_theorem_matches_argument() {
+        let premises = vec![implies(atom("P"), atom("Q")), atom("P")];
+        let argument = Theorem::new(premises.clone(), atom("Q"), Difficulty::Easy, None, None);
+        let wrapped = Theorem::new(
+            vec![],
+            implies(and(premises[0].clone(), premises[1].clone()), atom("Q")),
+            Difficulty::Easy,
+            None,
+            None,
+        );
+        assert_eq!(theorem_class(&argument), theorem_class(&wrapped));
+    }
+
+    #[test]
+    fn test_obfuscated_theorem_keeps_base_class() {
+        // (P ⊃ Q) · P ⊃ Q with its top conditional rewritten away
+        let premises = vec![implies(atom("P"), atom("Q")), atom("P")];
+        let base_class = argument_class(&premises, &atom("Q"));
+        let mut obfuscated = Theorem::new(
+            vec![],
+            or(not(and(premises[0].clone(), premises[1].clone())), atom("Q")),
+            Difficulty::Easy,
+            None,
+            None,
+        );
+        assert_eq!(theorem_class(&obfuscated), "n0-1-1");
+        obfuscated.base_class = Some(base_class.clone());
+        assert_eq!(theorem_class(&obfuscated), base_class);
+    }
+
+    #[test]
+    fn test_wide_arguments_not_canonicalised() {
+        let atoms: Vec<Formula> = ["A", "B", "C", "D", "E", "F", "G"].iter().map(|a| atom(a)).collect();
+        let premise = atoms[1..].iter().cloned().fold(atoms[0].clone(), and);
+        let id = argument_class(std::slice::from_ref(&premise), &atoms[6]);
+        assert!(id.starts_with("r7-"));
+        assert_eq!(id, argument_class(&[premise], &atoms[6]));
+    }
+}
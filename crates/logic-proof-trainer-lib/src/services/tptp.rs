@@ -0,0 +1,130 @@
+//! TPTP export: theorems as propositional FOF problems for first-order
+//! provers.
+//!
+//! TPTP reads capitalised words as variables, so atoms are renamed to lower
+//! words (`P` → `p`, `T1` → `t1`). The renaming comes with the problem so
+//! prover output can be mapped back.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::models::{Formula, Theorem};
+
+/// A theorem as a TPTP problem
+#[derive(Debug, Clone, PartialEq)]
+pub struct TptpProblem {
+    /// The problem file
+    pub text: String,
+    /// TPTP name of each atom
+    pub atoms: BTreeMap<String, String>,
+}
+
+/// TPTP names for `atoms`: lower case, `'` spelled out, a letter first, and
+/// a numeric suffix where two atoms would clash.
+pub fn tptp_atom_names(atoms: &BTreeSet<String>) -> BTreeMap<String, String> {
+    let mut taken = HashSet::new();
+    let mut names = BTreeMap::new();
+    for atom in atoms {
+        let mut base = atom.to_lowercase().replace('\'', "_prime");
+        if !base.starts_with(|c: char| c.is_ascii_lowercase()) {
+            base = format!("a_{}", base);
+        }
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while !taken.insert(name.clone()) {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        names.insert(atom.clone(), name);
+    }
+    names
+}
+
+/// `formula` in TPTP syntax, with binary connectives bracketed.
+pub fn formula_to_tptp(formula: &Formula, names: &BTreeMap<String, String>) -> String {
+    match formula {
+        Formula::Atom(name) => names.get(name).cloned().unwrap_or_else(|| name.to_lowercase()),
+        Formula::Contradiction => "$false".to_string(),
+        Formula::Not(inner) => format!("~ {}", formula_to_tptp(inner, names)),
+        Formula::And(l, r) => binary(l, "&", r, names),
+        Formula::Or(l, r) => binary(l, "|", r, names),
+        Formula::Implies(l, r) => binary(l, "=>", r, names),
+        Formula::Biconditional(l, r) => binary(l, "<=>", r, names),
+    }
+}
+
+fn binary(l: &Formula, op: &str, r: &Formula, names: &BTreeMap<String, String>) -> String {
+    format!("( {} {} {} )", formula_to_tptp(l, names), op, formula_to_tptp(r, names))
+}
+
+/// `theorem` as a FOF problem: each premise an axiom, the conclusion the
+/// conjecture. `valid`, when known, becomes the problem's status (Theorem
+/// or CounterSatisfiable).
+pub fn theorem_to_tptp(theorem: &Theorem, valid: Option<bool>) -> TptpProblem {
+    let atoms: BTreeSet<String> = theorem.premises.iter()
+        .chain(std::iter::once(&theorem.conclusion))
+        .flat_map(|f| f.atoms())
+        .collect();
+    let names = tptp_atom_names(&atoms);
+
+    let mut lines = vec![format!("% File     : {}", theorem.id)];
+    if let Some(valid) = valid {
+        lines.push(format!("% Status   : {}", if valid { "Theorem" } else { "CounterSatisfiable" }));
+    }
+    let renamed: Vec<String> = names.iter()
+        .filter(|(atom, name)| atom != name)
+        .map(|(atom, name)| format!("{}={}", atom, name))
+        .collect();
+    if !renamed.is_empty() {
+        lines.push(format!("% Atoms    : {}", renamed.join(", ")));
+    }
+    for (i, premise) in theorem.premises.iter().enumerate() {
+        lines.push(format!("fof(premise_{}, axiom, {}).", i + 1, formula_to_tptp(premise, &names)));
+    }
+    lines.push(format!("fof(conclusion, conjecture, {}).", formula_to_tptp(&theorem.conclusion, &names)));
+    TptpProblem { text: lines.join("\n") + "\n", atoms: names }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Difficulty;
+
+    fn parse(s: &str) -> Formula {
+        Formula::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_fof_problem() {
+        let mut theorem = Theorem::new(
+            vec![parse("P -> (Q & ~R)"), parse("P")],
+            parse("~R"),
+            Difficulty::Easy,
+            None,
+            None,
+        );
+        theorem.id = "easy-1".to_string();
+        let problem = theorem_to_tptp(&theorem, Some(true));
+        let expected = [
+            "% File     : easy-1",
+            "% Status   : Theorem",
+            "% Atoms    : P=p, Q=q, R=r",
+            "fof(premise_1, axiom, ( p => ( q & ~ r ) )).",
+            "fof(premise_2, axiom, p).",
+            "fof(conclusion, conjecture, ~ r).",
+        ];
+        assert_eq!(problem.text, expected.join("\n") + "\n");
+        assert_eq!(problem.atoms["Q"], "q");
+    }
+
+    #[test]
+    fn test_atom_names_are_distinct_lower_words() {
+        let atoms: BTreeSet<String> = ["P", "p", "P'", "1", "T1"].iter().map(|s| s.to_string()).collect();
+        let names = tptp_atom_names(&atoms);
+        assert_eq!(names["T1"], "t1");
+        assert_eq!(names["P'"], "p_prime");
+        assert_eq!(names["1"], "a_1");
+        assert_ne!(names["P"], names["p"]);
+        assert_eq!(names.values().collect::<HashSet<_>>().len(), atoms.len());
+        assert_eq!(formula_to_tptp(&Formula::Contradiction, &names), "$false");
+    }
+}
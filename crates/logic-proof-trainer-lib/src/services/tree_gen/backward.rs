@@ -24,12 +24,11 @@ pub fn backward_construct(
     // Check if goal is already available (from assumption or premise)
     if ctx.is_available(&goal) {
         // Use available formula - check if it's an assumption or premise
-        let tt = compute_truth_table(&goal);
-        if ctx.assumptions.iter().any(|a| compute_truth_table(a) == tt) {
-            return Ok(ProofNode::assumption(goal));
+        if let Some(leaf) = assumption_leaf(&goal, ctx) {
+            return leaf;
         }
         // It's a premise - return a premise node
-        return Ok(ProofNode::premise(goal));
+        return premise_leaf(goal, ctx);
     }
 
     // If we can commit goal as premise and have enough depth used already, just do it
@@ -46,10 +45,15 @@ pub fn backward_construct(
     // Pick a rule based on goal shape and requirements
     let rule = pick_rule_for_goal(&goal, ctx, rng);
 
-    // Apply the rule backward - if it fails, try falling back to make_leaf
+    // Apply the rule backward - if it fails, undo its premises and technique
+    // flags and fall back to make_leaf
+    let before = ctx.clone();
     match apply_rule_backward(rule, goal.clone(), ctx, rng) {
         Ok(node) => Ok(node),
-        Err(_) => make_leaf(goal, ctx, rng),
+        Err(_) => {
+            *ctx = ConstructionContext { remaining_depth: ctx.remaining_depth, ..before };
+            make_leaf(goal, ctx, rng)
+        }
     }
 }
 
@@ -60,15 +64,14 @@ fn make_leaf(
     rng: &mut impl Rng,
 ) -> Result<ProofNode, GenerationError> {
     // First check if available as assumption
-    let tt = compute_truth_table(&goal);
-    if ctx.assumptions.iter().any(|a| compute_truth_table(a) == tt) {
-        return Ok(ProofNode::assumption(goal));
+    if let Some(leaf) = assumption_leaf(&goal, ctx) {
+        return leaf;
     }
+    let tt = compute_truth_table(&goal);
 
     // Check if already a premise (semantically)
     if ctx.premises.iter().any(|p| compute_truth_table(p) == tt) {
-        // Find the matching premise and return it
-        return Ok(ProofNode::premise(goal));
+        return premise_leaf(goal, ctx);
     }
 
     // Try to commit as new premise
@@ -95,9 +98,10 @@ fn make_leaf(
                 Box::new(atom.clone()),
                 Box::new(goal.clone()),
             );
-            if ctx.can_commit_premise(&impl_formula) {
-                ctx.commit_premise(atom.clone());
-                ctx.commit_premise(impl_formula.clone());
+            // Commit both or neither: the pair can conflict where each alone does not
+            let mut trial = ctx.clone();
+            if trial.commit_premise(atom.clone()) && trial.commit_premise(impl_formula.clone()) {
+                *ctx = trial;
                 return Ok(ProofNode::derivation(
                     goal,
                     "MP",
@@ -114,6 +118,47 @@ fn make_leaf(
     Err(GenerationError::NoPremiseAvailable)
 }
 
+/// Reference an open assumption for `goal`. An assumption that is only
+/// equivalent to the goal (e.g. `Q ∨ P` for `P ∨ Q`) cannot stand in for it,
+/// so that case fails and the caller falls back to another construction.
+fn assumption_leaf(goal: &Formula, ctx: &ConstructionContext) -> Option<Result<ProofNode, GenerationError>> {
+    let tt = compute_truth_table(goal);
+    if !ctx.assumptions.iter().any(|a| compute_truth_table(a) == tt) {
+        return None;
+    }
+    if ctx.assumptions.contains(goal) {
+        Some(Ok(ProofNode::assumption(goal.clone())))
+    } else {
+        Some(Err(GenerationError::CannotProve))
+    }
+}
+
+/// Reference a committed premise for `goal`. As with assumptions, a premise
+/// only equivalent to the goal cannot stand in for it: citing the goal as a
+/// second premise would make the pair redundant.
+fn premise_leaf(goal: Formula, ctx: &ConstructionContext) -> Result<ProofNode, GenerationError> {
+    if ctx.premises.contains(&goal) {
+        Ok(ProofNode::premise(goal))
+    } else {
+        Err(GenerationError::CannotProve)
+    }
+}
+
+/// Commit the premises a subproof discovered to the enclosing context. Fails
+/// if one of them conflicts with the enclosing premises, since the proof
+/// built in the subproof cites it.
+fn merge_premises(ctx: &mut ConstructionContext, subproof_ctx: &ConstructionContext) -> Result<(), GenerationError> {
+    for premise in &subproof_ctx.premises {
+        if ctx.premises.contains(premise) {
+            continue;
+        }
+        if !ctx.commit_premise(premise.clone()) {
+            return Err(GenerationError::NoPremiseAvailable);
+        }
+    }
+    Ok(())
+}
+
 /// Choose which rule to apply backward based on goal shape and requirements
 fn pick_rule_for_goal(
     goal: &Formula,
@@ -138,6 +183,11 @@ fn pick_rule_for_goal(
         }
     }
 
+    // If we need IP and haven't used it yet, prove an atomic or negated goal by contradiction
+    if ctx.required.need_ip && !ctx.required.used_ip && matches!(goal, Formula::Atom(_)) && has_nesting {
+        return Fragment::IP;
+    }
+
     // Pick based on goal shape
     match goal {
         Formula::Implies(_, _) => {
@@ -409,8 +459,21 @@ fn apply_cp_backward(
     // Enter subproof with assumption
     let mut subproof_ctx = ctx.enter_subproof(antecedent.clone());
 
-    // Build proof of consequent with assumption available
-    let cons_proof = backward_construct(consequent, &mut subproof_ctx, rng)?;
+    // Build proof of consequent with assumption available. A case split
+    // still required goes directly inside, which nests the two techniques.
+    let mut split_ctx = subproof_ctx.clone();
+    let split = if ctx.required.need_case_split && !ctx.required.used_case_split && split_ctx.has_nesting_budget() {
+        apply_case_split_backward(consequent.clone(), &mut split_ctx, rng).ok()
+    } else {
+        None
+    };
+    let cons_proof = match split {
+        Some(node) => {
+            subproof_ctx = split_ctx;
+            node
+        }
+        None => prove_from_assumption(&antecedent, consequent.clone(), None, &mut subproof_ctx, rng)?,
+    };
 
     // Mark CP as truly used
     ctx.required.used_cp = true;
@@ -421,12 +484,16 @@ fn apply_cp_backward(
         ctx.required.used_case_split = true;
         ctx.required.case_split_inside_cp = true;
     }
+    if subproof_ctx.required.used_ip {
+        ctx.required.used_ip = true;
+    }
 
     // Merge discovered premises from subproof back to main context
-    for premise in &subproof_ctx.premises {
-        if !ctx.premises.iter().any(|p| compute_truth_table(p) == compute_truth_table(premise)) {
-            let _ = ctx.commit_premise(premise.clone());
-        }
+    merge_premises(ctx, &subproof_ctx)?;
+
+    // The premises found in the subproof must not make CP trivial either
+    if entails(&ctx.premises, &consequent) {
+        return Err(GenerationError::TrivialCP);
     }
 
     Ok(ProofNode::derivation(
@@ -448,18 +515,18 @@ fn apply_ip_backward(
     // Enter subproof with negation of goal as assumption
     let mut subproof_ctx = ctx.enter_subproof(assumption.clone());
 
-    // Build proof of contradiction
-    let contra_proof = build_contradiction_backward(&mut subproof_ctx, rng)?;
+    // Build proof of contradiction. Prefer the conditional pair: the generic
+    // builder contradicts ~A with a premise ~~A, which makes A directly available.
+    let contra_proof = match contradiction_via_conditionals(&assumption, &mut subproof_ctx, rng) {
+        Some(node) => node,
+        None => build_contradiction_backward(&mut subproof_ctx, rng)?,
+    };
 
     // Mark IP as used
     ctx.required.used_ip = true;
 
     // Merge premises
-    for premise in &subproof_ctx.premises {
-        if !ctx.premises.iter().any(|p| compute_truth_table(p) == compute_truth_table(premise)) {
-            let _ = ctx.commit_premise(premise.clone());
-        }
-    }
+    merge_premises(ctx, &subproof_ctx)?;
 
     Ok(ProofNode::derivation(
         goal,
@@ -469,6 +536,77 @@ fn apply_ip_backward(
     ))
 }
 
+/// Derive ⊥ from assumption X via a new premise X ⊃ B and X ⊃ ~B (a premise, or
+/// derived when depth allows), for an atom B not in X. Neither conditional alone
+/// yields ~X, so the enclosing IP stays forced.
+fn contradiction_via_conditionals(
+    assumption: &Formula,
+    ctx: &mut ConstructionContext,
+    rng: &mut impl Rng,
+) -> Option<ProofNode> {
+    let assumption_atoms = assumption.atoms();
+    let mut candidates: Vec<Formula> = ctx.atom_pool.iter()
+        .filter(|a| !assumption_atoms.contains(*a))
+        .map(|a| Formula::Atom(a.clone()))
+        .collect();
+    for i in (1..candidates.len()).rev() {
+        let j = rng.gen_range(0..=i);
+        candidates.swap(i, j);
+    }
+
+    for b in candidates {
+        let not_b = Formula::Not(Box::new(b.clone()));
+        let to_b = Formula::Implies(Box::new(assumption.clone()), Box::new(b.clone()));
+        let to_not_b = Formula::Implies(Box::new(assumption.clone()), Box::new(not_b.clone()));
+
+        // Commit both or neither. With depth to spare, X ⊃ ~B is proved
+        // rather than assumed, which varies the resulting premises.
+        let mut trial = ctx.clone();
+        if !trial.commit_premise(to_b.clone()) {
+            continue;
+        }
+        let derived = if trial.remaining_depth > 0 && rng.gen_bool(0.5) {
+            let mut derive_ctx = trial.clone();
+            backward_construct(to_not_b.clone(), &mut derive_ctx, rng).ok()
+                .filter(|node| !node.is_premise())
+                .map(|node| (node, derive_ctx))
+        } else {
+            None
+        };
+        let to_not_b_proof = match derived {
+            Some((node, derive_ctx)) => {
+                trial = derive_ctx;
+                node
+            }
+            None if trial.commit_premise(to_not_b.clone()) => ProofNode::premise(to_not_b),
+            None => continue,
+        };
+        *ctx = trial;
+
+        return Some(ProofNode::derivation(
+            Formula::Contradiction,
+            "NegE",
+            vec![
+                ProofNode::derivation(
+                    b,
+                    "MP",
+                    vec![ProofNode::premise(to_b), ProofNode::assumption(assumption.clone())],
+                    None,
+                ),
+                ProofNode::derivation(
+                    not_b,
+                    "MP",
+                    vec![to_not_b_proof, ProofNode::assumption(assumption.clone())],
+                    None,
+                ),
+            ],
+            None,
+        ));
+    }
+
+    None
+}
+
 /// NegIntro backward: goal ~A needs subproof [assume A, derive ⊥]
 fn apply_neg_intro_backward(
     goal: Formula,
@@ -486,11 +624,7 @@ fn apply_neg_intro_backward(
             let contra_proof = build_contradiction_backward(&mut subproof_ctx, rng)?;
 
             // Merge premises
-            for premise in &subproof_ctx.premises {
-                if !ctx.premises.iter().any(|p| compute_truth_table(p) == compute_truth_table(premise)) {
-                    let _ = ctx.commit_premise(premise.clone());
-                }
-            }
+            merge_premises(ctx, &subproof_ctx)?;
 
             Ok(ProofNode::derivation(
                 goal,
@@ -510,8 +644,16 @@ fn apply_case_split_backward(
     ctx: &mut ConstructionContext,
     rng: &mut impl Rng,
 ) -> Result<ProofNode, GenerationError> {
-    // Generate disjuncts using simple atoms
-    let atoms: Vec<_> = ctx.atom_pool.iter().map(|a| Formula::Atom(a.clone())).collect();
+    // Generate disjuncts using simple atoms the premises and assumptions do
+    // not already give: such a case would not need its assumption
+    let available: Vec<Formula> = ctx.available_formulas().into_iter().cloned().collect();
+    let atoms: Vec<_> = ctx.atom_pool.iter()
+        .map(|a| Formula::Atom(a.clone()))
+        .filter(|atom| !entails(&available, atom))
+        .collect();
+    if atoms.len() < 2 {
+        return Err(GenerationError::DSAvailable);
+    }
     let left_idx = rng.gen_range(0..atoms.len());
     let mut right_idx = rng.gen_range(0..atoms.len());
     // Try to pick different atoms
@@ -525,12 +667,12 @@ fn apply_case_split_backward(
     let left = atoms[left_idx].clone();
     let right = atoms[right_idx].clone();
 
-    // CRITICAL CHECK: Neither ~left nor ~right should be committable as premise
-    // If they are, DS would work instead of case split
+    // CRITICAL CHECK: Neither ~left nor ~right may already be available.
+    // If one is, DS would work instead of case split
     let neg_left = Formula::Not(Box::new(left.clone()));
     let neg_right = Formula::Not(Box::new(right.clone()));
 
-    if ctx.can_commit_premise(&neg_left) || ctx.can_commit_premise(&neg_right) {
+    if left == right || ctx.is_available(&neg_left) || ctx.is_available(&neg_right) {
         return Err(GenerationError::DSAvailable);
     }
 
@@ -539,7 +681,7 @@ fn apply_case_split_backward(
     // Prove disjunction - this needs to be a premise
     let disj_proof = if ctx.can_commit_premise(&disjunction) {
         ctx.commit_premise(disjunction.clone());
-        ProofNode::premise(disjunction)
+        ProofNode::premise(disjunction.clone())
     } else {
         backward_construct(disjunction.clone(), ctx, rng)?
     };
@@ -547,44 +689,49 @@ fn apply_case_split_backward(
     // Check if we need to force CP inside the case split branches
     let need_cp_in_branch = ctx.required.need_cp && !ctx.required.used_cp;
 
+    // Mark case split as used, so the branches do not split again to meet
+    // the requirement
+    ctx.required.used_case_split = true;
+
     // Case 1: assume left, prove goal
     let mut case1_ctx = ctx.enter_subproof(left.clone());
     let case1_proof = if need_cp_in_branch && case1_ctx.has_nesting_budget() {
         // Force CP inside this branch for proper nesting
         force_cp_in_branch(goal.clone(), &mut case1_ctx, rng)?
     } else {
-        backward_construct(goal.clone(), &mut case1_ctx, rng)?
+        prove_from_assumption(&left, goal.clone(), Some(&disjunction), &mut case1_ctx, rng)?
     };
 
+    // Premises found in case 1 hold in case 2 too; merge them first so the
+    // second case cannot commit premises that contradict them
+    merge_premises(ctx, &case1_ctx)?;
+
     // Case 2: assume right, prove goal
     let mut case2_ctx = ctx.enter_subproof(right.clone());
     let case2_proof = if need_cp_in_branch && !case1_ctx.required.used_cp && case2_ctx.has_nesting_budget() {
         // Force CP in branch 2 if branch 1 didn't use it
         force_cp_in_branch(goal.clone(), &mut case2_ctx, rng)?
     } else {
-        backward_construct(goal.clone(), &mut case2_ctx, rng)?
+        prove_from_assumption(&right, goal.clone(), Some(&disjunction), &mut case2_ctx, rng)?
     };
 
-    // Mark case split as used
-    ctx.required.used_case_split = true;
-
     // Merge technique usage flags from branches
     // If CP was used in either branch, mark it as cp_inside_case_split
     if case1_ctx.required.used_cp || case2_ctx.required.used_cp {
         ctx.required.used_cp = true;
         ctx.required.cp_inside_case_split = true;
     }
-
-    // Merge premises from both cases
-    for premise in &case1_ctx.premises {
-        if !ctx.premises.iter().any(|p| compute_truth_table(p) == compute_truth_table(premise)) {
-            let _ = ctx.commit_premise(premise.clone());
-        }
+    if case1_ctx.required.used_ip || case2_ctx.required.used_ip {
+        ctx.required.used_ip = true;
     }
-    for premise in &case2_ctx.premises {
-        if !ctx.premises.iter().any(|p| compute_truth_table(p) == compute_truth_table(premise)) {
-            let _ = ctx.commit_premise(premise.clone());
-        }
+
+    // Merge premises from the second case (the first case's are already in)
+    merge_premises(ctx, &case2_ctx)?;
+
+    // The split is forced only if the other premises do not prove the goal
+    let others: Vec<Formula> = ctx.premises.iter().filter(|p| **p != disjunction).cloned().collect();
+    if entails(&others, &goal) {
+        return Err(GenerationError::DSAvailable);
     }
 
     Ok(ProofNode::derivation(
@@ -601,6 +748,105 @@ fn apply_case_split_backward(
     ))
 }
 
+/// Prove `goal` inside the subproof that assumes `assumption`, so that the
+/// proof needs the assumption: the premises committed along the way (other
+/// than the disjunction a case split is `split_on`) must not entail the goal
+/// on their own, or the enclosing CP or case split is not forced. Tries free
+/// backward construction first, then a conditional chain from the assumption
+/// (A ⊃ X, X ⊃ G or just A ⊃ G).
+fn prove_from_assumption(
+    assumption: &Formula,
+    goal: Formula,
+    split_on: Option<&Formula>,
+    ctx: &mut ConstructionContext,
+    rng: &mut impl Rng,
+) -> Result<ProofNode, GenerationError> {
+    if *assumption == goal {
+        return Ok(ProofNode::assumption(goal));
+    }
+    let forced = |premises: &[Formula]| {
+        let others: Vec<Formula> = premises.iter().filter(|p| Some(*p) != split_on).cloned().collect();
+        !entails(&others, &goal)
+    };
+
+    let mut trial = ctx.clone();
+    if let Ok(node) = backward_construct(goal.clone(), &mut trial, rng) {
+        if forced(&trial.premises) {
+            *ctx = trial;
+            return Ok(node);
+        }
+    }
+
+    // Chain through an intermediate atom when one fits, else go direct
+    let mut middles: Vec<Formula> = ctx.atom_pool.iter()
+        .map(|a| Formula::Atom(a.clone()))
+        .filter(|x| x != assumption && *x != goal)
+        .collect();
+    for i in (1..middles.len()).rev() {
+        let j = rng.gen_range(0..=i);
+        middles.swap(i, j);
+    }
+    let direct = Formula::Implies(Box::new(assumption.clone()), Box::new(goal.clone()));
+    for middle in middles {
+        let to_middle = Formula::Implies(Box::new(assumption.clone()), Box::new(middle.clone()));
+        let to_goal = Formula::Implies(Box::new(middle.clone()), Box::new(goal.clone()));
+        let mut trial = ctx.clone();
+        if trial.commit_premise(to_middle.clone()) && trial.commit_premise(to_goal.clone())
+            && forced(&trial.premises)
+        {
+            *ctx = trial;
+            let middle_proof = ProofNode::derivation(
+                middle,
+                "MP",
+                vec![ProofNode::premise(to_middle), ProofNode::assumption(assumption.clone())],
+                None,
+            );
+            return Ok(ProofNode::derivation(
+                goal,
+                "MP",
+                vec![ProofNode::premise(to_goal), middle_proof],
+                None,
+            ));
+        }
+    }
+
+    // Inside an outer subproof, A ⊃ (B ⊃ G) also uses its assumption B
+    if let Some(outer) = ctx.assumptions.iter().rev().find(|a| *a != assumption).cloned() {
+        let to_outer = Formula::Implies(Box::new(outer.clone()), Box::new(goal.clone()));
+        let nested = Formula::Implies(Box::new(assumption.clone()), Box::new(to_outer.clone()));
+        let mut trial = ctx.clone();
+        if rng.gen_bool(0.5) && trial.commit_premise(nested.clone()) && forced(&trial.premises) {
+            *ctx = trial;
+            let outer_proof = ProofNode::derivation(
+                to_outer,
+                "MP",
+                vec![ProofNode::premise(nested), ProofNode::assumption(assumption.clone())],
+                None,
+            );
+            return Ok(ProofNode::derivation(
+                goal,
+                "MP",
+                vec![outer_proof, ProofNode::assumption(outer)],
+                None,
+            ));
+        }
+    }
+
+    let mut trial = ctx.clone();
+    if (trial.premises.contains(&direct) || trial.commit_premise(direct.clone()))
+        && forced(&trial.premises)
+    {
+        *ctx = trial;
+        return Ok(ProofNode::derivation(
+            goal,
+            "MP",
+            vec![ProofNode::premise(direct), ProofNode::assumption(assumption.clone())],
+            None,
+        ));
+    }
+    Err(GenerationError::NoPremiseAvailable)
+}
+
 /// Force CP to be used within a case split branch.
 /// Creates an intermediate implication that requires CP to prove, then uses MP to get the goal.
 fn force_cp_in_branch(
@@ -639,56 +885,26 @@ fn build_contradiction_backward(
     ctx: &mut ConstructionContext,
     rng: &mut impl Rng,
 ) -> Result<ProofNode, GenerationError> {
-    // Try to find P and ~P among available formulas
+    // Try to find P and ~P among available formulas. The pair must be
+    // syntactic complements: NegE does not apply to ~P and an equivalent of P.
     let available: Vec<Formula> = ctx.premises.iter().chain(ctx.assumptions.iter()).cloned().collect();
+    let leaf = |f: &Formula| if ctx.assumptions.contains(f) {
+        ProofNode::assumption(f.clone())
+    } else {
+        ProofNode::premise(f.clone())
+    };
 
     for f in &available {
         let neg = Formula::Not(Box::new(f.clone()));
-        let neg_tt = compute_truth_table(&neg);
-        if available.iter().any(|a| compute_truth_table(a) == neg_tt) {
+        if available.contains(&neg) {
             // Found P and ~P
             return Ok(ProofNode::derivation(
                 Formula::Contradiction,
                 "NegE",
-                vec![
-                    if ctx.assumptions.iter().any(|a| compute_truth_table(a) == compute_truth_table(f)) {
-                        ProofNode::assumption(f.clone())
-                    } else {
-                        ProofNode::premise(f.clone())
-                    },
-                    if ctx.assumptions.iter().any(|a| compute_truth_table(a) == neg_tt) {
-                        ProofNode::assumption(neg)
-                    } else {
-                        ProofNode::premise(neg)
-                    },
-                ],
+                vec![leaf(f), leaf(&neg)],
                 None,
             ));
         }
-
-        // Check if f is ~P and P is available
-        if let Formula::Not(inner) = f {
-            let inner_tt = compute_truth_table(inner);
-            if available.iter().any(|a| compute_truth_table(a) == inner_tt) {
-                return Ok(ProofNode::derivation(
-                    Formula::Contradiction,
-                    "NegE",
-                    vec![
-                        if ctx.assumptions.iter().any(|a| compute_truth_table(a) == inner_tt) {
-                            ProofNode::assumption((**inner).clone())
-                        } else {
-                            ProofNode::premise((**inner).clone())
-                        },
-                        if ctx.assumptions.iter().any(|a| compute_truth_table(a) == compute_truth_table(f)) {
-                            ProofNode::assumption(f.clone())
-                        } else {
-                            ProofNode::premise(f.clone())
-                        },
-                    ],
-                    None,
-                ));
-            }
-        }
     }
 
     // No direct contradiction available - need to derive one
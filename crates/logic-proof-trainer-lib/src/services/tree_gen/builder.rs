@@ -6,6 +6,7 @@ use super::super::proof_tree::{ProofNode, ProofTree};
 use super::super::fragments::Fragment;
 use super::context::{TreeGenConfig, ConstructionContext, RequiredTechniques, GenerationError, TAUTOLOGY};
 use super::backward::backward_construct;
+use super::super::truth_table::is_tautology;
 use super::templates::FallbackTemplates;
 
 /// Generates proof trees compositionally (proof-first approach)
@@ -101,12 +102,25 @@ impl ProofTreeGenerator {
     /// Generate a complete proof tree using the new backward construction algorithm.
     /// Falls back to the old forward algorithm and templates if backward fails.
     pub fn generate(&mut self) -> ProofTree {
-        let mut rng = rand::thread_rng();
+        self.generate_with_rng(&mut rand::thread_rng())
+    }
+
+    /// `generate` driven by the caller's RNG, for reproducible output.
+    pub fn generate_with_rng(&mut self, rng: &mut impl Rng) -> ProofTree {
+        self.try_generate_with_rng(rng).unwrap_or_else(|| {
+            eprintln!("Warning: Could not generate valid proof after {} attempts, using fallback", Self::MAX_RETRIES);
+            self.generate_fallback_theorem(rng)
+        })
+    }
+
+    /// `generate_with_rng` without the fallback templates: `None` if neither
+    /// backward nor forward construction produced a valid tree.
+    pub fn try_generate_with_rng(&mut self, rng: &mut impl Rng) -> Option<ProofTree> {
         let mut best_tree: Option<ProofTree> = None;
 
         // First try: Use new backward construction algorithm
         for _attempt in 0..(Self::MAX_RETRIES / 2) {
-            if let Ok(tree) = self.generate_backward(&mut rng) {
+            if let Ok(tree) = self.generate_backward(rng) {
                 let validation = tree.validate_with_difficulty(
                     self.config.min_proof_steps,
                     self.config.require_forces_cp,
@@ -114,7 +128,7 @@ impl ProofTreeGenerator {
                     self.config.require_forces_ip,
                 );
                 if validation.is_ok() && tree.fragment_count >= self.config.min_fragments {
-                    return tree;
+                    return Some(tree);
                 }
                 // Track as best if basically valid
                 if tree.is_valid() && best_tree.as_ref().map_or(true, |b| tree.fragment_count > b.fragment_count) {
@@ -125,7 +139,7 @@ impl ProofTreeGenerator {
 
         // Second try: Fall back to old forward generation algorithm
         for attempt in 0..(Self::MAX_RETRIES / 2) {
-            let tree = self.generate_once();
+            let tree = self.generate_once(rng);
 
             // Check for degenerate premises AND minimum proof difficulty AND forcing requirements
             let validation = tree.validate_with_difficulty(
@@ -159,29 +173,21 @@ impl ProofTreeGenerator {
             }
 
             // Found a valid tree
-            return tree;
+            return Some(tree);
         }
 
         // If we got a best tree from any attempt, use it
-        if let Some(tree) = best_tree {
-            return tree;
-        }
-
-        eprintln!("Warning: Could not generate valid proof after {} attempts, using fallback", Self::MAX_RETRIES);
-        self.generate_fallback_theorem(&mut rng)
+        best_tree
     }
 
     /// Generate a proof tree using the new backward construction algorithm.
     /// This builds the proof by working backward from a shaped goal.
     fn generate_backward(&self, rng: &mut impl Rng) -> Result<ProofTree, GenerationError> {
-        // Shape goal based on requirements
-        let goal = if self.config.require_forces_cp {
-            // For CP-forcing, generate an implication goal
-            self.generate_implication_goal(rng)
-        } else {
-            // Otherwise random interesting goal
-            self.random_interesting_goal(rng)
-        };
+        // A tautological goal needs no premises, so the theorem would be rejected
+        let goal = (0..Self::MAX_RETRIES)
+            .map(|_| self.backward_goal(rng))
+            .find(|goal| !is_tautology(goal))
+            .ok_or(GenerationError::CannotProve)?;
 
         // Create requirements from config
         let required = RequiredTechniques {
@@ -192,7 +198,7 @@ impl ProofTreeGenerator {
         };
 
         // Create construction context
-        let mut ctx = ConstructionContext::new(&self.config, required);
+        let mut ctx = ConstructionContext::new(&self.config, required).with_conclusion(&goal);
 
         // Build proof backward from goal
         let root = backward_construct(goal, &mut ctx, rng)?;
@@ -205,6 +211,20 @@ impl ProofTreeGenerator {
         Ok(ProofTree::new(root))
     }
 
+    /// Shape the goal based on requirements
+    fn backward_goal(&self, rng: &mut impl Rng) -> Formula {
+        if self.config.require_forces_cp {
+            // For CP-forcing, generate an implication goal
+            self.generate_implication_goal(rng)
+        } else if self.config.require_forces_ip {
+            // IP is only forced for atomic (or negated) conclusions
+            Formula::Atom(self.config.atom_pool[rng.gen_range(0..self.config.atom_pool.len())].clone())
+        } else {
+            // Otherwise random interesting goal
+            self.random_interesting_goal(rng)
+        }
+    }
+
     /// Generate an implication goal that will likely force CP
     fn generate_implication_goal(&self, rng: &mut impl Rng) -> Formula {
         let atoms: Vec<Formula> = self.config.atom_pool.iter()
@@ -281,7 +301,10 @@ impl ProofTreeGenerator {
         let d = Formula::Atom(atoms.get(3).cloned().unwrap_or_else(|| "S".to_string()));
         let e = Formula::Atom(atoms.get(4).cloned().unwrap_or_else(|| "T".to_string()));
 
-        if self.config.require_forces_case_split {
+        if self.config.require_forces_case_split && self.config.require_forces_cp {
+            // Only the nested pattern forces both at once
+            FallbackTemplates::build_cp_case_split_variant_1(&a, &b, &c, &d)
+        } else if self.config.require_forces_case_split {
             // Multiple case-split patterns for variety
             let variant = rng.gen_range(0..4);
             match variant {
@@ -310,11 +333,9 @@ impl ProofTreeGenerator {
     }
 
     /// Generate a single proof tree (may be degenerate)
-    fn generate_once(&mut self) -> ProofTree {
-        let mut rng = rand::thread_rng();
-
+    fn generate_once(&mut self, rng: &mut impl Rng) -> ProofTree {
         // Start with a random goal
-        let goal = self.random_interesting_goal(&mut rng);
+        let goal = self.random_interesting_goal(rng);
 
         // Reset state
         self.used_fragments = 0;
@@ -324,7 +345,7 @@ impl ProofTreeGenerator {
         self.combined_premises_tt = TAUTOLOGY;
 
         // Build the proof tree
-        let root = self.build_proof_of(&mut rng, goal);
+        let root = self.build_proof_of(rng, goal);
 
         ProofTree::new(root)
     }
@@ -446,11 +467,10 @@ impl ProofTreeGenerator {
                         None,
                     );
                 }
-                // Implication couldn't be committed - just use the safe premise directly
-                // This creates an invalid proof, but validation will catch it
-                return ProofNode::premise(safe_premise);
+                // Implication couldn't be committed - fall through. The node must
+                // still prove `goal`, or the tree stops being a derivation.
             }
-            // No safe premise found - create premise anyway, validation will catch it
+            // No usable safe premise - create premise anyway, validation will catch it
             // Don't call commit_premise since it will fail
             return ProofNode::premise(goal);
         }
@@ -988,4 +1008,56 @@ mod tests {
             assert!(tree.is_valid());
         }
     }
+
+    #[test]
+    fn test_forced_ip_generation() {
+        use rand::{rngs::StdRng, SeedableRng};
+        let mut config = TreeGenConfig::for_difficulty_value(50);
+        config.require_forces_cp = false;
+        config.require_forces_case_split = false;
+        config.require_forces_ip = true;
+
+        let mut gen = ProofTreeGenerator::new(config);
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..5 {
+            let tree = gen.generate_with_rng(&mut rng);
+            assert!(tree.validate_with_difficulty(0, false, false, true).is_ok());
+            assert!(tree.max_nesting >= 1);
+        }
+    }
+
+    #[test]
+    fn test_cp_case_split_template_forces_both() {
+        let atoms: Vec<Formula> = ["P", "Q", "R", "S"].iter().map(|a| Formula::Atom(a.to_string())).collect();
+        let tree = FallbackTemplates::build_cp_case_split_variant_1(&atoms[0], &atoms[1], &atoms[2], &atoms[3]);
+        assert!(tree.validate_with_difficulty(0, true, true, false).is_ok());
+        assert_eq!(tree.max_nesting, 2);
+    }
+
+    #[test]
+    fn test_backward_forces_cp_and_case_split() {
+        use rand::{rngs::StdRng, SeedableRng};
+        for (cp, case_split) in [(true, false), (false, true), (true, true)] {
+            let mut config = TreeGenConfig::for_difficulty_value(30);
+            config.require_forces_cp = cp;
+            config.require_forces_case_split = case_split;
+            let gen = ProofTreeGenerator::new(config);
+            let mut rng = StdRng::seed_from_u64(1);
+            let tree = (0..200).find_map(|_| gen.generate_backward(&mut rng).ok()
+                .filter(|tree| tree.validate_with_difficulty(0, cp, case_split, false).is_ok()));
+            assert!(tree.is_some(), "no backward tree forcing cp={} case_split={}", cp, case_split);
+        }
+    }
+
+    #[test]
+    fn test_generate_with_rng_is_reproducible() {
+        use rand::{rngs::StdRng, SeedableRng};
+        for seed in 0..5 {
+            let config = TreeGenConfig::for_difficulty_value(60);
+            let a = ProofTreeGenerator::new(config.clone()).generate_with_rng(&mut StdRng::seed_from_u64(seed));
+            let b = ProofTreeGenerator::new(config).generate_with_rng(&mut StdRng::seed_from_u64(seed));
+            assert_eq!(a.premises(), b.premises());
+            assert_eq!(a.conclusion(), b.conclusion());
+        }
+    }
 }
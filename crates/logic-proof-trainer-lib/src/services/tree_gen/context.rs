@@ -105,8 +105,10 @@ impl TreeGenConfig {
     pub fn for_difficulty_value(difficulty: u8) -> Self {
         let d = difficulty.clamp(1, 100) as usize;
 
-        // Atom count: 2-5 (2 at d=1, 5 at d=100)
-        let atom_count = 2 + (d * 3 / 100);
+        // Atom count: 2-5 (2 at d=1, 5 at d=100), at least 3 once CP and
+        // case splits are forced: two atoms leave almost no theorem that
+        // needs them
+        let atom_count = (2 + (d * 3 / 100)).max(if d >= 30 { 3 } else { 2 });
         let atoms: Vec<String> = ["P", "Q", "R", "S", "T"]
             .iter()
             .take(atom_count)
@@ -162,6 +164,9 @@ pub struct ConstructionContext {
     pub max_nesting: usize,
     /// Current nesting depth
     pub current_nesting: usize,
+    /// Truth table of the theorem's conclusion, when known. No single
+    /// premise may entail it, or the proof built around it is trivial.
+    pub conclusion_tt: Option<u32>,
 }
 
 impl ConstructionContext {
@@ -177,13 +182,31 @@ impl ConstructionContext {
             atom_pool: config.atom_pool.clone(),
             max_nesting: config.max_nesting,
             current_nesting: 0,
+            conclusion_tt: None,
         }
     }
 
+    /// Forbid premises that alone entail `conclusion`.
+    pub fn with_conclusion(mut self, conclusion: &Formula) -> Self {
+        self.conclusion_tt = Some(compute_truth_table(conclusion));
+        self
+    }
+
+    /// Whether a premise with truth table `tt` would entail the conclusion on its own
+    fn entails_conclusion(&self, tt: u32) -> bool {
+        self.conclusion_tt.is_some_and(|conclusion| tt & !conclusion == 0)
+    }
+
     /// Check if a formula can be committed as a premise without creating degeneracy
     pub fn can_commit_premise(&self, formula: &Formula) -> bool {
         let tt = compute_truth_table(formula);
 
+        // A tautology contributes nothing; a premise entailing the conclusion
+        // makes the proof trivial
+        if tt == TAUTOLOGY || self.entails_conclusion(tt) {
+            return false;
+        }
+
         // Would create contradiction?
         if (self.combined_premises_tt & tt) == 0 {
             return false;
@@ -202,6 +225,11 @@ impl ConstructionContext {
     pub fn commit_premise(&mut self, formula: Formula) -> bool {
         let tt = compute_truth_table(&formula);
 
+        // Check for tautology or a premise entailing the conclusion alone
+        if tt == TAUTOLOGY || self.entails_conclusion(tt) {
+            return false;
+        }
+
         // Check for contradiction
         if (self.combined_premises_tt & tt) == 0 {
             return false;
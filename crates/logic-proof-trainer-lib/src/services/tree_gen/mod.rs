@@ -11,6 +11,7 @@ pub use context::{
     TAUTOLOGY,
 };
 pub use builder::ProofTreeGenerator;
+pub use templates::FallbackTemplates;
 
 // Re-export for backward compatibility
 pub use backward::backward_construct;
@@ -139,6 +139,51 @@ impl FallbackTemplates {
         ProofTree::new(root)
     }
 
+    /// CP + case split: A∨B, (A∧C)⊃D, (B∧C)⊃D ⊢ C⊃D
+    /// Case split nested inside CP; each branch needs the CP assumption
+    pub fn build_cp_case_split_variant_1(a: &Formula, b: &Formula, c: &Formula, d: &Formula) -> ProofTree {
+        let a_or_b = Formula::Or(Box::new(a.clone()), Box::new(b.clone()));
+        let a_and_c = Formula::And(Box::new(a.clone()), Box::new(c.clone()));
+        let b_and_c = Formula::And(Box::new(b.clone()), Box::new(c.clone()));
+        let ac_impl_d = Formula::Implies(Box::new(a_and_c.clone()), Box::new(d.clone()));
+        let bc_impl_d = Formula::Implies(Box::new(b_and_c.clone()), Box::new(d.clone()));
+        let conclusion = Formula::Implies(Box::new(c.clone()), Box::new(d.clone()));
+
+        let root = ProofNode::derivation(
+            conclusion,
+            "CP",
+            vec![
+                ProofNode::assumption(c.clone()),
+                ProofNode::derivation(
+                    d.clone(),
+                    "CaseSplit",
+                    vec![
+                        ProofNode::premise(a_or_b),
+                        ProofNode::assumption(a.clone()),
+                        ProofNode::derivation(d.clone(), "MP", vec![
+                            ProofNode::premise(ac_impl_d),
+                            ProofNode::derivation(a_and_c, "Conj", vec![
+                                ProofNode::assumption(a.clone()),
+                                ProofNode::assumption(c.clone()),
+                            ], None),
+                        ], None),
+                        ProofNode::assumption(b.clone()),
+                        ProofNode::derivation(d.clone(), "MP", vec![
+                            ProofNode::premise(bc_impl_d),
+                            ProofNode::derivation(b_and_c, "Conj", vec![
+                                ProofNode::assumption(b.clone()),
+                                ProofNode::assumption(c.clone()),
+                            ], None),
+                        ], None),
+                    ],
+                    None,
+                ),
+            ],
+            Some(c.clone()),
+        );
+        ProofTree::new(root)
+    }
+
     /// CP Variant 1: A⊃B, C⊃D ⊢ (A∧C)⊃(B∧D)
     pub fn build_cp_variant_1(a: &Formula, b: &Formula, c: &Formula, d: &Formula) -> ProofTree {
         let a_impl_b = Formula::Implies(Box::new(a.clone()), Box::new(b.clone()));
@@ -114,6 +114,17 @@ impl DynTruthTable {
     pub fn eq(&self, other: &Self) -> bool {
         self.num_vars == other.num_vars && self.bits == other.bits
     }
+
+    /// Number of variables (columns) this table ranges over.
+    pub fn num_vars(&self) -> u8 {
+        self.num_vars
+    }
+
+    /// Truth value at `row`. Row 0 is the all-true assignment; variable 0 is
+    /// true for the first half of the rows, variable n-1 alternates every row.
+    pub fn get(&self, row: usize) -> bool {
+        (self.bits[row / 64] >> (row % 64)) & 1 == 1
+    }
 }
 
 /// Collect atoms from a formula into a sorted Vec (alphabetical, deterministic).
@@ -132,6 +143,16 @@ pub fn compute_truth_table_dynamic(formula: &Formula) -> DynTruthTable {
     eval_dyn(formula, &var_map, num_vars)
 }
 
+/// Compute a dynamic truth table over an explicit atom ordering, so several
+/// formulas can share the same columns. Atoms not in `atoms` evaluate as true.
+pub fn compute_truth_table_over(formula: &Formula, atoms: &[String]) -> DynTruthTable {
+    let num_vars = atoms.len().max(1) as u8;
+    let var_map: std::collections::HashMap<&str, u8> = atoms.iter().enumerate()
+        .map(|(i, a)| (a.as_str(), i as u8))
+        .collect();
+    eval_dyn(formula, &var_map, num_vars)
+}
+
 fn eval_dyn(formula: &Formula, var_map: &std::collections::HashMap<&str, u8>, num_vars: u8) -> DynTruthTable {
     match formula {
         Formula::Atom(name) => {
@@ -163,6 +184,41 @@ pub fn is_tautology_dynamic(formula: &Formula) -> bool {
     }
 }
 
+/// Check if two formulas are semantically equivalent, auto-selecting the engine
+/// like `is_tautology_dynamic`. Tables are built over the union of both atom sets.
+pub fn are_equivalent_dynamic(f1: &Formula, f2: &Formula) -> bool {
+    let atoms: Vec<String> = f1.atoms().into_iter()
+        .chain(f2.atoms())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let standard = ["P", "Q", "R", "S", "T"];
+    if atoms.iter().all(|a| standard.contains(&a.as_str())) {
+        are_equivalent(f1, f2)
+    } else {
+        compute_truth_table_over(f1, &atoms).eq(&compute_truth_table_over(f2, &atoms))
+    }
+}
+
+/// Check if premises entail a conclusion, auto-selecting the engine like
+/// `is_tautology_dynamic`. Tables are built over all atoms of the argument.
+pub fn entails_dynamic(premises: &[Formula], conclusion: &Formula) -> bool {
+    let atoms: Vec<String> = premises.iter()
+        .chain(std::iter::once(conclusion))
+        .flat_map(|f| f.atoms())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let standard = ["P", "Q", "R", "S", "T"];
+    if atoms.iter().all(|a| standard.contains(&a.as_str())) {
+        return entails(premises, conclusion);
+    }
+    premises.iter()
+        .fold(DynTruthTable::tautology(atoms.len().max(1) as u8), |acc, p| acc.and(&compute_truth_table_over(p, &atoms)))
+        .and(&compute_truth_table_over(conclusion, &atoms).not())
+        .is_contradiction()
+}
+
 /// Variable truth tables (standard row ordering PQRST from 11111 to 00000)
 fn var_truth_table(name: &str) -> u32 {
     match name {
@@ -522,6 +578,93 @@ pub fn validate_theorem_with_difficulty(
     Ok(())
 }
 
+/// Validate that a theorem is non-degenerate, auto-selecting the engine.
+/// Runs the same checks as `validate_theorem` (1-8), in the same order and
+/// with the same errors, but on dynamic truth tables over the theorem's shared atoms when it uses atoms outside P..T.
+pub fn validate_theorem_dynamic(premises: &[Formula], conclusion: &Formula) -> Result<(), DegenerateProofError> {
+    let atoms: Vec<String> = premises.iter()
+        .chain(std::iter::once(conclusion))
+        .flat_map(|f| f.atoms())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let standard = ["P", "Q", "R", "S", "T"];
+    if atoms.iter().all(|a| standard.contains(&a.as_str())) {
+        return validate_theorem(premises, conclusion);
+    }
+
+    let num_vars = atoms.len() as u8;
+    let table = |f: &Formula| compute_truth_table_over(f, &atoms);
+    let conjunction = |tables: &[&DynTruthTable]| {
+        tables.iter().fold(DynTruthTable::tautology(num_vars), |acc, t| acc.and(t))
+    };
+    let entails_tt = |ps: &DynTruthTable, c: &DynTruthTable| ps.and(&c.not()).is_contradiction();
+
+    let premise_tts: Vec<DynTruthTable> = premises.iter().map(&table).collect();
+    let conclusion_tt = table(conclusion);
+    let all_premises = conjunction(&premise_tts.iter().collect::<Vec<_>>());
+
+    // 1. Premises consistent
+    if all_premises.is_contradiction() {
+        return Err(DegenerateProofError::ContradictoryPremises);
+    }
+
+    // 1.5. No tautological premises
+    if premise_tts.iter().any(|t| t.is_tautology()) {
+        return Err(DegenerateProofError::TautologicalPremise);
+    }
+
+    // 2. Conclusion not a tautology
+    if conclusion_tt.is_tautology() {
+        return Err(DegenerateProofError::TautologicalConclusion);
+    }
+
+    // 3. No single premise entails conclusion
+    if premise_tts.iter().any(|t| entails_tt(t, &conclusion_tt)) {
+        return Err(DegenerateProofError::SinglePremiseEntails);
+    }
+
+    // 4. Negation of conclusion not available
+    let neg_conclusion_tt = conclusion_tt.not();
+    if premise_tts.iter().any(|t| t.eq(&neg_conclusion_tt)) {
+        return Err(DegenerateProofError::NegationOfConclusionAvailable);
+    }
+
+    // 5. Conditional not trivially provable via explosion
+    let mut current = conclusion;
+    while let Formula::Implies(ant, cons) = current {
+        let neg_ant_tt = table(ant).not();
+        if premise_tts.iter().any(|t| t.eq(&neg_ant_tt)) {
+            return Err(DegenerateProofError::ConditionalTrivialViaExplosion);
+        }
+        current = cons;
+    }
+
+    // 6. No redundant premises
+    for i in 0..premise_tts.len() {
+        if premise_tts[i + 1..].iter().any(|t| t.eq(&premise_tts[i])) {
+            return Err(DegenerateProofError::RedundantPremises);
+        }
+    }
+
+    // 7. All premises necessary. As in `validate_theorem`, premises that do
+    // not entail the conclusion fail here, so check 8 (validity) is folded in
+    if !entails_tt(&all_premises, &conclusion_tt) {
+        return Err(DegenerateProofError::UnnecessaryPremise);
+    }
+    for i in 0..premise_tts.len() {
+        let reduced: Vec<&DynTruthTable> = premise_tts.iter().enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, t)| t)
+            .collect();
+        if entails_tt(&conjunction(&reduced), &conclusion_tt) {
+            return Err(DegenerateProofError::UnnecessaryPremise);
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1054,4 +1197,59 @@ mod tests {
         );
         assert!(is_tautology_dynamic(&wrapped));
     }
+
+    #[test]
+    fn test_equivalent_dynamic_non_standard_atoms() {
+        // The u32 engine aliases A and B to P's column; the dynamic one must not.
+        assert!(are_equivalent(&atom("A"), &atom("B")));
+        assert!(!are_equivalent_dynamic(&atom("A"), &atom("B")));
+        assert!(are_equivalent_dynamic(
+            &implies(atom("A"), atom("B")),
+            &or(not(atom("A")), atom("B")),
+        ));
+    }
+
+    #[test]
+    fn test_entails_dynamic_non_standard_atoms() {
+        let premises = vec![implies(atom("A"), atom("B")), atom("A")];
+        assert!(entails_dynamic(&premises, &atom("B")));
+        // The u32 engine reads A and B as the same column
+        assert!(entails(&premises[1..], &atom("B")));
+        assert!(!entails_dynamic(&premises[1..], &atom("B")));
+    }
+
+    #[test]
+    fn test_validate_dynamic_matches_u32_on_standard_atoms() {
+        let premises = vec![implies(atom("P"), atom("Q")), atom("P")];
+        assert!(validate_theorem_dynamic(&premises, &atom("Q")).is_ok());
+        assert!(matches!(
+            validate_theorem_dynamic(&[and(atom("P"), atom("Q"))], &atom("P")),
+            Err(DegenerateProofError::SinglePremiseEntails)
+        ));
+    }
+
+    #[test]
+    fn test_validate_dynamic_non_standard_atoms() {
+        let premises = vec![implies(atom("A"), atom("B")), atom("A")];
+        assert!(validate_theorem_dynamic(&premises, &atom("B")).is_ok());
+
+        // A, B ⊢ C is invalid even though the u32 engine sees P, P ⊢ P.
+        // Both engines report an invalid argument as an unnecessary premise.
+        assert!(matches!(
+            validate_theorem_dynamic(&[atom("A"), atom("B")], &atom("C")),
+            Err(DegenerateProofError::UnnecessaryPremise)
+        ));
+        assert!(matches!(
+            validate_theorem(&[atom("P"), atom("Q")], &atom("R")),
+            Err(DegenerateProofError::UnnecessaryPremise)
+        ));
+        assert!(matches!(
+            validate_theorem_dynamic(&[atom("A"), atom("A"), implies(atom("A"), atom("B"))], &atom("B")),
+            Err(DegenerateProofError::RedundantPremises)
+        ));
+        assert!(matches!(
+            validate_theorem_dynamic(&[atom("A"), atom("C"), implies(atom("A"), atom("B"))], &atom("B")),
+            Err(DegenerateProofError::UnnecessaryPremise)
+        ));
+    }
 }
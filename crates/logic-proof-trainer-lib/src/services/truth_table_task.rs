@@ -0,0 +1,307 @@
+//! Truth-table tasks: write out the full truth table of an argument.
+//!
+//! The expected table has a column per atom and per premise and conclusion
+//! (atoms that are themselves premises share their column), and a row per
+//! assignment. An answer may order its columns and rows as it likes and
+//! write values as T/F or 1/0. Each row is matched to the reference
+//! `DynTruthTable` row for its atom values and graded on its own.
+
+use std::collections::{BTreeSet, HashSet};
+
+use crate::models::Formula;
+use super::truth_table::{compute_truth_table_over, DynTruthTable};
+
+/// Most atoms a truth-table task may have (2^8 = 256 rows)
+pub const MAX_TABLE_ATOMS: usize = 8;
+
+/// Why an answer table cannot be graded at all
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableError {
+    /// The argument has more atoms than `MAX_TABLE_ATOMS`
+    TooManyAtoms(usize),
+    /// The answer has no header line
+    Empty,
+    /// A header cell names neither an atom nor a formula of the argument
+    UnknownColumn(String),
+    /// Two header cells name the same column
+    DuplicateColumn(String),
+    /// A column of the expected table is not in the header
+    MissingColumn(String),
+}
+
+impl std::fmt::Display for TableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableError::TooManyAtoms(n) => write!(f, "{} atoms, more than the {} a truth-table task allows", n, MAX_TABLE_ATOMS),
+            TableError::Empty => write!(f, "The table has no header line"),
+            TableError::UnknownColumn(cell) => write!(f, "Column '{}' is not an atom or formula of the argument", cell),
+            TableError::DuplicateColumn(cell) => write!(f, "Column '{}' appears twice", cell),
+            TableError::MissingColumn(label) => write!(f, "Column '{}' is missing", label),
+        }
+    }
+}
+
+/// The columns of an argument's truth table: sorted atoms, then each
+/// distinct premise and the conclusion unless it is an atom
+#[derive(Debug, Clone)]
+pub struct TableColumns {
+    pub atoms: Vec<String>,
+    pub formulas: Vec<Formula>,
+}
+
+impl TableColumns {
+    pub fn new(premises: &[Formula], conclusion: &Formula) -> Result<Self, TableError> {
+        let all: Vec<&Formula> = premises.iter().chain(std::iter::once(conclusion)).collect();
+        let atoms: Vec<String> = all.iter()
+            .flat_map(|f| f.atoms())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if atoms.len() > MAX_TABLE_ATOMS {
+            return Err(TableError::TooManyAtoms(atoms.len()));
+        }
+        let mut formulas: Vec<Formula> = Vec::new();
+        for f in all {
+            if !matches!(f, Formula::Atom(_)) && !formulas.contains(f) {
+                formulas.push(f.clone());
+            }
+        }
+        Ok(Self { atoms, formulas })
+    }
+
+    /// Header labels: atom names, then formulas in bracketed ASCII
+    pub fn labels(&self) -> Vec<String> {
+        self.atoms.iter().cloned()
+            .chain(self.formulas.iter().map(|f| f.ascii_string_bracketed()))
+            .collect()
+    }
+
+    pub fn row_count(&self) -> usize {
+        1 << self.atoms.len()
+    }
+
+    /// Reference table of each formula column, over `atoms`
+    fn reference(&self) -> Vec<DynTruthTable> {
+        self.formulas.iter().map(|f| compute_truth_table_over(f, &self.atoms)).collect()
+    }
+
+    /// The expected table as text: `|`-separated, T/F, starting from the
+    /// all-true row.
+    pub fn render(&self) -> String {
+        let reference = self.reference();
+        let n = self.atoms.len();
+        let mut lines = vec![self.labels().join(" | ")];
+        for row in 0..self.row_count() {
+            let cells: Vec<&str> = (0..n)
+                .map(|i| (row >> (n - 1 - i)) & 1 == 0)
+                .chain(reference.iter().map(|t| t.get(row)))
+                .map(|v| if v { "T" } else { "F" })
+                .collect();
+            lines.push(cells.join(" | "));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Row index of an assignment in `DynTruthTable` order (row 0 all true)
+fn row_index(values: &[bool]) -> usize {
+    let n = values.len();
+    values.iter().enumerate()
+        .filter(|(_, v)| !**v)
+        .map(|(i, _)| 1 << (n - 1 - i))
+        .sum()
+}
+
+/// Result of grading an answer table
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableGrade {
+    /// Rows the expected table has
+    pub rows: usize,
+    /// Rows of the answer that match an expected row not matched before
+    pub rows_correct: usize,
+    /// First answer row (1-based, not counting the header) that is wrong
+    pub first_wrong_row: Option<usize>,
+    /// What is wrong with each wrong row, and any assignments with no row
+    pub errors: Vec<String>,
+}
+
+impl TableGrade {
+    /// Share of the expected rows answered correctly, in percent
+    pub fn percent_correct(&self) -> f64 {
+        100.0 * self.rows_correct as f64 / self.rows as f64
+    }
+
+    /// Every expected row is there and right, with nothing extra
+    pub fn is_correct(&self) -> bool {
+        self.rows_correct == self.rows && self.errors.is_empty()
+    }
+}
+
+/// Split a line into cells on the table's delimiter. Outer `|` of markdown
+/// rows are dropped.
+fn split_cells(line: &str, delimiter: Option<char>) -> Vec<String> {
+    match delimiter {
+        Some(d) => line.trim().trim_matches(d).split(d).map(|c| c.trim().to_string()).collect(),
+        None => line.split_whitespace().map(str::to_string).collect(),
+    }
+}
+
+/// The delimiter a header uses: tab, comma or `|`, in that order, else
+/// whitespace. With `|`, disjunction in headers must be written `v`.
+fn detect_delimiter(header: &str) -> Option<char> {
+    ['\t', ',', '|'].into_iter().find(|d| header.contains(*d))
+}
+
+fn parse_value(cell: &str) -> Option<bool> {
+    match cell.to_lowercase().as_str() {
+        "t" | "1" | "true" => Some(true),
+        "f" | "0" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Markdown separator lines such as `|---|:--:|`
+fn is_separator(line: &str) -> bool {
+    line.chars().all(|c| matches!(c, '-' | ':' | '|' | '+' | '=' | ' ' | '\t'))
+}
+
+/// Grade an answer table for the argument `premises` ⊢ `conclusion`. The
+/// first non-empty line is the header; blank and markdown separator lines
+/// are skipped.
+pub fn grade_truth_table(premises: &[Formula], conclusion: &Formula, answer: &str) -> Result<TableGrade, TableError> {
+    let columns = TableColumns::new(premises, conclusion)?;
+    let mut lines = answer.lines().map(str::trim).filter(|l| !l.is_empty() && !is_separator(l));
+    let header = lines.next().ok_or(TableError::Empty)?;
+    let delimiter = detect_delimiter(header);
+
+    // For each header cell, its column: an atom index or a formula index
+    // after the atoms
+    let labels = columns.labels();
+    let mut positions: Vec<usize> = Vec::new();
+    for cell in split_cells(header, delimiter) {
+        let column = columns.atoms.iter().position(|a| *a == cell).or_else(|| {
+            let formula = Formula::parse(&cell).ok()?;
+            match &formula {
+                Formula::Atom(name) => columns.atoms.iter().position(|a| a == name),
+                _ => columns.formulas.iter().position(|f| *f == formula).map(|i| columns.atoms.len() + i),
+            }
+        });
+        let column = column.ok_or_else(|| TableError::UnknownColumn(cell.clone()))?;
+        if positions.contains(&column) {
+            return Err(TableError::DuplicateColumn(cell));
+        }
+        positions.push(column);
+    }
+    if let Some(missing) = (0..labels.len()).find(|c| !positions.contains(c)) {
+        return Err(TableError::MissingColumn(labels[missing].clone()));
+    }
+
+    let reference = columns.reference();
+    let n = columns.atoms.len();
+    let mut seen: HashSet<usize> = HashSet::new();
+    let mut grade = TableGrade { rows: columns.row_count(), rows_correct: 0, first_wrong_row: None, errors: Vec::new() };
+    for (i, line) in lines.enumerate() {
+        let row_number = i + 1;
+        match grade_row(&split_cells(line, delimiter), &positions, &columns, &reference, &mut seen) {
+            Ok(()) => grade.rows_correct += 1,
+            Err(e) => {
+                grade.first_wrong_row.get_or_insert(row_number);
+                grade.errors.push(format!("Row {}: {}", row_number, e));
+            }
+        }
+    }
+    for row in (0..columns.row_count()).filter(|r| !seen.contains(r)) {
+        let assignment: Vec<String> = columns.atoms.iter().enumerate()
+            .map(|(i, a)| format!("{}={}", a, if (row >> (n - 1 - i)) & 1 == 0 { "T" } else { "F" }))
+            .collect();
+        grade.errors.push(format!("No row for {}", assignment.join(", ")));
+    }
+    Ok(grade)
+}
+
+/// Check one answer row against the reference row for its atom values, and
+/// mark that assignment as seen.
+fn grade_row(
+    cells: &[String],
+    positions: &[usize],
+    columns: &TableColumns,
+    reference: &[DynTruthTable],
+    seen: &mut HashSet<usize>,
+) -> Result<(), String> {
+    if cells.len() != positions.len() {
+        return Err(format!("{} cells for {} columns", cells.len(), positions.len()));
+    }
+    let n = columns.atoms.len();
+    let mut values = vec![false; positions.len()];
+    for (cell, &column) in cells.iter().zip(positions) {
+        values[column] = parse_value(cell).ok_or_else(|| format!("'{}' is not a truth value", cell))?;
+    }
+    let row = row_index(&values[..n]);
+    if !seen.insert(row) {
+        return Err("repeats an earlier row's atom values".to_string());
+    }
+    let wrong: Vec<String> = columns.formulas.iter().zip(reference).enumerate()
+        .filter(|(i, (_, table))| values[n + i] != table.get(row))
+        .map(|(_, (f, _))| f.ascii_string_bracketed())
+        .collect();
+    if wrong.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("wrong value for {}", wrong.join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argument() -> (Vec<Formula>, Formula) {
+        (vec![Formula::parse("P -> Q").unwrap(), Formula::parse("P").unwrap()], Formula::parse("Q").unwrap())
+    }
+
+    #[test]
+    fn test_rendered_table_grades_correct() {
+        let (premises, conclusion) = argument();
+        let columns = TableColumns::new(&premises, &conclusion).unwrap();
+        assert_eq!(columns.labels(), vec!["P", "Q", "P > Q"]);
+        let table = columns.render();
+        assert_eq!(table.lines().nth(4), Some("F | F | T"));
+        let grade = grade_truth_table(&premises, &conclusion, &table).unwrap();
+        assert!(grade.is_correct());
+        assert_eq!(grade.percent_correct(), 100.0);
+    }
+
+    #[test]
+    fn test_reordered_columns_and_rows() {
+        let (premises, conclusion) = argument();
+        let answer = "P>Q,Q,P\n1,0,0\n1,1,0\n1,1,1\n0,0,1";
+        let grade = grade_truth_table(&premises, &conclusion, answer).unwrap();
+        assert!(grade.is_correct(), "{:?}", grade.errors);
+
+        let markdown = "| Q | P | P > Q |\n|---|---|---|\n| T | T | T |\n| F | T | T |\n| T | F | T |";
+        let grade = grade_truth_table(&premises, &conclusion, markdown).unwrap();
+        assert_eq!(grade.rows_correct, 2);
+        assert_eq!(grade.first_wrong_row, Some(2));
+        assert_eq!(grade.percent_correct(), 50.0);
+        assert_eq!(grade.errors.len(), 2);
+        assert!(!grade.is_correct());
+    }
+
+    #[test]
+    fn test_header_errors() {
+        let (premises, conclusion) = argument();
+        assert_eq!(grade_truth_table(&premises, &conclusion, ""), Err(TableError::Empty));
+        assert_eq!(
+            grade_truth_table(&premises, &conclusion, "P | Q"),
+            Err(TableError::MissingColumn("P > Q".to_string()))
+        );
+        assert_eq!(
+            grade_truth_table(&premises, &conclusion, "P | Q | R"),
+            Err(TableError::UnknownColumn("R".to_string()))
+        );
+        assert_eq!(
+            grade_truth_table(&premises, &conclusion, "P | P | P > Q"),
+            Err(TableError::DuplicateColumn("P".to_string()))
+        );
+    }
+}
@@ -41,7 +41,7 @@ impl ProofVerifier {
                 Self::verify_inference(line, *rule, lines, proof)
             }
             Justification::Equivalence { rule, line: ref_line } => {
-                Self::verify_equivalence(line, *rule, *ref_line, proof)
+                Self::verify_equivalence(line, *rule, *ref_line, proof, false)
             }
             Justification::SubproofConclusion {
                 technique,
@@ -162,11 +162,14 @@ impl ProofVerifier {
         }
     }
 
+    /// `single_occurrence` also accepts a rewrite of one occurrence of a
+    /// subformula (see `is_valid_replacement`), as equivalence proofs do.
     fn verify_equivalence(
         line: &ProofLine,
         rule: EquivalenceRule,
         ref_line: usize,
         proof: &Proof,
+        single_occurrence: bool,
     ) -> VerificationResult {
         // Check reference line exists and is accessible
         if ref_line >= line.line_number {
@@ -201,7 +204,12 @@ impl ProofVerifier {
         }
 
         // Check if the target formula can be derived from the source using this rule
-        if Self::is_valid_equivalence_application(&source_line.formula, &line.formula, rule) {
+        let valid = if single_occurrence {
+            Self::is_valid_replacement(&source_line.formula, &line.formula, rule)
+        } else {
+            Self::is_valid_equivalence_application(&source_line.formula, &line.formula, rule)
+        };
+        if valid {
             VerificationResult::valid()
         } else {
             // Check for case-sensitivity issues to provide a better error message
@@ -279,6 +287,47 @@ impl ProofVerifier {
         Self::check_subformula_equivalence(source, target, rule)
     }
 
+    /// Whether `target` is `source` with `rule` applied to the whole formula,
+    /// to every occurrence of a subformula, or to just one occurrence of it.
+    /// Only equivalence proofs accept the last; `verify_proof` does not.
+    pub fn is_valid_replacement(source: &Formula, target: &Formula, rule: EquivalenceRule) -> bool {
+        Self::is_valid_equivalence_application(source, target, rule)
+            || Self::check_positional_equivalence(source, target, rule)
+    }
+
+    /// Replacement of a single occurrence, as the obfuscation passes rewrite.
+    /// The replaced occurrence holds every difference between `source` and
+    /// `target`, so only the subformulas on the path to the smallest one
+    /// holding them all need trying.
+    fn check_positional_equivalence(source: &Formula, target: &Formula, rule: EquivalenceRule) -> bool {
+        let (mut from, mut to) = (source, target);
+        loop {
+            if rule.equivalent_forms(from).contains(to) {
+                return true;
+            }
+            let next = match (from, to) {
+                (Formula::Not(a), Formula::Not(b)) => Some((a, b)),
+                (Formula::And(l1, r1), Formula::And(l2, r2))
+                | (Formula::Or(l1, r1), Formula::Or(l2, r2))
+                | (Formula::Implies(l1, r1), Formula::Implies(l2, r2))
+                | (Formula::Biconditional(l1, r1), Formula::Biconditional(l2, r2)) => {
+                    if l1 == l2 {
+                        Some((r1, r2))
+                    } else if r1 == r2 {
+                        Some((l1, l2))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            };
+            match next {
+                Some((a, b)) => (from, to) = (a.as_ref(), b.as_ref()),
+                None => return false,
+            }
+        }
+    }
+
     fn check_subformula_equivalence(source: &Formula, target: &Formula, rule: EquivalenceRule) -> bool {
         // Try to find a subformula in source that can be transformed to match target
         for subformula in source.subformulas() {
@@ -392,6 +441,39 @@ impl ProofVerifier {
         }
     }
 
+    /// Replacement-only restriction of equivalence proofs: apart from the
+    /// premise, every line must apply an equivalence rule to an earlier line.
+    pub fn verify_replacement_only(justification: &Justification) -> VerificationResult {
+        match justification {
+            Justification::Premise | Justification::Equivalence { .. } => VerificationResult::valid(),
+            _ => VerificationResult::invalid(
+                "Only equivalence rules (replacement) may be used in an equivalence proof",
+            ),
+        }
+    }
+
+    /// Verify all lines in an equivalence proof: `verify_replacement_only`,
+    /// then the usual check of each line, except that a rewrite may replace
+    /// a single occurrence of a subformula.
+    pub fn verify_equivalence_proof(proof: &mut Proof) {
+        for i in 0..proof.lines.len() {
+            let line = &proof.lines[i];
+            let mut result = Self::verify_replacement_only(&line.justification);
+            if result.is_valid {
+                result = match &line.justification {
+                    Justification::Equivalence { rule, line: ref_line } => {
+                        Self::verify_equivalence(line, *rule, *ref_line, proof, true)
+                    }
+                    _ => Self::verify_line(line, proof),
+                };
+            }
+
+            let line = &mut proof.lines[i];
+            line.is_valid = result.is_valid;
+            line.validation_message = result.message;
+        }
+    }
+
     /// Verify all lines in a proof
     pub fn verify_proof(proof: &mut Proof) {
         for i in 0..proof.lines.len() {
@@ -865,6 +947,59 @@ mod tests {
         assert!(result.is_valid);
     }
 
+    #[test]
+    fn test_verify_equivalence_single_occurrence() {
+        // Only the second P ⊃ Q is rewritten
+        let source = Formula::parse("(P -> Q) & (P -> Q)").unwrap();
+        let target = Formula::parse("(P -> Q) & (~P | Q)").unwrap();
+        assert!(ProofVerifier::is_valid_replacement(&source, &target, EquivalenceRule::Implication));
+        assert!(!ProofVerifier::is_valid_replacement(&source, &target, EquivalenceRule::Contraposition));
+        assert!(!ProofVerifier::is_valid_equivalence_application(&source, &target, EquivalenceRule::Implication));
+
+        // Only equivalence proofs accept it; verify_proof keeps rewriting every occurrence
+        let theorem = Theorem::new(vec![source.clone()], target.clone(), Difficulty::Easy, None, None);
+        let mut proof = Proof::new(theorem);
+        proof.add_line(target, Justification::Equivalence { rule: EquivalenceRule::Implication, line: 1 });
+        ProofVerifier::verify_proof(&mut proof);
+        assert!(!proof.lines[1].is_valid);
+        ProofVerifier::verify_equivalence_proof(&mut proof);
+        assert!(proof.lines[1].is_valid);
+
+        // Rewriting every occurrence is accepted by both
+        let all = Formula::parse("(~P | Q) & (~P | Q)").unwrap();
+        assert!(ProofVerifier::is_valid_equivalence_application(&source, &all, EquivalenceRule::Implication));
+        assert!(ProofVerifier::is_valid_replacement(&source, &all, EquivalenceRule::Implication));
+    }
+
+    #[test]
+    fn test_verify_equivalence_proof_replacement_only() {
+        let theorem = Theorem::new(
+            vec![Formula::parse("P & Q").unwrap()],
+            Formula::parse("Q & P").unwrap(),
+            Difficulty::Easy,
+            None,
+            None,
+        );
+        let mut proof = Proof::new(theorem.clone());
+        proof.add_line(
+            Formula::parse("Q & P").unwrap(),
+            Justification::Equivalence { rule: EquivalenceRule::Commutation, line: 1 },
+        );
+        ProofVerifier::verify_equivalence_proof(&mut proof);
+        assert!(proof.lines.iter().all(|l| l.is_valid));
+
+        // Valid inferences are still rejected
+        let mut proof = Proof::new(theorem);
+        proof.add_line(atom("P"), Justification::Inference { rule: InferenceRule::Simplification, lines: vec![1] });
+        proof.add_line(atom("Q"), Justification::Inference { rule: InferenceRule::Simplification, lines: vec![1] });
+        proof.add_line(
+            Formula::parse("Q & P").unwrap(),
+            Justification::Inference { rule: InferenceRule::Conjunction, lines: vec![3, 2] },
+        );
+        ProofVerifier::verify_equivalence_proof(&mut proof);
+        assert!(proof.lines[1..].iter().all(|l| !l.is_valid));
+    }
+
     #[test]
     fn test_verify_ip_close_with_contradiction_symbol() {
         // Simulates user's scenario:
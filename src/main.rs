@@ -1,14 +1,33 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use logic_proof_trainer_lib::models::{
     Formula, Proof, Justification,
-    theorem::{BaseComplexity, Difficulty, DifficultySpec, DifficultyTier, Theorem},
+    theorem::{BaseComplexity, Difficulty, DifficultySpec, SizeMetric, TargetSize, Theorem},
+    tier_presets::{TierPreset, TierPresets},
     rules::{InferenceRule, EquivalenceRule, ProofTechnique},
 };
-use logic_proof_trainer_lib::services::{TheoremGenerator, ProofVerifier, ObfuscateGenerator};
-use rand::Rng;
+use logic_proof_trainer_lib::services::{
+    ProofVerifier, ObfuscateGenerator, ProofTreeGenerator, TheoremMetrics,
+    ProofTree, QualityGates, Rejection, RejectionStats, TreeGenConfig, DEFAULT_MAX_RETRIES,
+    BudgetPolicy, GenerationBudget, add_distractors, DistractorError,
+    ChainConfig, ChainGenerator, CHAIN_RULES, SatConfig, generate_sat_problem, check_assignment,
+    TableColumns, grade_truth_table, Lexicon, NaturalTheorem, verbalize_argument,
+    LATEX_PACKAGES, theorem_to_latex, proof_to_fitch, proof_tree_to_bussproofs,
+    formulas_to_dot, proof_tree_to_dot, proof_to_dot, theorem_to_tptp, negated_conjecture_cnf,
+    DegenerateProofError, ImportRejection, ImportedProblem, parse_tptp_problem, parse_text_problems, validate_imported,
+    LEAN_PRELUDE, lean_theorem_name, theorem_to_lean, proof_to_lean,
+    Perturbation, near_miss, check_countermodel, entails_dynamic, MAX_JUDGMENT_ATOMS,
+    is_tautology_dynamic, theorem_class, validate_theorem_with_difficulty,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Instant;
 
 // ─── CLI argument parsing ───────────────────────────────────────────────────
 
@@ -23,77 +42,583 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Generate a benchmark theorem set
-    Generate {
-        /// Number of theorems to generate
-        #[arg(short, long, default_value_t = 100)]
-        count: usize,
-
-        /// Difficulty distribution as "N:tier,N:tier,..."
-        /// e.g. "30:easy,30:medium,20:hard,15:expert,5:nightmare"
-        #[arg(short, long)]
-        difficulty_distribution: Option<String>,
+    Generate(Box<GenerateArgs>),
 
-        /// Preset difficulty tier (easy/medium/hard/expert/nightmare/marathon/absurd/cosmic/mind)
+    /// Validate a proof against a theorem
+    Validate {
+        /// Path to theorem JSON file (single theorem object)
         #[arg(long)]
-        tier: Option<String>,
+        theorem: PathBuf,
 
-        /// Number of variables (2-20) for custom spec
+        /// Path to proof JSON file (array of proof lines)
         #[arg(long)]
-        variables: Option<u8>,
+        proof: PathBuf,
+    },
 
-        /// Number of passes (1-20) for custom spec
+    /// Check an answer to a validity-judgment theorem: a proof for a "valid"
+    /// verdict, a countermodel for an "invalid" one. For a satisfiability
+    /// task: an assignment for "satisfiable", a proof of # for "unsatisfiable".
+    /// For a truth-table task: the table, graded row by row.
+    CheckAnswer {
+        /// Path to theorem JSON file (single theorem object)
         #[arg(long)]
-        passes: Option<u16>,
+        theorem: PathBuf,
 
-        /// Transforms per pass (1-24) for custom spec
+        /// Path to answer JSON file ({"verdict": ..., "proof": [...]},
+        /// {"verdict": ..., "countermodel": {...}}, {"verdict": ..., "assignment": {...}}
+        /// or {"table": "..."})
         #[arg(long)]
-        transforms: Option<u16>,
+        answer: PathBuf,
+    },
 
-        /// Base complexity (simple/complex) for custom spec
-        #[arg(long)]
-        base: Option<String>,
+    /// Compute per-theorem metrics for a theorem set
+    Analyze {
+        /// Path to theorem set JSON file (array of theorems, as written by generate)
+        #[arg(short, long)]
+        input: PathBuf,
 
-        /// Substitution depth (0-4) for custom spec
-        #[arg(long)]
-        substitution: Option<u16>,
+        /// Output format (json/csv)
+        #[arg(short, long, default_value = "json")]
+        format: String,
+
+        /// Output file path (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Sample theorems per tier and report the resulting distributions
+    Calibrate {
+        /// Number of theorems to sample per tier
+        #[arg(short = 'n', long, default_value_t = 20)]
+        samples: usize,
 
-        /// Number of bridge atoms (0-5) for cross-zone interdependencies
+        /// Comma-separated tiers to sample, in difficulty order (default: all tiers)
         #[arg(long)]
-        bridge_atoms: Option<u8>,
+        tiers: Option<String>,
 
-        /// Maximum formula nodes (default: 20000) for custom spec
+        #[command(flatten)]
+        spec: SpecArgs,
+
+        #[command(flatten)]
+        quality: QualityArgs,
+
+        /// Output format (table/json)
+        #[arg(short, long, default_value = "table")]
+        format: String,
+
+        /// Seed for the samples (default: random, printed to stderr)
         #[arg(long)]
-        max_nodes: Option<u32>,
+        seed: Option<u64>,
+    },
+
+    /// Render theorems with their solutions, or one theorem with a proof,
+    /// for typesetting or checking in Lean
+    Render {
+        /// Path to a theorem JSON file: one theorem, or a set as written by generate
+        #[arg(short, long)]
+        input: PathBuf,
 
-        /// Maximum formula depth (default: 100) for custom spec
+        /// Path to proof JSON file (as for validate) to render instead of
+        /// the solution; the input must hold one theorem
         #[arg(long)]
-        max_depth: Option<u32>,
+        proof: Option<PathBuf>,
+
+        /// Output format (latex/dot/lean)
+        #[arg(short, long, default_value = "latex")]
+        format: String,
+
+        /// Graph to draw with --format dot: ast (syntax trees of the premises
+        /// and conclusion), tree (the solution's derivation) or proof (the
+        /// proof's citation graph)
+        #[arg(long, default_value = "proof")]
+        graph: String,
 
-        /// Disable gnarly combos (forced multi-rule transformation chains)
+        /// Draw identical subformulas once (--graph ast)
         #[arg(long)]
-        no_gnarly_combos: bool,
+        share: bool,
 
-        /// Enable gnarly combos (forced multi-rule transformation chains)
-        #[arg(long, conflicts_with = "no_gnarly_combos")]
-        gnarly_combos: bool,
+        /// Output file path (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
 
-        /// Output file path
-        #[arg(short, long, default_value = "theorems.json")]
+    /// Export theorems as problems for external provers and SAT solvers
+    Export {
+        /// Path to a theorem JSON file: one theorem, or a set as written by generate
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output format: tptp (FOF problem per theorem) or dimacs (CNF of
+        /// the negated conjecture per theorem)
+        #[arg(short, long, default_value = "tptp")]
+        format: String,
+
+        /// Directory to write the problems and their atom maps to
+        #[arg(short, long)]
         output: PathBuf,
     },
 
-    /// Validate a proof against a theorem
-    Validate {
-        /// Path to theorem JSON file (single theorem object)
-        #[arg(long)]
-        theorem: PathBuf,
+    /// Import propositional problems from TPTP or plain-text files as a
+    /// theorem set
+    Import {
+        /// Problem file, or a directory of .p, .tptp and .txt files
+        #[arg(short, long)]
+        input: PathBuf,
 
-        /// Path to proof JSON file (array of proof lines)
+        /// Input format (tptp/text); by default .p and .tptp files are TPTP
+        /// and other files text
+        #[arg(short, long)]
+        format: Option<String>,
+
+        /// Output file path for the theorem set
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Keep theorems that fail the degeneracy checks, such as
+        /// contradictory premises. Invalid arguments are always dropped.
         #[arg(long)]
-        proof: PathBuf,
+        keep_degenerate: bool,
+
+        /// Difficulty value (1-100) to label the theorems with
+        #[arg(long, default_value_t = 50, value_parser = clap::value_parser!(u8).range(1..=100))]
+        difficulty_value: u8,
     },
 }
 
+#[derive(Args)]
+struct GenerateArgs {
+    /// Number of theorems to generate
+    #[arg(short, long, default_value_t = 100)]
+    count: usize,
+
+    /// Difficulty distribution as "N:tier,N:tier,..."
+    /// e.g. "30:easy,30:medium,20:hard,15:expert,5:nightmare"
+    #[arg(short, long)]
+    difficulty_distribution: Option<String>,
+
+    /// Preset difficulty tier (baby/easy/medium/hard/expert/nightmare/marathon/absurd/cosmic/mind,
+    /// or any tier defined in --presets)
+    #[arg(long)]
+    tier: Option<String>,
+
+    #[command(flatten)]
+    spec: SpecArgs,
+
+    /// Keep the argument's premises instead of wrapping them into one
+    /// conditional tautology. Premises and conclusion are obfuscated separately.
+    #[arg(long)]
+    keep_premises: bool,
+
+    /// Build theorems backward from a proof with the proof-tree generator and
+    /// include that proof tree as each theorem's `solution`
+    #[arg(long, conflicts_with_all = ["tier", "difficulty_distribution", "keep_premises"])]
+    proof_tree: bool,
+
+    /// Techniques every proof-tree theorem must force, comma-separated:
+    /// cp, case-split, ip (default: derived from --tree-difficulty)
+    #[arg(long, requires = "proof_tree")]
+    require: Option<String>,
+
+    /// Maximum subproof nesting depth for proof-tree theorems
+    /// (default: derived from --tree-difficulty)
+    #[arg(long, requires = "proof_tree")]
+    nesting: Option<usize>,
+
+    /// Difficulty value (1-100) that sizes proof-tree theorems
+    #[arg(long, default_value_t = 50, requires = "proof_tree")]
+    tree_difficulty: u8,
+
+    /// Build chain theorems whose proof is exactly this many inference steps
+    /// (1-100) and include that proof as each theorem's `solution`
+    #[arg(long, conflicts_with_all = ["tier", "difficulty_distribution", "keep_premises", "proof_tree"])]
+    chain: Option<usize>,
+
+    /// Rules chain steps are drawn from, comma-separated: mp, mt, ds, hs, cd
+    /// (default: all)
+    #[arg(long, requires = "chain")]
+    chain_rules: Option<String>,
+
+    /// Mix this many distractor premises into every theorem: consistent with
+    /// the real premises and sharing their atoms, but not needed for the
+    /// conclusion. `essential_premises` records which premises are needed.
+    #[arg(long, default_value_t = 0)]
+    distractors: usize,
+
+    /// Validity-judgment set: make a share of the theorems near-miss invalid
+    /// arguments and label every theorem `valid` true or false
+    #[arg(long, conflicts_with = "distractors")]
+    judgment: bool,
+
+    /// Share of a judgment set that is made invalid (0-1)
+    #[arg(long, default_value_t = 0.5, requires = "judgment")]
+    invalid_fraction: f64,
+
+    /// Equivalence-proof tasks: prove the tier's obfuscated formula from the
+    /// wrapped base formula it was rewritten from, by replacement only. The
+    /// recorded rewrites are included as each theorem's `solution`.
+    #[arg(long, conflicts_with_all = ["keep_premises", "proof_tree", "chain", "distractors", "judgment"])]
+    equivalence: bool,
+
+    /// Satisfiability tasks: sets of random formulas to satisfy with an
+    /// assignment, or to refute when no assignment satisfies them all
+    #[arg(long, conflicts_with_all = [
+        "tier", "difficulty_distribution", "keep_premises", "proof_tree", "chain", "distractors", "judgment", "equivalence",
+    ])]
+    sat: bool,
+
+    /// Atoms a satisfiability set is drawn over (2-5)
+    #[arg(long, default_value_t = 3, requires = "sat")]
+    sat_atoms: usize,
+
+    /// Formulas per satisfiability set (2-12)
+    #[arg(long, default_value_t = 4, requires = "sat")]
+    sat_formulas: usize,
+
+    /// Maximum depth of each formula in a satisfiability set (1-6)
+    #[arg(long, default_value_t = 3, requires = "sat")]
+    sat_depth: usize,
+
+    /// Share of satisfiability sets that are unsatisfiable (0-1)
+    #[arg(long, default_value_t = 0.5, requires = "sat")]
+    unsat_fraction: f64,
+
+    /// Truth-table tasks: the answer is the full truth table of each
+    /// theorem's atoms, premises and conclusion, included as `table`
+    #[arg(long, conflicts_with_all = ["equivalence", "sat"])]
+    truth_table: bool,
+
+    /// Add an English word-problem version of every theorem as `natural`
+    #[arg(long)]
+    natural_language: bool,
+
+    /// JSON object mapping atoms to the sentences they stand for (default:
+    /// built-in sentences for the letters A-Z)
+    #[arg(long, requires = "natural_language")]
+    lexicon: Option<PathBuf>,
+
+    #[command(flatten)]
+    quality: QualityArgs,
+
+    #[command(flatten)]
+    budget: BudgetArgs,
+
+    /// Output file path
+    #[arg(short, long, default_value = "theorems.json")]
+    output: PathBuf,
+
+    /// Output format: json (one array, written at the end) or jsonl (one
+    /// theorem per line, written as generated). Default: jsonl for a .jsonl
+    /// output path, json otherwise.
+    #[arg(long)]
+    format: Option<String>,
+
+    /// Continue a partial JSONL file: keep its complete theorems and generate
+    /// the rest with the seed recorded in it
+    #[arg(long)]
+    resume: bool,
+
+    /// Also write a JSON summary (counts, semantic classes, per-tier rejections)
+    #[arg(long)]
+    summary: Option<PathBuf>,
+
+    /// Worker threads. The output for a given --seed does not depend on it.
+    #[arg(short, long, default_value_t = 1)]
+    jobs: usize,
+
+    /// Master seed; each theorem's RNG is derived from it and the theorem's
+    /// position in the set (default: random, printed to stderr)
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+/// Acceptance criteria for spec-generated theorems (tier, custom spec and
+/// distribution modes). Rejected theorems are regenerated.
+#[derive(Args)]
+struct QualityArgs {
+    /// Reject theorems with fewer nodes (premises and conclusion together)
+    #[arg(long)]
+    gate_min_nodes: Option<usize>,
+
+    /// Reject theorems with more nodes (premises and conclusion together)
+    #[arg(long)]
+    gate_max_nodes: Option<usize>,
+
+    /// Minimum distinct atoms a theorem must use (default: 2)
+    #[arg(long)]
+    gate_min_atoms: Option<usize>,
+
+    /// Minimum equivalence rewrites applied across all passes (default: 1)
+    #[arg(long)]
+    gate_min_transforms: Option<usize>,
+
+    /// Accept trivially shaped theorems such as (P . Q) > P
+    #[arg(long)]
+    allow_trivial: bool,
+
+    /// Attempts per theorem before generation fails
+    #[arg(long, default_value_t = DEFAULT_MAX_RETRIES)]
+    max_retries: usize,
+}
+
+impl QualityArgs {
+    fn gates(&self) -> Result<QualityGates, String> {
+        if self.max_retries == 0 {
+            return Err("--max-retries must be at least 1".to_string());
+        }
+        let defaults = QualityGates::default();
+        Ok(QualityGates {
+            min_nodes: self.gate_min_nodes,
+            max_nodes: self.gate_max_nodes,
+            min_atoms: self.gate_min_atoms.or(defaults.min_atoms),
+            min_transforms: self.gate_min_transforms.or(defaults.min_transforms),
+            reject_trivial_shape: !self.allow_trivial,
+            max_retries: self.max_retries,
+        })
+    }
+}
+
+/// Per-theorem limits for spec-generated theorems (tier, custom spec and
+/// distribution modes), covering all quality-gate attempts.
+#[derive(Args)]
+struct BudgetArgs {
+    /// Wall-clock budget per theorem in milliseconds
+    #[arg(long)]
+    budget_ms: Option<u64>,
+
+    /// Memory budget per theorem in KiB, estimated from the size of the
+    /// formulas being built
+    #[arg(long)]
+    budget_kb: Option<u64>,
+
+    /// What to do with a theorem that runs over budget: retry (with a
+    /// smaller spec), skip (leave it out) or fail (default)
+    #[arg(long)]
+    on_budget: Option<String>,
+}
+
+impl BudgetArgs {
+    fn budget(&self) -> Result<GenerationBudget, String> {
+        let policy = match self.on_budget.as_deref() {
+            None => BudgetPolicy::default(),
+            Some(name) => name.parse()?,
+        };
+        let budget = GenerationBudget { max_millis: self.budget_ms, max_kilobytes: self.budget_kb, policy };
+        if budget.is_unlimited() && self.on_budget.is_some() {
+            return Err("--on-budget needs --budget-ms or --budget-kb".to_string());
+        }
+        if self.budget_ms == Some(0) || self.budget_kb == Some(0) {
+            return Err("--budget-ms and --budget-kb must be at least 1".to_string());
+        }
+        Ok(budget)
+    }
+}
+
+/// Spec flags shared by every command that generates theorems.
+#[derive(Args)]
+struct SpecArgs {
+    /// Number of variables (2-20) for custom spec
+    #[arg(long)]
+    variables: Option<u8>,
+
+    /// Number of passes (1-20) for custom spec
+    #[arg(long)]
+    passes: Option<u16>,
+
+    /// Transforms per pass (1-24) for custom spec
+    #[arg(long)]
+    transforms: Option<u16>,
+
+    /// Base complexity (simple/complex) for custom spec
+    #[arg(long)]
+    base: Option<String>,
+
+    /// Substitution depth (0-4) for custom spec
+    #[arg(long)]
+    substitution: Option<u16>,
+
+    /// Number of bridge atoms (0-5) for cross-zone interdependencies
+    #[arg(long)]
+    bridge_atoms: Option<u8>,
+
+    /// Maximum formula nodes (default: 20000) for custom spec
+    #[arg(long)]
+    max_nodes: Option<u32>,
+
+    /// Maximum formula depth (default: 100) for custom spec
+    #[arg(long)]
+    max_depth: Option<u32>,
+
+    /// Target band for total formula nodes, e.g. 40-80
+    #[arg(long, value_parser = parse_size_band, conflicts_with = "target_length")]
+    target_nodes: Option<(u32, u32)>,
+
+    /// Target band for the ASCII length of premises plus conclusion, e.g. 200-400
+    #[arg(long, value_parser = parse_size_band)]
+    target_length: Option<(u32, u32)>,
+
+    /// Rewrite weights per equivalence rule, e.g. "dist=3,taut=0" (0 excludes a rule)
+    #[arg(long, value_parser = parse_rule_weights)]
+    rule_weights: Option<RuleWeights>,
+
+    /// Custom gnarly combo chains, e.g. "contra+dem,impl+dist" (turns gnarly combos on)
+    #[arg(long, value_parser = parse_combo_chains)]
+    combo_chains: Option<ComboChains>,
+
+    /// Disable gnarly combos (forced multi-rule transformation chains)
+    #[arg(long)]
+    no_gnarly_combos: bool,
+
+    /// Enable gnarly combos (forced multi-rule transformation chains)
+    #[arg(long, conflicts_with = "no_gnarly_combos")]
+    gnarly_combos: bool,
+
+    /// Tier presets file (.json or .toml) layered over the built-in tier table,
+    /// e.g. tier-presets.json. May define new named tiers.
+    #[arg(long)]
+    presets: Option<PathBuf>,
+}
+
+impl SpecArgs {
+    /// Resolve --gnarly-combos / --no-gnarly-combos into an optional override.
+    fn gnarly_override(&self) -> Option<bool> {
+        if self.gnarly_combos {
+            Some(true)
+        } else if self.no_gnarly_combos {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Load the tier presets: the built-in table, overridden by --presets if given.
+    fn tier_presets(&self) -> Result<TierPresets, String> {
+        match &self.presets {
+            Some(path) => TierPresets::load(path).map_err(|e| e.to_string()),
+            None => Ok(TierPresets::builtin()),
+        }
+    }
+
+    /// Resolve --target-nodes / --target-length into a size band.
+    fn target_size(&self) -> Option<TargetSize> {
+        let (metric, (min, max)) = match (self.target_nodes, self.target_length) {
+            (Some(band), _) => (SizeMetric::Nodes, band),
+            (None, Some(band)) => (SizeMetric::AsciiLength, band),
+            (None, None) => return None,
+        };
+        Some(TargetSize { metric, min, max })
+    }
+
+    /// Apply the orthogonal overrides (max nodes/depth, bridge atoms, gnarly,
+    /// target size, rule weights, combo chains) on top of a tier preset.
+    fn apply_overrides(&self, spec: &mut DifficultySpec) {
+        if let Some(nodes) = self.max_nodes {
+            spec.max_formula_nodes = Some(nodes);
+        }
+        if let Some(depth) = self.max_depth {
+            spec.max_formula_depth = Some(depth);
+        }
+        if let Some(ba) = self.bridge_atoms {
+            spec.bridge_atoms = Some(ba);
+        }
+        if let Some(gnarly) = self.gnarly_override() {
+            spec.gnarly_combos = Some(gnarly);
+        }
+        if let Some(target) = self.target_size() {
+            spec.target_size = Some(target);
+        }
+        if let Some(RuleWeights(weights)) = &self.rule_weights {
+            spec.rule_weights = Some(weights.clone());
+        }
+        if let Some(ComboChains(chains)) = &self.combo_chains {
+            spec.combo_chains = Some(chains.clone());
+            if self.gnarly_override().is_none() {
+                spec.gnarly_combos = Some(true);
+            }
+        }
+    }
+
+    /// Build a custom spec if any custom spec flag was given
+    /// (max_nodes/max_depth/target size/rule weights/combo chains alone don't
+    /// count, they are orthogonal).
+    fn custom_spec(&self) -> Option<DifficultySpec> {
+        if self.variables.is_none() && self.passes.is_none() && self.transforms.is_none()
+            && self.base.is_none() && self.substitution.is_none()
+        {
+            return None;
+        }
+        Some(DifficultySpec {
+            variables: self.variables.unwrap_or(3),
+            passes: self.passes.unwrap_or(1),
+            transforms_per_pass: self.transforms.unwrap_or(5),
+            base_complexity: match self.base.as_deref() {
+                Some("complex") => BaseComplexity::Complex,
+                _ => BaseComplexity::Simple,
+            },
+            substitution_depth: self.substitution.unwrap_or(0),
+            bridge_atoms: self.bridge_atoms,
+            max_formula_nodes: self.max_nodes,
+            max_formula_depth: self.max_depth,
+            gnarly_combos: self.gnarly_override(),
+            target_size: self.target_size(),
+            rule_weights: self.rule_weights.clone().map(|RuleWeights(w)| w),
+            combo_chains: self.combo_chains.clone().map(|ComboChains(c)| c),
+        })
+    }
+}
+
+#[derive(Clone)]
+struct RuleWeights(BTreeMap<EquivalenceRule, f64>);
+
+#[derive(Clone)]
+struct ComboChains(Vec<Vec<EquivalenceRule>>);
+
+fn parse_rule(name: &str) -> Result<EquivalenceRule, String> {
+    EquivalenceRule::from_name(name).ok_or_else(|| format!(
+        "Unknown equivalence rule '{}'. Use one of: {}",
+        name.trim(),
+        EquivalenceRule::all().iter().map(|r| r.abbreviation()).collect::<Vec<_>>().join(", ")
+    ))
+}
+
+/// Parse `rule=weight` pairs such as `dist=3,taut=0`.
+fn parse_rule_weights(s: &str) -> Result<RuleWeights, String> {
+    let mut weights = BTreeMap::new();
+    for entry in s.split(',') {
+        let (rule, weight) = entry.split_once('=')
+            .ok_or_else(|| format!("Invalid rule weight '{}': expected RULE=WEIGHT", entry.trim()))?;
+        let weight: f64 = weight.trim().parse()
+            .map_err(|_| format!("Invalid weight '{}' for {}", weight.trim(), rule.trim()))?;
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(format!("Weight for {} must be a non-negative number", rule.trim()));
+        }
+        weights.insert(parse_rule(rule)?, weight);
+    }
+    if EquivalenceRule::all().iter().all(|rule| weights.get(rule) == Some(&0.0)) {
+        return Err("Rule weights exclude every rule".to_string());
+    }
+    Ok(RuleWeights(weights))
+}
+
+/// Parse comma-separated chains of `+`-joined rules such as `contra+dem,impl+dist`.
+fn parse_combo_chains(s: &str) -> Result<ComboChains, String> {
+    s.split(',')
+        .map(|chain| chain.split('+').map(parse_rule).collect::<Result<Vec<_>, _>>())
+        .collect::<Result<Vec<_>, _>>()
+        .map(ComboChains)
+}
+
+/// Parse a `MIN-MAX` size band such as `40-80`.
+fn parse_size_band(s: &str) -> Result<(u32, u32), String> {
+    let (min, max) = s.split_once('-')
+        .ok_or_else(|| format!("Invalid size band '{}': expected MIN-MAX", s))?;
+    let min: u32 = min.trim().parse().map_err(|_| format!("Invalid size band minimum '{}'", min))?;
+    let max: u32 = max.trim().parse().map_err(|_| format!("Invalid size band maximum '{}'", max))?;
+    if min == 0 || min > max {
+        return Err(format!("Invalid size band '{}': need 1 <= MIN <= MAX", s));
+    }
+    Ok((min, max))
+}
+
 // ─── Output types ───────────────────────────────────────────────────────────
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -105,6 +630,64 @@ struct BenchTheorem {
     difficulty_value: u8,
     #[serde(skip_serializing_if = "Option::is_none")]
     difficulty_spec: Option<DifficultySpec>,
+    /// Semantic class id of the theorem (see `services::semantic_class`)
+    #[serde(default)]
+    semantic_class: String,
+    /// Semantic class id of the base argument the theorem was obfuscated from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base_class: Option<String>,
+    /// Proof the theorem was built from (proof-tree generation only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    solution: Option<ProofTree>,
+    /// Master seed of the generate run; with the id it reproduces the theorem
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    /// Indices of the premises the conclusion needs; the others are
+    /// distractors (generate --distractors only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    essential_premises: Option<Vec<usize>>,
+    /// Whether the conclusion follows (generate --judgment only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    valid: Option<bool>,
+    /// How an invalid theorem was made from a valid one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    perturbation: Option<Perturbation>,
+    /// Assignment under which an invalid theorem's premises hold and its
+    /// conclusion fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    countermodel: Option<BTreeMap<String, bool>>,
+    /// Task other than proving the conclusion with any rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    task: Option<Task>,
+    /// Whether some assignment makes every premise true (generate --sat only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    satisfiable: Option<bool>,
+    /// Assignment making every premise true, if there is one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    model: Option<BTreeMap<String, bool>>,
+    /// Expected truth table (generate --truth-table only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    table: Option<String>,
+    /// The theorem in English (generate --natural-language only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    natural: Option<NaturalTheorem>,
+    /// File and line the theorem was imported from (import only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+}
+
+/// Task kinds with their own answer rules
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Task {
+    /// Rewrite the only premise into the conclusion with equivalence rules
+    /// (generate --equivalence)
+    Equivalence,
+    /// Satisfy the premises with an assignment, or derive the conclusion ⊥
+    /// from them (generate --sat)
+    Satisfiability,
+    /// Write out the truth table of the argument (generate --truth-table)
+    TruthTable,
 }
 
 impl From<&Theorem> for BenchTheorem {
@@ -116,10 +699,132 @@ impl From<&Theorem> for BenchTheorem {
             difficulty: difficulty_label(t.difficulty_value),
             difficulty_value: t.difficulty_value,
             difficulty_spec: None,
+            semantic_class: theorem_class(t),
+            base_class: t.base_class.clone(),
+            solution: None,
+            seed: None,
+            essential_premises: None,
+            valid: None,
+            perturbation: None,
+            countermodel: None,
+            task: None,
+            satisfiable: None,
+            model: None,
+            table: None,
+            natural: None,
+            source: None,
         }
     }
 }
 
+impl BenchTheorem {
+    /// Parse the ASCII premises and conclusion back into formulas.
+    fn parse_formulas(&self) -> Result<(Vec<Formula>, Formula), String> {
+        let premises = self.premises.iter()
+            .map(|p| Formula::parse(p).map_err(|e| format!("Invalid premise '{}': {}", p, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let conclusion = Formula::parse(&self.conclusion)
+            .map_err(|e| format!("Invalid conclusion '{}': {}", self.conclusion, e))?;
+        Ok((premises, conclusion))
+    }
+
+    /// The theorem as the trainer's `Theorem`, for proofs of it.
+    fn to_theorem(&self) -> Result<Theorem, String> {
+        let (premises, conclusion) = self.parse_formulas()?;
+        Ok(Theorem::with_difficulty_value(
+            premises,
+            conclusion,
+            legacy_difficulty(self.difficulty_value),
+            self.difficulty_value,
+            None,
+            None,
+        ))
+    }
+
+    /// Replace the premises with `theorem`'s plus `count` distractors. Classes
+    /// are left as computed for the argument without them.
+    fn add_distractors(&mut self, theorem: &Theorem, count: usize, rng: &mut impl Rng) -> Result<(), String> {
+        if count == 0 {
+            return Ok(());
+        }
+        let mixed = add_distractors(&theorem.premises, &theorem.conclusion, count, rng)
+            .map_err(|e| match e {
+                DistractorError::NotEnough { .. } => format!("{}: {}. Lower --distractors.", self.difficulty, e),
+                DistractorError::TooManyAtoms { .. } => format!("{}: {}", self.difficulty, e),
+            })?;
+        self.premises = mixed.premises.iter().map(|f| f.ascii_string_bracketed()).collect();
+        self.essential_premises = Some(mixed.essential);
+        Ok(())
+    }
+
+    /// With --judgment, label the theorem valid or, with probability
+    /// --invalid-fraction, replace it by a near miss of `theorem`. A near
+    /// miss has no solution and no base class.
+    fn judge(&mut self, theorem: &Theorem, args: &GenerateArgs, rng: &mut impl Rng) -> Result<(), String> {
+        if !args.judgment {
+            return Ok(());
+        }
+        if !rng.gen_bool(args.invalid_fraction) {
+            self.valid = Some(true);
+            return Ok(());
+        }
+        let miss = near_miss(&theorem.premises, &theorem.conclusion, rng)
+            .map_err(|e| format!("{}: {}", self.difficulty, e))?;
+        let invalid = Theorem { premises: miss.premises, conclusion: miss.conclusion, base_class: None, ..theorem.clone() };
+        self.premises = invalid.premises.iter().map(|f| f.ascii_string_bracketed()).collect();
+        self.conclusion = invalid.conclusion.ascii_string_bracketed();
+        self.semantic_class = theorem_class(&invalid);
+        self.base_class = None;
+        self.solution = None;
+        self.valid = Some(false);
+        self.perturbation = Some(miss.perturbation);
+        self.countermodel = Some(miss.countermodel);
+        Ok(())
+    }
+
+    /// With --truth-table, make the theorem a truth-table task with its
+    /// expected table.
+    fn tabulate(&mut self, args: &GenerateArgs) -> Result<(), String> {
+        if !args.truth_table {
+            return Ok(());
+        }
+        let (premises, conclusion) = self.parse_formulas()?;
+        let columns = TableColumns::new(&premises, &conclusion)
+            .map_err(|e| format!("{}: {}. Use a tier with fewer atoms.", self.difficulty, e))?;
+        self.table = Some(columns.render());
+        self.task = Some(Task::TruthTable);
+        Ok(())
+    }
+
+    /// Add the word-problem version of the theorem.
+    fn verbalize(&mut self, lexicon: &Lexicon) -> Result<(), String> {
+        let (premises, conclusion) = self.parse_formulas()?;
+        let natural = verbalize_argument(&premises, &conclusion, lexicon)
+            .map_err(|e| format!("{}: {}. Pass a --lexicon that covers it.", self.id, e))?;
+        self.natural = Some(natural);
+        Ok(())
+    }
+
+    /// Whether the conclusion follows, where the theorem says: a judgment
+    /// verdict, an unsatisfiable premise set, or any other generated theorem
+    /// (valid by construction).
+    fn expected_valid(&self) -> bool {
+        self.valid.or(self.satisfiable.map(|sat| !sat)).unwrap_or(true)
+    }
+
+    /// Premise lines (1-based, as in a proof) that are distractors.
+    fn distractor_lines(&self) -> Option<Vec<usize>> {
+        let essential = self.essential_premises.as_ref()?;
+        Some((0..self.premises.len()).filter(|i| !essential.contains(i)).map(|i| i + 1).collect())
+    }
+
+    /// Class id that identifies the underlying argument: the base form when the
+    /// theorem was obfuscated from one, otherwise the theorem's own class.
+    fn argument_class(&self) -> &str {
+        self.base_class.as_deref().unwrap_or(&self.semantic_class)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct ValidateInput {
     line_number: usize,
@@ -133,10 +838,24 @@ struct ValidateOutput {
     valid: bool,
     line_count: usize,
     errors: Vec<String>,
+    /// Distractor premise lines the proof cites (theorems with
+    /// `essential_premises` only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    distractors_cited: Option<Vec<usize>>,
 }
 
 // ─── Difficulty helpers ─────────────────────────────────────────────────────
 
+/// Map a 1-100 difficulty value to the legacy 4-level Difficulty.
+fn legacy_difficulty(value: u8) -> Difficulty {
+    match value {
+        1..=25 => Difficulty::Easy,
+        26..=45 => Difficulty::Medium,
+        46..=70 => Difficulty::Hard,
+        _ => Difficulty::Expert,
+    }
+}
+
 fn difficulty_label(value: u8) -> String {
     match value {
         1..=25 => "Easy".to_string(),
@@ -148,35 +867,84 @@ fn difficulty_label(value: u8) -> String {
     }
 }
 
-/// Extended tier range that supports all 9 tiers.
-/// For tiers beyond Marathon (absurd/cosmic/mind), maps to difficulty value 100
-/// since spec-based generation handles actual parameters.
-fn tier_range_extended(name: &str) -> Result<(u8, u8), String> {
-    match name {
-        "baby" => Ok((1, 12)),
-        "easy" => Ok((1, 25)),
-        "medium" => Ok((26, 45)),
-        "hard" => Ok((46, 70)),
-        "expert" => Ok((71, 85)),
-        "nightmare" => Ok((86, 95)),
-        "marathon" => Ok((96, 100)),
-        "absurd" | "cosmic" | "mind" => Ok((100, 100)),
-        other => Err(format!(
-            "Unknown difficulty tier: '{}'. Use baby/easy/medium/hard/expert/nightmare/marathon/absurd/cosmic/mind.",
-            other
-        )),
-    }
+fn parse_tier(name: &str, presets: &TierPresets) -> Result<TierPreset, String> {
+    presets.get(name)
+        .cloned()
+        .ok_or_else(|| format!("Unknown tier: '{}'. Use {}.", name, presets.names()))
+}
+
+/// Generate one gated theorem from a preset's spec within `budget`, keeping
+/// tier metadata for built-in tiers. Returns the spec actually used (smaller
+/// after budget retries), or `None` if the theorem was skipped.
+fn generate_budgeted(
+    preset: &TierPreset,
+    keep_premises: bool,
+    gates: &QualityGates,
+    budget: &GenerationBudget,
+    stats: &mut RejectionStats,
+    rng: &mut impl Rng,
+) -> Result<Option<(Theorem, DifficultySpec)>, String> {
+    let spec = &preset.spec;
+    ObfuscateGenerator::generate_budgeted(spec, preset.tier, keep_premises, gates, budget, stats, rng).map_err(|e| {
+        if let Rejection::OverBudget(_) = e.last {
+            return format!(
+                "{}: {}. Raise --budget-ms/--budget-kb{}.",
+                preset.label(), e.last,
+                match budget.policy {
+                    BudgetPolicy::Retry => " (the spec cannot shrink further)",
+                    _ => " or pass --on-budget retry/skip",
+                }
+            );
+        }
+        format!(
+            "{}: {}. Relax the --gate-* limits{}, raise --max-retries{}.",
+            preset.label(), e,
+            if spec.target_size.is_some() { ", move the --target-* band towards the tier's natural size" } else { "" },
+            if keep_premises { " or lower --substitution" } else { "" }
+        )
+    })
+}
+
+/// Acceptance and rejection counts for one tier, as reported by generate.
+#[derive(Serialize)]
+struct TierRejections {
+    tier: String,
+    #[serde(flatten)]
+    stats: RejectionStats,
+}
+
+/// The stats entry for `label`, created on first use.
+fn tier_stats<'a>(all: &'a mut Vec<TierRejections>, label: &str) -> &'a mut RejectionStats {
+    let index = match all.iter().position(|t| t.tier == label) {
+        Some(index) => index,
+        None => {
+            all.push(TierRejections { tier: label.to_string(), stats: RejectionStats::default() });
+            all.len() - 1
+        }
+    };
+    &mut all[index].stats
 }
 
-#[derive(Debug)]
-enum DistributionEntry {
-    /// Legacy mode: generate with random difficulty value in range
-    Range { count: usize, min_val: u8, max_val: u8, tier_name: String },
-    /// Spec mode: generate with a DifficultySpec (tier is known)
-    Spec { count: usize, tier: DifficultyTier, spec: DifficultySpec, tier_name: String },
+#[derive(Serialize)]
+struct GenerateSummary {
+    output: String,
+    seed: u64,
+    theorems: usize,
+    distinct_classes: usize,
+    quality_gates: Option<QualityGates>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    budget: Option<GenerationBudget>,
+    /// Theorems labelled invalid (--judgment only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    invalid: Option<usize>,
+    /// Unsatisfiable formula sets (--sat only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unsatisfiable: Option<usize>,
+    tiers: Vec<TierRejections>,
 }
 
-fn parse_difficulty_distribution(spec: &str) -> Result<Vec<DistributionEntry>, String> {
+/// Parse `N:tier,...` into counts of tier presets (built-in or loaded from file).
+fn parse_difficulty_distribution(spec: &str, presets: &TierPresets) -> Result<Vec<(usize, TierPreset)>, String> {
     let mut result = Vec::new();
     for part in spec.split(',') {
         let parts: Vec<&str> = part.trim().split(':').collect();
@@ -186,26 +954,9 @@ fn parse_difficulty_distribution(spec: &str) -> Result<Vec<DistributionEntry>, S
         let count: usize = parts[0].trim().parse()
             .map_err(|_| format!("Invalid count: '{}'", parts[0]))?;
         let tier_name = parts[1].trim().to_lowercase();
-
-        // All known tiers use the spec-based generation path
-        if let Some(dt) = DifficultyTier::from_str(&tier_name) {
-            result.push(DistributionEntry::Spec {
-                count,
-                tier: dt,
-                spec: DifficultySpec::from_tier(dt),
-                tier_name: dt.label().to_string(),
-            });
-            continue;
-        }
-
-        // Fallback for unknown tier names: use legacy range-based path
-        let (min, max) = tier_range_extended(&tier_name)?;
-        result.push(DistributionEntry::Range {
-            count,
-            min_val: min,
-            max_val: max,
-            tier_name,
-        });
+        let preset = presets.get(&tier_name)
+            .ok_or_else(|| format!("Unknown difficulty tier: '{}'. Use {}.", tier_name, presets.names()))?;
+        result.push((count, preset.clone()));
     }
     Ok(result)
 }
@@ -214,132 +965,481 @@ fn parse_difficulty_distribution(spec: &str) -> Result<Vec<DistributionEntry>, S
 
 /// Determine the generation mode from CLI flags.
 enum GenerateMode {
-    /// --tier <name>: all theorems use one tier preset
-    Tier(DifficultyTier, DifficultySpec, String),
+    /// --tier <name>: all theorems use one tier preset (with CLI overrides applied)
+    Tier(TierPreset),
     /// --variables/--passes/... custom spec
     CustomSpec(DifficultySpec),
     /// --difficulty-distribution or default, with optional max_nodes/max_depth overrides
     Distribution(String, Option<u32>, Option<u32>),
+    /// --proof-tree: theorems built backward from a proof by ProofTreeGenerator
+    ProofTree(TreeGenConfig),
+    /// --chain <k>: k-step inference chains built forward by ChainGenerator
+    Chain(ChainConfig),
+    /// --sat: satisfiability tasks
+    Sat(SatConfig),
 }
 
-fn resolve_generate_mode(
-    tier: &Option<String>,
-    variables: &Option<u8>,
-    passes: &Option<u16>,
-    transforms: &Option<u16>,
-    base: &Option<String>,
-    substitution: &Option<u16>,
-    bridge_atoms: &Option<u8>,
-    max_nodes: &Option<u32>,
-    max_depth: &Option<u32>,
-    distribution: &Option<String>,
-    gnarly_override: Option<bool>,
-) -> Result<GenerateMode, String> {
+fn resolve_generate_mode(args: &GenerateArgs, presets: &TierPresets) -> Result<GenerateMode, String> {
+    let spec_args = &args.spec;
+
+    // Mode 0: --proof-tree, --chain or --sat
+    if args.proof_tree {
+        return Ok(GenerateMode::ProofTree(proof_tree_config(args)?));
+    }
+    if let Some(steps) = args.chain {
+        return Ok(GenerateMode::Chain(chain_config(steps, args.chain_rules.as_deref())?));
+    }
+    if args.sat {
+        let config = SatConfig::new(args.sat_atoms, args.sat_formulas, args.sat_depth);
+        config.validate().map_err(|e| format!("Invalid --sat-*: {}", e))?;
+        return Ok(GenerateMode::Sat(config));
+    }
+
     // Mode 1: --tier
-    if let Some(tier_name) = tier {
-        let dt = DifficultyTier::from_str(tier_name)
-            .ok_or_else(|| format!("Unknown tier: '{}'. Use baby/easy/medium/hard/expert/nightmare/marathon/absurd/cosmic/mind.", tier_name))?;
-        let mut spec = DifficultySpec::from_tier(dt);
-        if let Some(nodes) = max_nodes {
-            spec.max_formula_nodes = Some(*nodes);
-        }
-        if let Some(depth) = max_depth {
-            spec.max_formula_depth = Some(*depth);
-        }
-        if let Some(ba) = bridge_atoms {
-            spec.bridge_atoms = Some(*ba);
-        }
-        if let Some(gnarly) = gnarly_override {
-            spec.gnarly_combos = Some(gnarly);
-        }
-        return Ok(GenerateMode::Tier(dt, spec, dt.label().to_string()));
+    if let Some(tier_name) = &args.tier {
+        let mut preset = parse_tier(tier_name, presets)?;
+        spec_args.apply_overrides(&mut preset.spec);
+        return Ok(GenerateMode::Tier(preset));
     }
 
-    // Mode 2: any custom spec flag (except max_nodes/max_depth which are orthogonal)
-    if variables.is_some() || passes.is_some() || transforms.is_some() || base.is_some() || substitution.is_some() {
-        let spec = DifficultySpec {
-            variables: variables.unwrap_or(3),
-            passes: passes.unwrap_or(1),
-            transforms_per_pass: transforms.unwrap_or(5),
-            base_complexity: match base.as_deref() {
-                Some("complex") => BaseComplexity::Complex,
-                _ => BaseComplexity::Simple,
-            },
-            substitution_depth: substitution.unwrap_or(0),
-            bridge_atoms: *bridge_atoms,
-            max_formula_nodes: *max_nodes,
-            max_formula_depth: *max_depth,
-            gnarly_combos: gnarly_override,
-        };
+    // Mode 2: any custom spec flag
+    if let Some(spec) = spec_args.custom_spec() {
         return Ok(GenerateMode::CustomSpec(spec));
     }
 
     // Mode 3: --difficulty-distribution or default
-    if gnarly_override.is_some() {
+    if spec_args.gnarly_override().is_some() {
         eprintln!("Warning: --gnarly-combos/--no-gnarly-combos is ignored in distribution mode. Each tier uses its own default.");
     }
-    let dist_str = distribution.clone()
+    let dist_str = args.difficulty_distribution.clone()
         .unwrap_or_else(|| "30:easy,30:medium,20:hard,15:expert,5:nightmare".to_string());
-    Ok(GenerateMode::Distribution(dist_str, *max_nodes, *max_depth))
+    Ok(GenerateMode::Distribution(dist_str, spec_args.max_nodes, spec_args.max_depth))
 }
 
-fn cmd_generate(
-    count: usize,
-    distribution: &Option<String>,
-    tier: &Option<String>,
-    variables: &Option<u8>,
-    passes: &Option<u16>,
-    transforms: &Option<u16>,
-    base: &Option<String>,
-    substitution: &Option<u16>,
-    bridge_atoms: &Option<u8>,
-    max_nodes: &Option<u32>,
-    max_depth: &Option<u32>,
-    gnarly_override: Option<bool>,
-    output: &PathBuf,
+/// Build the proof-tree generator config from --tree-difficulty, --require and --nesting.
+fn proof_tree_config(args: &GenerateArgs) -> Result<TreeGenConfig, String> {
+    if !(1..=100).contains(&args.tree_difficulty) {
+        return Err(format!("--tree-difficulty must be 1-100, got {}", args.tree_difficulty));
+    }
+    let mut config = TreeGenConfig::for_difficulty_value(args.tree_difficulty);
+
+    if let Some(list) = &args.require {
+        config.require_forces_cp = false;
+        config.require_forces_case_split = false;
+        config.require_forces_ip = false;
+        for name in list.split(',').map(|n| n.trim().to_lowercase()).filter(|n| !n.is_empty()) {
+            match name.as_str() {
+                "cp" => config.require_forces_cp = true,
+                "case-split" | "case_split" => config.require_forces_case_split = true,
+                "ip" => config.require_forces_ip = true,
+                "none" => {}
+                other => return Err(format!("Unknown technique '{}'. Use cp, case-split or ip.", other)),
+            }
+        }
+    }
+    // Below difficulty 30 the pool has two atoms, too few for CP or case
+    // split theorems; use the three that difficulty 30 starts with
+    if (config.require_forces_cp || config.require_forces_case_split) && config.atom_pool.len() < 3 {
+        config.atom_pool = TreeGenConfig::for_difficulty_value(30).atom_pool;
+    }
+    if config.require_forces_cp && config.require_forces_ip {
+        return Err("cp and ip cannot both be forced: CP is forced only for conditional conclusions, IP only for non-conditional ones.".to_string());
+    }
+
+    if let Some(nesting) = args.nesting {
+        config.max_nesting = nesting;
+    }
+    let min_nesting = if config.require_forces_cp && config.require_forces_case_split { 2 } else { 1 };
+    if config.max_nesting < min_nesting {
+        return Err(format!("--nesting must be at least {} for the required techniques", min_nesting));
+    }
+    Ok(config)
+}
+
+/// Build the chain generator config from --chain and --chain-rules.
+fn chain_config(steps: usize, rules: Option<&str>) -> Result<ChainConfig, String> {
+    let mut config = ChainConfig::new(steps);
+    if let Some(list) = rules {
+        config.rules = list.split(',')
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(|name| CHAIN_RULES.iter()
+                .find(|rule| rule.abbreviation().eq_ignore_ascii_case(name))
+                .copied()
+                .ok_or_else(|| format!("Unknown chain rule '{}'. Use mp, mt, ds, hs or cd.", name)))
+            .collect::<Result<Vec<_>, _>>()?;
+    }
+    config.validate().map_err(|e| format!("Invalid --chain/--chain-rules: {}", e))?;
+    Ok(config)
+}
+
+/// Comma-separated techniques a config forces, for progress messages.
+fn required_techniques_label(config: &TreeGenConfig) -> String {
+    let names: Vec<&str> = [
+        (config.require_forces_cp, "cp"),
+        (config.require_forces_case_split, "case-split"),
+        (config.require_forces_ip, "ip"),
+    ].iter().filter(|(on, _)| *on).map(|(_, name)| *name).collect();
+    if names.is_empty() { "none".to_string() } else { names.join(",") }
+}
+
+/// Generator calls per proof-tree theorem before giving up. The generator
+/// falls back to its best attempt, which may not force every technique; a
+/// call that produced no valid tree at all counts as a failed attempt rather
+/// than taking the generator's fixed templates.
+const MAX_PROOF_TREE_ATTEMPTS: usize = 20;
+
+/// Generate one proof tree whose theorem forces the configured techniques
+/// within the configured nesting depth.
+fn generate_proof_tree(config: &TreeGenConfig, rng: &mut impl Rng) -> Result<ProofTree, String> {
+    let mut generator = ProofTreeGenerator::new(config.clone());
+    for _ in 0..MAX_PROOF_TREE_ATTEMPTS {
+        let Some(tree) = generator.try_generate_with_rng(rng) else {
+            continue;
+        };
+        let forced = validate_theorem_with_difficulty(
+            &tree.premises(),
+            tree.conclusion(),
+            None,
+            config.require_forces_cp,
+            config.require_forces_case_split,
+            config.require_forces_ip,
+        );
+        if forced.is_ok() && tree.max_nesting <= config.max_nesting {
+            return Ok(tree);
+        }
+    }
+    Err(format!(
+        "No proof tree forcing {} within nesting {} after {} attempts. Try a larger --nesting or --tree-difficulty.",
+        required_techniques_label(config), config.max_nesting, MAX_PROOF_TREE_ATTEMPTS
+    ))
+}
+
+/// What to generate for one position of the theorem set.
+#[derive(Clone)]
+enum Job {
+    /// Gated spec generation with a tier preset (or the custom spec)
+    Spec(Arc<TierPreset>),
+    /// Proof-first generation with the proof tree as solution
+    ProofTree(Arc<TreeGenConfig>),
+    /// Chain generation with the chain proof as solution
+    Chain(Arc<ChainGenerator>),
+    /// Satisfiability set, unsatisfiable with probability --unsat-fraction
+    Sat(Arc<SatConfig>),
+}
+
+/// Per-theorem seed: the master seed and index mixed with SplitMix64, so
+/// neighbouring indices get unrelated streams.
+fn theorem_seed(master: u64, index: usize) -> u64 {
+    let mut z = master.wrapping_add((index as u64).wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// A generated theorem (`None` if skipped over budget); spec jobs also
+/// return their tier's gate statistics.
+type JobOutput = (Option<BenchTheorem>, Option<TierRejections>);
+
+/// Generate the theorem for one job.
+fn run_job(
+    job: &Job,
+    args: &GenerateArgs,
+    gates: &QualityGates,
+    budget: &GenerationBudget,
+    rng: &mut StdRng,
+) -> Result<JobOutput, String> {
+    match job {
+        Job::Spec(preset) if args.equivalence => {
+            if preset.spec.target_size.is_some() {
+                return Err(format!("{}: --equivalence does not support target sizes", preset.label()));
+            }
+            let (theorem, pair) = ObfuscateGenerator::generate_equivalence(&preset.spec, preset.tier, rng);
+            let mut bench = BenchTheorem::from(&theorem);
+            bench.difficulty = preset.label().to_string();
+            bench.difficulty_spec = Some(preset.spec.clone());
+            bench.solution = Some(pair.solution());
+            bench.task = Some(Task::Equivalence);
+            Ok((Some(bench), None))
+        }
+        Job::Spec(preset) => {
+            let mut stats = RejectionStats::default();
+            let bench = match generate_budgeted(preset, args.keep_premises, gates, budget, &mut stats, rng)? {
+                Some((theorem, spec)) => {
+                    let mut bench = BenchTheorem::from(&theorem);
+                    bench.difficulty = preset.label().to_string();
+                    bench.difficulty_spec = Some(spec);
+                    bench.add_distractors(&theorem, args.distractors, rng)?;
+                    bench.judge(&theorem, args, rng)?;
+                    bench.tabulate(args)?;
+                    Some(bench)
+                }
+                None => None,
+            };
+            Ok((bench, Some(TierRejections { tier: preset.label().to_string(), stats })))
+        }
+        Job::ProofTree(config) => {
+            let tree = generate_proof_tree(config, rng)?;
+            let theorem = Theorem::with_difficulty_value(
+                tree.premises(),
+                tree.conclusion().clone(),
+                legacy_difficulty(args.tree_difficulty),
+                args.tree_difficulty,
+                None,
+                None,
+            );
+            let mut bench = BenchTheorem::from(&theorem);
+            bench.add_distractors(&theorem, args.distractors, rng)?;
+            bench.solution = Some(tree);
+            bench.judge(&theorem, args, rng)?;
+            bench.tabulate(args)?;
+            Ok((Some(bench), None))
+        }
+        Job::Chain(generator) => {
+            let chain = generator.generate(rng);
+            // The step count (1-100) doubles as the difficulty value
+            let steps = chain.proof.fragment_count as u8;
+            let theorem = Theorem::with_difficulty_value(
+                chain.premises,
+                chain.conclusion,
+                legacy_difficulty(steps),
+                steps,
+                None,
+                None,
+            );
+            let mut bench = BenchTheorem::from(&theorem);
+            bench.difficulty = format!("Chain-{}", steps);
+            bench.add_distractors(&theorem, args.distractors, rng)?;
+            bench.solution = Some(chain.proof);
+            bench.judge(&theorem, args, rng)?;
+            bench.tabulate(args)?;
+            Ok((Some(bench), None))
+        }
+        Job::Sat(config) => {
+            let satisfiable = !rng.gen_bool(args.unsat_fraction);
+            let problem = generate_sat_problem(config, satisfiable, rng)
+                .map_err(|e| format!("{}. Raise --sat-atoms or --sat-depth.", e))?;
+            // Sizes the set like the other modes' difficulty values
+            let value = (config.atoms * config.formulas * config.depth).min(100) as u8;
+            let theorem = Theorem::with_difficulty_value(
+                problem.formulas,
+                Formula::Contradiction,
+                legacy_difficulty(value),
+                value,
+                None,
+                None,
+            );
+            let mut bench = BenchTheorem::from(&theorem);
+            bench.difficulty = format!("Sat-{}x{}", config.atoms, config.formulas);
+            bench.task = Some(Task::Satisfiability);
+            bench.satisfiable = Some(problem.model.is_some());
+            bench.model = problem.model;
+            Ok((Some(bench), None))
+        }
+    }
+}
+
+/// Run the jobs from `first` on `--jobs` threads and pass each result to
+/// `emit` in job order. Each job gets its own RNG from `theorem_seed`, so
+/// results do not depend on scheduling (unless a time budget cuts some
+/// short). After a failure no new jobs start; the error of the earliest
+/// failed job is returned.
+fn run_jobs(
+    jobs: &[Job],
+    first: usize,
+    args: &GenerateArgs,
+    gates: &QualityGates,
+    budget: &GenerationBudget,
+    seed: u64,
+    mut emit: impl FnMut(usize, JobOutput) -> Result<(), String>,
 ) -> Result<(), String> {
-    let mode = resolve_generate_mode(tier, variables, passes, transforms, base, substitution, bridge_atoms, max_nodes, max_depth, distribution, gnarly_override)?;
+    let next = AtomicUsize::new(first);
+    let failed = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel::<(usize, Result<JobOutput, String>)>();
+    std::thread::scope(|scope| {
+        for _ in 0..args.jobs.min(jobs.len().saturating_sub(first)).max(1) {
+            let (sender, next, failed) = (sender.clone(), &next, &failed);
+            scope.spawn(move || {
+                while !failed.load(Ordering::Relaxed) {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(job) = jobs.get(index) else { break };
+                    let mut rng = StdRng::seed_from_u64(theorem_seed(seed, index));
+                    let result = run_job(job, args, gates, budget, &mut rng);
+                    if result.is_err() {
+                        failed.store(true, Ordering::Relaxed);
+                    }
+                    if sender.send((index, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        // Jobs finish out of order; hold results until their turn
+        let mut pending = BTreeMap::new();
+        let mut expected = first;
+        for (index, result) in receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&expected) {
+                if let Err(e) = result.and_then(|output| emit(expected, output)) {
+                    failed.store(true, Ordering::Relaxed);
+                    return Err(e);
+                }
+                expected += 1;
+            }
+        }
+        Ok(())
+    })
+}
 
-    let mut rng = rand::thread_rng();
-    let mut theorems: Vec<BenchTheorem> = Vec::with_capacity(count);
-    let mut theorem_id = 1usize;
+/// Theorems already in a partial JSONL file and the byte length of the
+/// complete lines holding them. A trailing line cut off mid-write is dropped;
+/// any other unreadable line is an error.
+fn read_partial_jsonl(path: &PathBuf) -> Result<(Vec<BenchTheorem>, u64), String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), 0)),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let mut theorems = Vec::new();
+    let mut complete = 0;
+    let mut lines = text.split_inclusive('\n').enumerate().peekable();
+    while let Some((number, line)) = lines.next() {
+        // Only the last line can lack its newline, and then it was cut off
+        if !line.ends_with('\n') {
+            break;
+        }
+        if !line.trim().is_empty() {
+            match serde_json::from_str::<BenchTheorem>(line) {
+                Ok(theorem) => theorems.push(theorem),
+                Err(_) if lines.peek().is_none() => break,
+                Err(e) => return Err(format!("{} line {}: {}", path.display(), number + 1, e)),
+            }
+        }
+        complete += line.len() as u64;
+    }
+    Ok((theorems, complete))
+}
 
-    match mode {
-        GenerateMode::Tier(dt, spec, tier_name) => {
-            eprintln!("Generating {} {} theorems via tier spec...", count, tier_name);
-            for _ in 0..count {
-                let theorem = ObfuscateGenerator::generate_with_tier_spec(dt, &spec, &mut rng);
-                let mut bench = BenchTheorem::from(&theorem);
-                bench.id = format!("v1-{:03}", theorem_id);
-                bench.difficulty = tier_name.clone();
-                bench.difficulty_spec = Some(spec.clone());
-                theorems.push(bench);
-                theorem_id += 1;
+/// Check that `existing` is the start of the set `jobs` describes: ids in
+/// increasing order (theorems skipped over budget leave gaps), one master
+/// seed, and the tier each spec job would produce. Returns the seed recorded
+/// in the file, if any, and the index of the first job still to run.
+fn check_resumable(existing: &[BenchTheorem], jobs: &[Job], seed: Option<u64>) -> Result<(Option<u64>, usize), String> {
+    let mut recorded = seed;
+    let mut next = 0;
+    for theorem in existing {
+        let index = theorem.id.strip_prefix("v1-")
+            .and_then(|n| n.parse::<usize>().ok())
+            .filter(|&n| n > next && n <= jobs.len())
+            .ok_or_else(|| format!(
+                "Cannot resume: {} does not follow v1-{:03} within --count {}",
+                theorem.id, next, jobs.len()
+            ))? - 1;
+        next = index + 1;
+        match (theorem.seed, recorded) {
+            (None, _) => return Err(format!("Cannot resume: {} has no seed", theorem.id)),
+            (Some(found), Some(wanted)) if found != wanted => {
+                return Err(format!("Cannot resume: {} was generated with seed {}, not {}", theorem.id, found, wanted));
+            }
+            (Some(found), _) => recorded = Some(found),
+        }
+        if let Job::Spec(preset) = &jobs[index] {
+            if theorem.difficulty != preset.label() {
+                return Err(format!(
+                    "Cannot resume: {} is {}, but these options put {} there",
+                    theorem.id, theorem.difficulty, preset.label()
+                ));
             }
         }
+    }
+    Ok((recorded, next))
+}
+
+/// The lexicon at `path`, or the built-in English one.
+fn load_lexicon(path: Option<&PathBuf>) -> Result<Lexicon, String> {
+    let Some(path) = path else {
+        return Ok(Lexicon::english());
+    };
+    let json = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read lexicon file: {}", e))?;
+    let sentences: BTreeMap<String, String> = serde_json::from_str(&json)
+        .map_err(|e| format!("Failed to parse lexicon JSON: {}", e))?;
+    Lexicon::new(sentences).map_err(|e| format!("Invalid lexicon: {}", e))
+}
+
+fn cmd_generate(args: &GenerateArgs) -> Result<(), String> {
+    let count = args.count;
+    let output = &args.output;
+    let presets = args.spec.tier_presets()?;
+    let mode = resolve_generate_mode(args, &presets)?;
+
+    let gates = args.quality.gates()?;
+    let budget = args.budget.budget()?;
+    if args.jobs == 0 {
+        return Err("--jobs must be at least 1".to_string());
+    }
+    if !(0.0..=1.0).contains(&args.invalid_fraction) {
+        return Err(format!("--invalid-fraction must be 0-1, got {}", args.invalid_fraction));
+    }
+    if !(0.0..=1.0).contains(&args.unsat_fraction) {
+        return Err(format!("--unsat-fraction must be 0-1, got {}", args.unsat_fraction));
+    }
+    let lexicon = if args.natural_language { Some(load_lexicon(args.lexicon.as_ref())?) } else { None };
+    let jsonl = match args.format.as_deref() {
+        Some("json") => false,
+        Some("jsonl") => true,
+        Some(other) => return Err(format!("Unknown format: '{}'. Use json/jsonl.", other)),
+        None => output.extension().is_some_and(|e| e == "jsonl"),
+    };
+    if args.resume && !jsonl {
+        return Err("--resume needs JSONL output (--format jsonl or a .jsonl path)".to_string());
+    }
+
+    // One job per theorem, in output order
+    let mut jobs: Vec<Job> = Vec::with_capacity(count);
+
+    match mode {
+        GenerateMode::Tier(preset) => {
+            eprintln!("Generating {} {} theorems via tier spec...", count, preset.label());
+            jobs.extend(std::iter::repeat_n(Job::Spec(Arc::new(preset)), count));
+        }
 
         GenerateMode::CustomSpec(spec) => {
             eprintln!(
                 "Generating {} theorems with custom spec (vars={}, passes={}, transforms={}, base={:?}, sub={})...",
                 count, spec.variables, spec.passes, spec.transforms_per_pass, spec.base_complexity, spec.substitution_depth
             );
-            for _ in 0..count {
-                let theorem = ObfuscateGenerator::generate_with_spec(&spec, &mut rng);
-                let mut bench = BenchTheorem::from(&theorem);
-                bench.id = format!("v1-{:03}", theorem_id);
-                bench.difficulty = "Custom".to_string();
-                bench.difficulty_spec = Some(spec.clone());
-                theorems.push(bench);
-                theorem_id += 1;
-            }
+            let preset = TierPreset { name: "Custom".to_string(), tier: None, spec };
+            jobs.extend(std::iter::repeat_n(Job::Spec(Arc::new(preset)), count));
+        }
+
+        GenerateMode::ProofTree(config) => {
+            eprintln!(
+                "Generating {} proof-tree theorems (difficulty {}, forcing {}, nesting <= {})...",
+                count, args.tree_difficulty, required_techniques_label(&config), config.max_nesting
+            );
+            jobs.extend(std::iter::repeat_n(Job::ProofTree(Arc::new(config)), count));
+        }
+
+        GenerateMode::Chain(config) => {
+            let rules: Vec<&str> = config.rules.iter().map(|r| r.abbreviation()).collect();
+            eprintln!("Generating {} {}-step chain theorems ({})...", count, config.steps, rules.join(","));
+            jobs.extend(std::iter::repeat_n(Job::Chain(Arc::new(ChainGenerator::new(config))), count));
+        }
+
+        GenerateMode::Sat(config) => {
+            eprintln!(
+                "Generating {} satisfiability sets ({} formulas of depth <= {} over {} atoms)...",
+                count, config.formulas, config.depth, config.atoms
+            );
+            jobs.extend(std::iter::repeat_n(Job::Sat(Arc::new(config)), count));
         }
 
         GenerateMode::Distribution(dist_str, max_nodes_override, max_depth_override) => {
-            let entries = parse_difficulty_distribution(&dist_str)?;
-            let total: usize = entries.iter().map(|e| match e {
-                DistributionEntry::Range { count, .. } => *count,
-                DistributionEntry::Spec { count, .. } => *count,
-            }).sum();
+            let entries = parse_difficulty_distribution(&dist_str, &presets)?;
+            let total: usize = entries.iter().map(|(tier_count, _)| tier_count).sum();
             if total != count {
                 return Err(format!(
                     "Distribution sums to {} but --count is {}. They must match.",
@@ -347,43 +1447,18 @@ fn cmd_generate(
                 ));
             }
 
-            for entry in &entries {
-                match entry {
-                    DistributionEntry::Range { count: tier_count, min_val, max_val, tier_name } => {
-                        eprintln!("Generating {} {} theorems (difficulty {}-{})...", tier_count, tier_name, min_val, max_val);
-                        for _ in 0..*tier_count {
-                            let difficulty_value = rng.gen_range(*min_val..=*max_val);
-                            let generator = TheoremGenerator::with_difficulty_value(difficulty_value);
-                            let theorem = generator.generate_with_value(difficulty_value);
-                            let mut bench = BenchTheorem::from(&theorem);
-                            bench.id = format!("v1-{:03}", theorem_id);
-                            theorems.push(bench);
-                            theorem_id += 1;
-                        }
-                    }
-                    DistributionEntry::Spec { count: tier_count, tier, spec, tier_name } => {
-                        // Apply max_nodes/max_depth overrides if provided.
-                        // gnarly_combos is NOT overridden — each tier's spec from
-                        // DifficultySpec::from_tier() already has the correct per-tier default.
-                        let mut spec = spec.clone();
-                        if let Some(nodes) = max_nodes_override {
-                            spec.max_formula_nodes = Some(nodes);
-                        }
-                        if let Some(depth) = max_depth_override {
-                            spec.max_formula_depth = Some(depth);
-                        }
-                        eprintln!("Generating {} {} theorems via spec...", tier_count, tier_name);
-                        for _ in 0..*tier_count {
-                            let theorem = ObfuscateGenerator::generate_with_tier_spec(*tier, &spec, &mut rng);
-                            let mut bench = BenchTheorem::from(&theorem);
-                            bench.id = format!("v1-{:03}", theorem_id);
-                            bench.difficulty = tier_name.clone();
-                            bench.difficulty_spec = Some(spec.clone());
-                            theorems.push(bench);
-                            theorem_id += 1;
-                        }
-                    }
+            for (tier_count, mut preset) in entries {
+                // Apply max_nodes/max_depth overrides if provided.
+                // gnarly_combos is NOT overridden — each tier preset
+                // already has the correct per-tier default.
+                if let Some(nodes) = max_nodes_override {
+                    preset.spec.max_formula_nodes = Some(nodes);
+                }
+                if let Some(depth) = max_depth_override {
+                    preset.spec.max_formula_depth = Some(depth);
                 }
+                eprintln!("Generating {} {} theorems via spec...", tier_count, preset.label());
+                jobs.extend(std::iter::repeat_n(Job::Spec(Arc::new(preset)), tier_count));
             }
         }
     }
@@ -396,59 +1471,147 @@ fn cmd_generate(
         }
     }
 
-    let json = serde_json::to_string_pretty(&theorems)
-        .map_err(|e| format!("JSON serialization error: {}", e))?;
-    fs::write(output, &json)
-        .map_err(|e| format!("Failed to write output file: {}", e))?;
+    let (mut theorems, complete_bytes) = if args.resume {
+        read_partial_jsonl(output)?
+    } else {
+        (Vec::new(), 0)
+    };
+    let (recorded, first) = check_resumable(&theorems, &jobs, args.seed)?;
+    let seed = recorded.unwrap_or_else(rand::random);
+    if args.resume {
+        eprintln!("Resuming {} after {} complete theorems", output.display(), theorems.len());
+    }
+    eprintln!("Seed {}, {} worker thread(s)", seed, args.jobs.min(jobs.len()).max(1));
+
+    // JSONL streams each theorem to disk as soon as it is its turn
+    let mut stream = if jsonl {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(args.resume)
+            .truncate(!args.resume)
+            .open(output)
+            .map_err(|e| format!("Failed to open output file: {}", e))?;
+        // Drop a line cut off by an earlier crash
+        file.set_len(complete_bytes)
+            .map_err(|e| format!("Failed to truncate output file: {}", e))?;
+        Some(file)
+    } else {
+        None
+    };
+
+    // Proof-tree generation can land on the same theorem twice; later copies
+    // are dropped and leave a gap in the ids, like theorems over budget
+    let dedupe = matches!(jobs.first(), Some(Job::ProofTree(_)));
+    let mut emitted: HashSet<(Vec<String>, String)> = theorems.iter()
+        .map(|t| (t.premises.clone(), t.conclusion.clone()))
+        .collect();
+    let mut duplicates = 0;
+
+    let mut rejections: Vec<TierRejections> = Vec::new();
+    run_jobs(&jobs, first, args, &gates, &budget, seed, |index, (bench, stats)| {
+        if let Some(tier) = stats {
+            tier_stats(&mut rejections, &tier.tier).merge(&tier.stats);
+        }
+        let Some(mut bench) = bench else {
+            return Ok(());
+        };
+        if dedupe && !emitted.insert((bench.premises.clone(), bench.conclusion.clone())) {
+            duplicates += 1;
+            return Ok(());
+        }
+        bench.id = format!("v1-{:03}", index + 1);
+        bench.seed = Some(seed);
+        if let Some(lexicon) = &lexicon {
+            bench.verbalize(lexicon)?;
+        }
+        if let Some(file) = stream.as_mut() {
+            let line = serde_json::to_string(&bench)
+                .map_err(|e| format!("JSON serialization error: {}", e))?;
+            writeln!(file, "{}", line)
+                .map_err(|e| format!("Failed to write output file: {}", e))?;
+        }
+        theorems.push(bench);
+        Ok(())
+    })?;
+
+    if !jsonl {
+        let json = serde_json::to_string_pretty(&theorems)
+            .map_err(|e| format!("JSON serialization error: {}", e))?;
+        fs::write(output, &json)
+            .map_err(|e| format!("Failed to write output file: {}", e))?;
+    }
 
     eprintln!("Wrote {} theorems to {}", theorems.len(), output.display());
+    if duplicates > 0 {
+        eprintln!("Dropped {} duplicate proof-tree theorems", duplicates);
+    }
+
+    let distinct: HashSet<&str> = theorems.iter().map(|t| t.argument_class()).collect();
+    eprintln!("Distinct arguments: {} semantic classes across {} theorems", distinct.len(), theorems.len());
+    let invalid = args.judgment.then(|| theorems.iter().filter(|t| t.valid == Some(false)).count());
+    if let Some(invalid) = invalid {
+        eprintln!("Judgment: {} valid, {} invalid", theorems.len() - invalid, invalid);
+    }
+    let unsatisfiable = args.sat.then(|| theorems.iter().filter(|t| t.satisfiable == Some(false)).count());
+    if let Some(unsatisfiable) = unsatisfiable {
+        eprintln!("Satisfiability: {} satisfiable, {} unsatisfiable", theorems.len() - unsatisfiable, unsatisfiable);
+    }
+    for tier in &rejections {
+        eprintln!("Quality gates, {}: {}", tier.tier, tier.stats.summary());
+    }
+
+    if let Some(path) = &args.summary {
+        let summary = GenerateSummary {
+            output: output.display().to_string(),
+            theorems: theorems.len(),
+            distinct_classes: distinct.len(),
+            seed,
+            quality_gates: (!rejections.is_empty()).then_some(gates),
+            budget: (!budget.is_unlimited()).then_some(budget),
+            invalid,
+            unsatisfiable,
+            tiers: rejections,
+        };
+        let json = serde_json::to_string_pretty(&summary)
+            .map_err(|e| format!("JSON serialization error: {}", e))?;
+        fs::write(path, json)
+            .map_err(|e| format!("Failed to write summary file: {}", e))?;
+        eprintln!("Wrote summary to {}", path.display());
+    }
     Ok(())
 }
 
 // ─── Validate command ───────────────────────────────────────────────────────
 
-fn cmd_validate(theorem_path: &PathBuf, proof_path: &PathBuf) -> Result<(), String> {
-    // Read theorem
-    let theorem_json = fs::read_to_string(theorem_path)
+fn read_bench_theorem(path: &PathBuf) -> Result<BenchTheorem, String> {
+    let theorem_json = fs::read_to_string(path)
         .map_err(|e| format!("Failed to read theorem file: {}", e))?;
-    let bench_theorem: BenchTheorem = serde_json::from_str(&theorem_json)
-        .map_err(|e| format!("Failed to parse theorem JSON: {}", e))?;
-
-    // Parse theorem formulas
-    let premises: Vec<Formula> = bench_theorem.premises.iter()
-        .map(|p| Formula::parse(p).map_err(|e| format!("Invalid premise '{}': {}", p, e)))
-        .collect::<Result<Vec<_>, _>>()?;
-
-    let conclusion = Formula::parse(&bench_theorem.conclusion)
-        .map_err(|e| format!("Invalid conclusion '{}': {}", bench_theorem.conclusion, e))?;
-
-    let difficulty = match bench_theorem.difficulty_value {
-        1..=25 => Difficulty::Easy,
-        26..=45 => Difficulty::Medium,
-        46..=70 => Difficulty::Hard,
-        _ => Difficulty::Expert,
-    };
-
-    let theorem = Theorem::with_difficulty_value(
-        premises,
-        conclusion,
-        difficulty,
-        bench_theorem.difficulty_value,
-        None,
-        None,
-    );
+    serde_json::from_str(&theorem_json)
+        .map_err(|e| format!("Failed to parse theorem JSON: {}", e))
+}
 
-    // Read proof lines
+fn cmd_validate(theorem_path: &PathBuf, proof_path: &PathBuf) -> Result<(), String> {
+    let bench_theorem = read_bench_theorem(theorem_path)?;
     let proof_json = fs::read_to_string(proof_path)
         .map_err(|e| format!("Failed to read proof file: {}", e))?;
     let input_lines: Vec<ValidateInput> = serde_json::from_str(&proof_json)
         .map_err(|e| format!("Failed to parse proof JSON: {}", e))?;
 
-    // Build the proof by replaying each line
-    let mut proof = Proof::new(theorem);
+    let output = check_proof(&bench_theorem, &input_lines)?;
+    let json = serde_json::to_string_pretty(&output)
+        .map_err(|e| format!("JSON serialization error: {}", e))?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Build the proof of `bench_theorem` by replaying `input_lines`, verifying
+/// each line. Lines that do not parse or verify are reported as errors.
+fn replay_proof(bench_theorem: &BenchTheorem, input_lines: &[ValidateInput]) -> Result<(Proof, Vec<String>), String> {
+    let mut proof = Proof::new(bench_theorem.to_theorem()?);
     let mut errors: Vec<String> = Vec::new();
 
-    for input_line in &input_lines {
+    for input_line in input_lines {
         let formula = match Formula::parse(&input_line.formula) {
             Ok(f) => f,
             Err(e) => {
@@ -465,6 +1628,13 @@ fn cmd_validate(theorem_path: &PathBuf, proof_path: &PathBuf) -> Result<(), Stri
             }
         };
 
+        if bench_theorem.task == Some(Task::Equivalence) {
+            if let Some(message) = ProofVerifier::verify_replacement_only(&justification).message {
+                errors.push(format!("Line {}: {}", input_line.line_number, message));
+                continue;
+            }
+        }
+
         // Handle different justification types
         match &justification {
             Justification::Assumption { technique } => {
@@ -500,6 +1670,12 @@ fn cmd_validate(theorem_path: &PathBuf, proof_path: &PathBuf) -> Result<(), Stri
             }
         }
     }
+    Ok((proof, errors))
+}
+
+/// Replay `input_lines` as a proof of `bench_theorem`, verifying every line.
+fn check_proof(bench_theorem: &BenchTheorem, input_lines: &[ValidateInput]) -> Result<ValidateOutput, String> {
+    let (mut proof, mut errors) = replay_proof(bench_theorem, input_lines)?;
 
     // Check completeness
     proof.check_complete();
@@ -541,18 +1717,867 @@ fn cmd_validate(theorem_path: &PathBuf, proof_path: &PathBuf) -> Result<(), Stri
     }
 
     let non_premise_lines = proof.lines.len().saturating_sub(proof.theorem.premises.len());
-    let output = ValidateOutput {
+    let distractors_cited = bench_theorem.distractor_lines().map(|distractors| {
+        let cited: HashSet<usize> = proof.lines.iter()
+            .flat_map(|l| l.justification.referenced_lines())
+            .collect();
+        distractors.into_iter().filter(|line| cited.contains(line)).collect()
+    });
+    Ok(ValidateOutput {
         valid: proof.is_complete && errors.is_empty(),
         line_count: non_premise_lines,
         errors,
+        distractors_cited,
+    })
+}
+
+// ─── Check-answer command ───────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+struct JudgmentAnswer {
+    /// "valid" or "invalid" ("satisfiable" or "unsatisfiable" for a
+    /// satisfiability task)
+    verdict: String,
+    /// Proof lines backing a "valid" verdict (as for validate)
+    #[serde(default)]
+    proof: Option<Vec<ValidateInput>>,
+    /// Assignment backing an "invalid" verdict
+    #[serde(default)]
+    countermodel: Option<BTreeMap<String, bool>>,
+    /// Assignment backing a "satisfiable" verdict
+    #[serde(default)]
+    assignment: Option<BTreeMap<String, bool>>,
+}
+
+#[derive(Debug, Serialize)]
+struct CheckAnswerOutput {
+    /// The verdict is right and its proof or countermodel checks out
+    correct: bool,
+    /// The verdict alone is right (null if validity is unknown)
+    verdict_correct: Option<bool>,
+    /// Whether the conclusion follows: by truth table, or the theorem's
+    /// `valid` label when it has too many atoms for one
+    expected_valid: Option<bool>,
+    errors: Vec<String>,
+    /// Proof check for a "valid" verdict
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proof: Option<ValidateOutput>,
+}
+
+fn cmd_check_answer(theorem_path: &PathBuf, answer_path: &PathBuf) -> Result<(), String> {
+    let bench_theorem = read_bench_theorem(theorem_path)?;
+    let answer_json = fs::read_to_string(answer_path)
+        .map_err(|e| format!("Failed to read answer file: {}", e))?;
+    if bench_theorem.task == Some(Task::TruthTable) {
+        let answer: TableAnswer = serde_json::from_str(&answer_json)
+            .map_err(|e| format!("Failed to parse answer JSON: {}", e))?;
+        let output = check_table_answer(&bench_theorem, &answer)?;
+        let json = serde_json::to_string_pretty(&output)
+            .map_err(|e| format!("JSON serialization error: {}", e))?;
+        println!("{}", json);
+        return Ok(());
+    }
+    let answer: JudgmentAnswer = serde_json::from_str(&answer_json)
+        .map_err(|e| format!("Failed to parse answer JSON: {}", e))?;
+    if bench_theorem.task == Some(Task::Satisfiability) {
+        let output = check_sat_answer(&bench_theorem, &answer)?;
+        let json = serde_json::to_string_pretty(&output)
+            .map_err(|e| format!("JSON serialization error: {}", e))?;
+        println!("{}", json);
+        return Ok(());
+    }
+    let claims_valid = match answer.verdict.trim().to_lowercase().as_str() {
+        "valid" => true,
+        "invalid" => false,
+        other => return Err(format!("Unknown verdict: '{}'. Use valid/invalid.", other)),
+    };
+
+    let (premises, conclusion) = bench_theorem.parse_formulas()?;
+    let atoms: HashSet<String> = premises.iter().chain(std::iter::once(&conclusion)).flat_map(|f| f.atoms()).collect();
+    let expected_valid = if atoms.len() <= MAX_JUDGMENT_ATOMS {
+        Some(entails_dynamic(&premises, &conclusion))
+    } else {
+        bench_theorem.valid
+    };
+
+    let mut errors = Vec::new();
+    let mut proof = None;
+    let backed = if claims_valid {
+        match &answer.proof {
+            Some(lines) => {
+                let output = check_proof(&bench_theorem, lines)?;
+                let valid = output.valid;
+                proof = Some(output);
+                valid
+            }
+            None => {
+                errors.push("A valid verdict needs a proof".to_string());
+                false
+            }
+        }
+    } else {
+        match &answer.countermodel {
+            Some(assignment) => match check_countermodel(&premises, &conclusion, assignment) {
+                Ok(()) => true,
+                Err(e) => {
+                    errors.push(format!("Not a countermodel: {}", e));
+                    false
+                }
+            },
+            None => {
+                errors.push("An invalid verdict needs a countermodel".to_string());
+                false
+            }
+        }
     };
 
+    let output = CheckAnswerOutput {
+        correct: backed,
+        verdict_correct: expected_valid.map(|valid| valid == claims_valid),
+        expected_valid,
+        errors,
+        proof,
+    };
     let json = serde_json::to_string_pretty(&output)
         .map_err(|e| format!("JSON serialization error: {}", e))?;
     println!("{}", json);
     Ok(())
 }
 
+#[derive(Debug, Serialize)]
+struct CheckSatOutput {
+    /// The verdict is right and its assignment or refutation checks out
+    correct: bool,
+    verdict_correct: bool,
+    /// Whether some assignment satisfies the formulas, by truth table
+    expected_satisfiable: bool,
+    errors: Vec<String>,
+    /// Refutation check for an "unsatisfiable" verdict
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proof: Option<ValidateOutput>,
+}
+
+/// Score an answer to a satisfiability task: a "satisfiable" verdict needs
+/// an assignment making every formula true, an "unsatisfiable" one a proof
+/// of ⊥ from the formulas.
+fn check_sat_answer(bench_theorem: &BenchTheorem, answer: &JudgmentAnswer) -> Result<CheckSatOutput, String> {
+    let claims_satisfiable = match answer.verdict.trim().to_lowercase().as_str() {
+        "satisfiable" => true,
+        "unsatisfiable" => false,
+        other => return Err(format!("Unknown verdict: '{}'. Use satisfiable/unsatisfiable.", other)),
+    };
+    let (formulas, _) = bench_theorem.parse_formulas()?;
+    let expected_satisfiable = !entails_dynamic(&formulas, &Formula::Contradiction);
+
+    let mut errors = Vec::new();
+    let mut proof = None;
+    let backed = if claims_satisfiable {
+        match &answer.assignment {
+            Some(assignment) => match check_assignment(&formulas, assignment) {
+                Ok(()) => true,
+                Err(e) => {
+                    errors.push(format!("Not a satisfying assignment: {}", e));
+                    false
+                }
+            },
+            None => {
+                errors.push("A satisfiable verdict needs an assignment".to_string());
+                false
+            }
+        }
+    } else {
+        match &answer.proof {
+            Some(lines) => {
+                let output = check_proof(bench_theorem, lines)?;
+                let valid = output.valid;
+                proof = Some(output);
+                valid
+            }
+            None => {
+                errors.push("An unsatisfiable verdict needs a proof of #".to_string());
+                false
+            }
+        }
+    };
+
+    Ok(CheckSatOutput {
+        correct: backed,
+        verdict_correct: claims_satisfiable == expected_satisfiable,
+        expected_satisfiable,
+        errors,
+        proof,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct TableAnswer {
+    /// The truth table as text: a header line naming the columns, then one
+    /// line per row, cells separated by `|`, tabs or commas
+    table: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CheckTableOutput {
+    /// Every row is there and right
+    correct: bool,
+    /// Rows the expected table has
+    rows: usize,
+    rows_correct: usize,
+    percent_correct: f64,
+    /// First wrong row of the answer (1-based, after the header)
+    first_wrong_row: Option<usize>,
+    errors: Vec<String>,
+}
+
+/// Grade an answer to a truth-table task row by row. A header that does not
+/// name the expected columns fails the whole table.
+fn check_table_answer(bench_theorem: &BenchTheorem, answer: &TableAnswer) -> Result<CheckTableOutput, String> {
+    let (premises, conclusion) = bench_theorem.parse_formulas()?;
+    let rows = TableColumns::new(&premises, &conclusion).map_err(|e| e.to_string())?.row_count();
+    Ok(match grade_truth_table(&premises, &conclusion, &answer.table) {
+        Ok(grade) => CheckTableOutput {
+            correct: grade.is_correct(),
+            rows,
+            rows_correct: grade.rows_correct,
+            percent_correct: grade.percent_correct(),
+            first_wrong_row: grade.first_wrong_row,
+            errors: grade.errors,
+        },
+        Err(e) => CheckTableOutput {
+            correct: false,
+            rows,
+            rows_correct: 0,
+            percent_correct: 0.0,
+            first_wrong_row: None,
+            errors: vec![e.to_string()],
+        },
+    })
+}
+
+// ─── Analyze command ────────────────────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+struct AnalyzeRow {
+    id: String,
+    difficulty: String,
+    #[serde(flatten)]
+    metrics: TheoremMetrics,
+}
+
+const ANALYZE_CSV_HEADER: &str = "id,difficulty,node_count,depth,atom_count,\
+not,and,or,implies,biconditional,contradiction,negation_depth,ascii_length,\
+estimated_tokens,applicable_rewrites,min_proof_steps,forces_cp,forces_case_split,forces_ip";
+
+fn csv_optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn analyze_csv_line(row: &AnalyzeRow) -> String {
+    let m = &row.metrics;
+    let c = &m.connectives;
+    [
+        row.id.clone(),
+        row.difficulty.clone(),
+        m.node_count.to_string(),
+        m.depth.to_string(),
+        m.atom_count.to_string(),
+        c.not.to_string(),
+        c.and.to_string(),
+        c.or.to_string(),
+        c.implies.to_string(),
+        c.biconditional.to_string(),
+        c.contradiction.to_string(),
+        m.negation_depth.to_string(),
+        m.ascii_length.to_string(),
+        m.estimated_tokens.to_string(),
+        m.applicable_rewrites.to_string(),
+        csv_optional(m.min_proof_steps),
+        csv_optional(m.forces_cp),
+        csv_optional(m.forces_case_split),
+        csv_optional(m.forces_ip),
+    ].join(",")
+}
+
+/// Read a theorem set written by generate: a JSON array or JSONL.
+fn read_theorem_set(path: &PathBuf) -> Result<Vec<BenchTheorem>, String> {
+    let json = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read theorem set: {}", e))?;
+    if json.trim_start().starts_with('[') {
+        return serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse theorem set JSON: {}", e));
+    }
+    json.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| serde_json::from_str(line)
+            .map_err(|e| format!("Failed to parse theorem set JSONL line {}: {}", number + 1, e)))
+        .collect()
+}
+
+fn cmd_analyze(input: &PathBuf, format: &str, output: &Option<PathBuf>) -> Result<(), String> {
+    let theorems = read_theorem_set(input)?;
+    eprintln!("Analyzing {} theorems...", theorems.len());
+
+    let mut rows = Vec::with_capacity(theorems.len());
+    for bench in &theorems {
+        let (premises, conclusion) = bench.parse_formulas()
+            .map_err(|e| format!("{}: {}", bench.id, e))?;
+        rows.push(AnalyzeRow {
+            id: bench.id.clone(),
+            difficulty: bench.difficulty.clone(),
+            metrics: TheoremMetrics::compute(&premises, &conclusion),
+        });
+    }
+
+    let text = match format {
+        "json" => serde_json::to_string_pretty(&rows)
+            .map_err(|e| format!("JSON serialization error: {}", e))?,
+        "csv" => {
+            let mut lines = vec![ANALYZE_CSV_HEADER.to_string()];
+            lines.extend(rows.iter().map(analyze_csv_line));
+            lines.join("\n") + "\n"
+        }
+        other => return Err(format!("Unknown format: '{}'. Use json/csv.", other)),
+    };
+
+    match output {
+        Some(path) => {
+            fs::write(path, &text)
+                .map_err(|e| format!("Failed to write output file: {}", e))?;
+            eprintln!("Wrote metrics for {} theorems to {}", rows.len(), path.display());
+        }
+        None => println!("{}", text.trim_end()),
+    }
+    Ok(())
+}
+
+// ─── Calibrate command ──────────────────────────────────────────────────────
+
+/// Adjacent tiers whose node counts overlap at least this much are flagged.
+/// 0.35 means the harder tier is no bigger than the easier one over a third
+/// of the time.
+const OVERLAP_WARN_THRESHOLD: f64 = 0.35;
+
+#[derive(Debug, Serialize)]
+struct Percentiles {
+    p10: f64,
+    p50: f64,
+    p90: f64,
+    max: f64,
+}
+
+impl Percentiles {
+    /// Nearest-rank percentiles of `values` (which need not be sorted).
+    fn of(values: &[f64]) -> Self {
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let rank = |p: f64| {
+            if sorted.is_empty() {
+                return 0.0;
+            }
+            let idx = ((p * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len()) - 1;
+            sorted[idx]
+        };
+        Percentiles { p10: rank(0.1), p50: rank(0.5), p90: rank(0.9), max: rank(1.0) }
+    }
+
+    fn summary(&self, precision: usize) -> String {
+        format!("{:.*}/{:.*}/{:.*}/{:.*}",
+            precision, self.p10, precision, self.p50, precision, self.p90, precision, self.max)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct TierCalibration {
+    tier: String,
+    samples: usize,
+    spec: DifficultySpec,
+    node_count: Percentiles,
+    depth: Percentiles,
+    atoms: Percentiles,
+    generation_ms: Percentiles,
+    check_ms: Percentiles,
+    #[serde(skip)]
+    raw_node_counts: Vec<f64>,
+}
+
+#[derive(Debug, Serialize)]
+struct TierOverlap {
+    easier: String,
+    harder: String,
+    /// Probability that a sample of the harder tier has no more nodes than a
+    /// sample of the easier one (ties count half)
+    overlap: f64,
+    flagged: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct CalibrationReport {
+    tiers: Vec<TierCalibration>,
+    overlaps: Vec<TierOverlap>,
+}
+
+/// Sample `samples` theorems from `spec` through the same quality gates as
+/// `generate`; rejections are counted in `stats`.
+fn calibrate_spec(
+    label: &str,
+    spec: &DifficultySpec,
+    samples: usize,
+    gates: &QualityGates,
+    stats: &mut RejectionStats,
+    rng: &mut impl Rng,
+) -> Result<TierCalibration, String> {
+    let mut nodes = Vec::with_capacity(samples);
+    let mut depths = Vec::with_capacity(samples);
+    let mut atoms = Vec::with_capacity(samples);
+    let mut gen_ms = Vec::with_capacity(samples);
+    let mut check_ms = Vec::with_capacity(samples);
+
+    for _ in 0..samples {
+        let start = Instant::now();
+        let theorem = ObfuscateGenerator::generate_gated(spec, None, false, gates, &GenerationBudget::default(), stats, rng)
+            .map_err(|e| format!("{}: {}. Relax the --gate-* limits or raise --max-retries.", label, e))?;
+        gen_ms.push(start.elapsed().as_secs_f64() * 1000.0);
+
+        let start = Instant::now();
+        is_tautology_dynamic(&theorem.conclusion);
+        check_ms.push(start.elapsed().as_secs_f64() * 1000.0);
+
+        nodes.push(theorem.conclusion.node_count() as f64);
+        depths.push(theorem.conclusion.depth() as f64);
+        atoms.push(theorem.conclusion.atoms().len() as f64);
+    }
+
+    Ok(TierCalibration {
+        tier: label.to_string(),
+        samples,
+        spec: spec.clone(),
+        node_count: Percentiles::of(&nodes),
+        depth: Percentiles::of(&depths),
+        atoms: Percentiles::of(&atoms),
+        generation_ms: Percentiles::of(&gen_ms),
+        check_ms: Percentiles::of(&check_ms),
+        raw_node_counts: nodes,
+    })
+}
+
+/// P(harder <= easier) over all sample pairs, ties counting half.
+fn overlap_fraction(easier: &[f64], harder: &[f64]) -> f64 {
+    if easier.is_empty() || harder.is_empty() {
+        return 0.0;
+    }
+    let mut score = 0.0;
+    for h in harder {
+        for e in easier {
+            if h < e {
+                score += 1.0;
+            } else if h == e {
+                score += 0.5;
+            }
+        }
+    }
+    score / (easier.len() * harder.len()) as f64
+}
+
+fn cmd_calibrate(
+    samples: usize,
+    tiers: &Option<String>,
+    spec_args: &SpecArgs,
+    quality: &QualityArgs,
+    format: &str,
+    seed: Option<u64>,
+) -> Result<(), String> {
+    if format != "table" && format != "json" {
+        return Err(format!("Unknown format: '{}'. Use table/json.", format));
+    }
+    let gates = quality.gates()?;
+
+    let targets: Vec<(String, DifficultySpec)> = if let Some(spec) = spec_args.custom_spec() {
+        if tiers.is_some() {
+            return Err("--tiers cannot be combined with custom spec flags".to_string());
+        }
+        vec![("Custom".to_string(), spec)]
+    } else {
+        let presets = spec_args.tier_presets()?;
+        let tier_list: Vec<TierPreset> = match tiers {
+            Some(list) => list.split(',').map(|t| parse_tier(t, &presets)).collect::<Result<_, _>>()?,
+            None => presets.all().to_vec(),
+        };
+        tier_list.into_iter().map(|mut preset| {
+            spec_args.apply_overrides(&mut preset.spec);
+            (preset.label().to_string(), preset.spec)
+        }).collect()
+    };
+
+    let seed = seed.unwrap_or_else(rand::random);
+    eprintln!("Seed {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut report = CalibrationReport { tiers: Vec::new(), overlaps: Vec::new() };
+    for (label, spec) in &targets {
+        eprintln!("Sampling {} {} theorems...", samples, label);
+        let mut stats = RejectionStats::default();
+        report.tiers.push(calibrate_spec(label, spec, samples, &gates, &mut stats, &mut rng)?);
+        eprintln!("Quality gates, {}: {}", label, stats.summary());
+    }
+
+    for pair in report.tiers.windows(2) {
+        let overlap = overlap_fraction(&pair[0].raw_node_counts, &pair[1].raw_node_counts);
+        report.overlaps.push(TierOverlap {
+            easier: pair[0].tier.clone(),
+            harder: pair[1].tier.clone(),
+            overlap,
+            flagged: overlap >= OVERLAP_WARN_THRESHOLD,
+        });
+    }
+
+    if format == "json" {
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| format!("JSON serialization error: {}", e))?;
+        println!("{}", json);
+        return Ok(());
+    }
+
+    println!("Percentiles shown as p10/p50/p90/max over {} samples per tier", samples);
+    println!("{:<10} {:<24} {:<16} {:<16} {:<28} check ms", "tier", "nodes", "depth", "atoms", "gen ms");
+    for t in &report.tiers {
+        println!("{:<10} {:<24} {:<16} {:<16} {:<28} {}",
+            t.tier, t.node_count.summary(0), t.depth.summary(0), t.atoms.summary(0),
+            t.generation_ms.summary(2), t.check_ms.summary(3));
+    }
+    for o in report.overlaps.iter().filter(|o| o.flagged) {
+        println!("Overlap: {} vs {} — {} is no larger {:.0}% of the time",
+            o.easier, o.harder, o.harder, o.overlap * 100.0);
+    }
+    Ok(())
+}
+
+// ─── Render command ─────────────────────────────────────────────────────────
+
+/// Output formats of the render command
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RenderFormat {
+    /// Sequent, bussproofs tree and Fitch diagram
+    Latex,
+    /// One Graphviz digraph per theorem
+    Dot(DotGraph),
+    /// Lean 4 file: the rule library, then one theorem per theorem
+    Lean,
+}
+
+/// What a DOT rendering draws
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DotGraph {
+    Ast { share: bool },
+    Tree,
+    Proof,
+}
+
+impl RenderFormat {
+    fn parse(name: &str, graph: &str, share: bool) -> Result<Self, String> {
+        match name {
+            "latex" => Ok(RenderFormat::Latex),
+            "dot" => Ok(RenderFormat::Dot(match graph {
+                "ast" => DotGraph::Ast { share },
+                "tree" => DotGraph::Tree,
+                "proof" => DotGraph::Proof,
+                other => return Err(format!("Unknown graph: '{}'. Use ast/tree/proof.", other)),
+            })),
+            "lean" => Ok(RenderFormat::Lean),
+            other => Err(format!("Unknown format: '{}'. Use latex/dot/lean.", other)),
+        }
+    }
+
+    /// Opening lines of the output
+    fn header(self) -> String {
+        match self {
+            RenderFormat::Latex => format!("% Needs \\usepackage{{{}}}", LATEX_PACKAGES),
+            RenderFormat::Dot(_) => "// One graph per theorem; draw with dot -Tsvg -O".to_string(),
+            RenderFormat::Lean => format!("-- Check with: lean <file>.lean\n\n{}", LEAN_PRELUDE),
+        }
+    }
+}
+
+/// Read one theorem (as for validate) or a theorem set (as for analyze).
+fn read_theorems(path: &PathBuf) -> Result<Vec<BenchTheorem>, String> {
+    let json = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read theorem file: {}", e))?;
+    match serde_json::from_str(&json) {
+        Ok(theorem) => Ok(vec![theorem]),
+        Err(_) => read_theorem_set(path),
+    }
+}
+
+/// The proof to render for `bench`: `lines` replayed and verified, else the
+/// solution linearised and verified, else none.
+fn proof_to_render(bench: &BenchTheorem, lines: Option<&[ValidateInput]>) -> Result<Option<Proof>, String> {
+    if let Some(lines) = lines {
+        let (mut proof, errors) = replay_proof(bench, lines)?;
+        proof.check_complete();
+        if !proof.is_complete || !errors.is_empty() {
+            eprintln!("Warning: the proof of {} does not verify; invalid lines are marked", bench.id);
+        }
+        return Ok(Some(proof));
+    }
+    let Some(solution) = &bench.solution else {
+        return Ok(None);
+    };
+    let mut proof = solution.to_proof(bench.to_theorem()?)
+        .map_err(|e| format!("{}: cannot linearise the solution: {}", bench.id, e))?;
+    if bench.task == Some(Task::Equivalence) {
+        ProofVerifier::verify_equivalence_proof(&mut proof);
+    } else {
+        ProofVerifier::verify_proof(&mut proof);
+    }
+    Ok(Some(proof))
+}
+
+/// One theorem in LaTeX: the sequent, the solution tree in bussproofs and
+/// the proof as a Fitch diagram.
+fn render_latex(bench: &BenchTheorem, proof: Option<&Proof>) -> Result<String, String> {
+    let (premises, conclusion) = bench.parse_formulas()?;
+    let mut parts = vec![
+        format!("% {} ({})", bench.id, bench.difficulty),
+        theorem_to_latex(&premises, &conclusion),
+    ];
+    if let Some(solution) = &bench.solution {
+        parts.push(proof_tree_to_bussproofs(solution).map_err(|e| format!("{}: {}", bench.id, e))?);
+    }
+    if let Some(proof) = proof {
+        parts.push(proof_to_fitch(proof));
+    }
+    Ok(parts.join("\n\n"))
+}
+
+/// One theorem as a DOT digraph, or a comment when there is nothing to draw.
+fn render_dot(bench: &BenchTheorem, proof: Option<&Proof>, graph: DotGraph) -> Result<String, String> {
+    let heading = format!("// {} ({})", bench.id, bench.difficulty);
+    let body = match graph {
+        DotGraph::Ast { share } => {
+            let (premises, conclusion) = bench.parse_formulas()?;
+            let mut labelled: Vec<(String, &Formula)> = premises.iter().enumerate()
+                .map(|(i, p)| (format!("Premise {}", i + 1), p))
+                .collect();
+            labelled.push(("Conclusion".to_string(), &conclusion));
+            formulas_to_dot(&labelled, share)
+        }
+        DotGraph::Tree => match &bench.solution {
+            Some(solution) => proof_tree_to_dot(solution),
+            None => "// no solution to draw".to_string(),
+        },
+        DotGraph::Proof => match proof {
+            Some(proof) => proof_to_dot(proof),
+            None => "// no proof to draw; pass --proof".to_string(),
+        },
+    };
+    Ok(format!("{}\n{}", heading, body))
+}
+
+/// One theorem as a Lean theorem: proved by the proof when it translates,
+/// otherwise left as `sorry`.
+fn render_lean(bench: &BenchTheorem, proof: Option<&Proof>) -> Result<String, String> {
+    let heading = format!("-- {} ({})", bench.id, bench.difficulty);
+    let name = lean_theorem_name(&bench.id);
+    let translated = match proof {
+        Some(proof) => proof_to_lean(&name, proof).map_err(|e| {
+            eprintln!("Warning: the proof of {} is not translated: {}", bench.id, e);
+            format!("-- proof not translated: {}", e)
+        }),
+        None => Err("-- no proof; pass --proof".to_string()),
+    };
+    let body = match translated {
+        Ok(text) => text,
+        Err(note) => format!("{}\n{}", note, theorem_to_lean(&name, &bench.to_theorem()?)),
+    };
+    Ok(format!("{}\n{}", heading, body))
+}
+
+fn cmd_render(
+    input: &PathBuf,
+    proof_path: &Option<PathBuf>,
+    format: &str,
+    graph: &str,
+    share: bool,
+    output: &Option<PathBuf>,
+) -> Result<(), String> {
+    let format = RenderFormat::parse(format, graph, share)?;
+    let theorems = read_theorems(input)?;
+    let lines: Option<Vec<ValidateInput>> = match proof_path {
+        Some(path) => {
+            if theorems.len() != 1 {
+                return Err(format!("--proof needs a single theorem, but the input has {}", theorems.len()));
+            }
+            let json = fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read proof file: {}", e))?;
+            Some(serde_json::from_str(&json).map_err(|e| format!("Failed to parse proof JSON: {}", e))?)
+        }
+        None => None,
+    };
+
+    let mut sections = Vec::with_capacity(theorems.len());
+    for bench in &theorems {
+        let proof = proof_to_render(bench, lines.as_deref())?;
+        let section = match format {
+            RenderFormat::Latex => render_latex(bench, proof.as_ref())?,
+            RenderFormat::Dot(graph) => render_dot(bench, proof.as_ref(), graph)?,
+            RenderFormat::Lean => render_lean(bench, proof.as_ref())?,
+        };
+        sections.push(section);
+    }
+    let text = std::iter::once(format.header()).chain(sections).collect::<Vec<_>>().join("\n\n") + "\n";
+
+    match output {
+        Some(path) => {
+            fs::write(path, &text)
+                .map_err(|e| format!("Failed to write output file: {}", e))?;
+            eprintln!("Rendered {} theorems to {}", theorems.len(), path.display());
+        }
+        None => println!("{}", text.trim_end()),
+    }
+    Ok(())
+}
+
+// ─── Export command ─────────────────────────────────────────────────────────
+
+/// Write each theorem as `<id>.p` (TPTP) or `<id>.cnf` (DIMACS) in `output`,
+/// with its atom names in `<id>.map.json`.
+fn cmd_export(input: &PathBuf, format: &str, output: &PathBuf) -> Result<(), String> {
+    if format != "tptp" && format != "dimacs" {
+        return Err(format!("Unknown format: '{}'. Use tptp/dimacs.", format));
+    }
+    let theorems = read_theorems(input)?;
+    fs::create_dir_all(output)
+        .map_err(|e| format!("Failed to create output directory: {}", e))?;
+
+    for bench in &theorems {
+        let mut theorem = bench.to_theorem()?;
+        theorem.id = bench.id.clone();
+        let valid = bench.expected_valid();
+        let (extension, text, atoms) = if format == "tptp" {
+            let problem = theorem_to_tptp(&theorem, Some(valid));
+            ("p", problem.text, serde_json::to_string_pretty(&problem.atoms))
+        } else {
+            let cnf = negated_conjecture_cnf(&theorem);
+            let mut comments = vec![
+                format!("{}: premises and negated conclusion, Tseitin-encoded", bench.id),
+                format!("expected: {}", if valid { "UNSAT (valid)" } else { "SAT (invalid)" }),
+            ];
+            comments.extend(cnf.atoms.iter().map(|(atom, var)| format!("atom {} {}", var, atom)));
+            ("cnf", cnf.to_dimacs(&comments), serde_json::to_string_pretty(&cnf.atoms))
+        };
+        let atoms = atoms.map_err(|e| format!("Failed to serialize atom map: {}", e))?;
+        let stem = bench.id.replace(['/', '\\'], "_");
+        fs::write(output.join(format!("{}.{}", stem, extension)), text)
+            .map_err(|e| format!("Failed to write {}: {}", bench.id, e))?;
+        fs::write(output.join(format!("{}.map.json", stem)), atoms + "\n")
+            .map_err(|e| format!("Failed to write atom map of {}: {}", bench.id, e))?;
+    }
+    eprintln!("Exported {} theorems to {}", theorems.len(), output.display());
+    Ok(())
+}
+
+// ─── Import command ─────────────────────────────────────────────────────────
+
+/// Files to import from `input`: the file itself, or a directory's problem
+/// files in name order.
+fn import_files(input: &PathBuf) -> Result<Vec<PathBuf>, String> {
+    if !input.is_dir() {
+        return Ok(vec![input.clone()]);
+    }
+    let entries = fs::read_dir(input)
+        .map_err(|e| format!("Failed to read input directory: {}", e))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|e| format!("Failed to read input directory: {}", e))?.path();
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if path.is_file() && matches!(extension, "p" | "tptp" | "txt") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Parse `path` as `format`, or by its extension. Problems get the file
+/// stem as id, numbered when the file holds several.
+fn read_problems(path: &PathBuf, format: Option<&str>) -> Result<Vec<(String, ImportedProblem)>, String> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let tptp = match format {
+        Some("tptp") => true,
+        Some("text") => false,
+        Some(other) => return Err(format!("Unknown format: '{}'. Use tptp/text.", other)),
+        None => matches!(extension, "p" | "tptp"),
+    };
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let problems = if tptp {
+        parse_tptp_problem(&text).map(|problem| vec![problem])
+    } else {
+        parse_text_problems(&text)
+    };
+    let problems = problems.map_err(|e| format!("{}: {}", path.display(), e))?;
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("problem");
+    let single = problems.len() == 1;
+    Ok(problems.into_iter()
+        .enumerate()
+        .map(|(i, problem)| (if single { stem.to_string() } else { format!("{}-{}", stem, i + 1) }, problem))
+        .collect())
+}
+
+/// Import every problem under `input` that passes the degeneracy checks
+/// (or, with `keep_degenerate`, is at least valid) and write them as a
+/// theorem set. Rejected problems are reported with their reason.
+fn cmd_import(
+    input: &PathBuf,
+    format: Option<&str>,
+    output: &PathBuf,
+    keep_degenerate: bool,
+    difficulty_value: u8,
+) -> Result<(), String> {
+    let mut theorems = Vec::new();
+    let mut rejected: BTreeMap<String, usize> = BTreeMap::new();
+    let mut total = 0;
+    for path in import_files(input)? {
+        for (id, problem) in read_problems(&path, format)? {
+            total += 1;
+            let source = format!("{}:{}", path.display(), problem.line);
+            if let Err(rejection) = validate_imported(&problem.premises, &problem.conclusion) {
+                let keep = keep_degenerate && matches!(
+                    &rejection,
+                    ImportRejection::Degenerate(e) if !matches!(e, DegenerateProofError::InvalidTheorem)
+                );
+                if !keep {
+                    eprintln!("{} ({}): {}", id, source, rejection);
+                    *rejected.entry(rejection.to_string()).or_default() += 1;
+                    continue;
+                }
+                eprintln!("{} ({}): {} (kept)", id, source, rejection);
+            }
+            let mut theorem = Theorem::with_difficulty_value(
+                problem.premises,
+                problem.conclusion,
+                legacy_difficulty(difficulty_value),
+                difficulty_value,
+                None,
+                None,
+            );
+            theorem.id = id;
+            let mut bench = BenchTheorem::from(&theorem);
+            bench.source = Some(source);
+            theorems.push(bench);
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&theorems)
+        .map_err(|e| format!("JSON serialization error: {}", e))?;
+    fs::write(output, &json)
+        .map_err(|e| format!("Failed to write output file: {}", e))?;
+    eprintln!("Imported {} of {} problems to {}", theorems.len(), total, output.display());
+    for (reason, count) in &rejected {
+        eprintln!("  rejected {}: {}", count, reason);
+    }
+    Ok(())
+}
+
 // ─── Justification parsing ──────────────────────────────────────────────────
 
 fn parse_justification(s: &str) -> Result<Justification, String> {
@@ -687,48 +2712,28 @@ fn main() {
     let cli = Cli::parse();
 
     let result = match cli.command {
-        Commands::Generate {
-            count,
-            difficulty_distribution,
-            tier,
-            variables,
-            passes,
-            transforms,
-            base,
-            substitution,
-            bridge_atoms,
-            max_nodes,
-            max_depth,
-            no_gnarly_combos,
-            gnarly_combos,
-            output,
-        } => {
-            let gnarly_override = if gnarly_combos {
-                Some(true)
-            } else if no_gnarly_combos {
-                Some(false)
-            } else {
-                None
-            };
-            cmd_generate(
-                count,
-                &difficulty_distribution,
-                &tier,
-                &variables,
-                &passes,
-                &transforms,
-                &base,
-                &substitution,
-                &bridge_atoms,
-                &max_nodes,
-                &max_depth,
-                gnarly_override,
-                &output,
-            )
-        }
+        Commands::Generate(args) => cmd_generate(&args),
         Commands::Validate { theorem, proof } => {
             cmd_validate(&theorem, &proof)
         }
+        Commands::CheckAnswer { theorem, answer } => {
+            cmd_check_answer(&theorem, &answer)
+        }
+        Commands::Analyze { input, format, output } => {
+            cmd_analyze(&input, &format, &output)
+        }
+        Commands::Calibrate { samples, tiers, spec, quality, format, seed } => {
+            cmd_calibrate(samples, &tiers, &spec, &quality, &format, seed)
+        }
+        Commands::Render { input, proof, format, graph, share, output } => {
+            cmd_render(&input, &proof, &format, &graph, share, &output)
+        }
+        Commands::Export { input, format, output } => {
+            cmd_export(&input, &format, &output)
+        }
+        Commands::Import { input, format, output, keep_degenerate, difficulty_value } => {
+            cmd_import(&input, format.as_deref(), &output, keep_degenerate, difficulty_value)
+        }
     };
 
     if let Err(e) = result {
@@ -736,3 +2741,94 @@ fn main() {
         std::process::exit(1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh path in the temp directory for one test's output.
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("propbench-{}-{}", std::process::id(), name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    /// Run `generate` with `args` (plus --output) and return the file it
+    /// wrote, which is then removed.
+    fn generate(output: &PathBuf, args: &[&str]) -> String {
+        let cli = Cli::try_parse_from(
+            ["propbench", "generate", "--output", output.to_str().unwrap()].iter().chain(args),
+        ).unwrap();
+        let Commands::Generate(args) = cli.command else { unreachable!() };
+        cmd_generate(&args).unwrap();
+        let text = fs::read_to_string(output).unwrap();
+        fs::remove_file(output).unwrap();
+        text
+    }
+
+    #[test]
+    fn test_calibrate_rejects_tiers_with_custom_spec() {
+        let cli = Cli::try_parse_from(["propbench", "calibrate", "--tiers", "easy", "--passes", "3"]).unwrap();
+        let Commands::Calibrate { samples, tiers, spec, quality, format, seed } = cli.command else { unreachable!() };
+        let err = cmd_calibrate(samples, &tiers, &spec, &quality, &format, seed).unwrap_err();
+        assert!(err.contains("--tiers"), "{}", err);
+    }
+
+    #[test]
+    fn test_generate_jobs_do_not_change_output() {
+        let args = ["--count", "6", "--seed", "7", "--difficulty-distribution", "3:baby,3:easy"];
+        let one = generate(&temp_path("jobs-1.json"), &[&args[..], &["--jobs", "1"]].concat());
+        let four = generate(&temp_path("jobs-4.json"), &[&args[..], &["--jobs", "4"]].concat());
+        assert_eq!(one, four);
+        assert_eq!(serde_json::from_str::<Vec<BenchTheorem>>(&one).unwrap().len(), 6);
+
+        // Duplicate proof trees are dropped in job order, whichever thread finishes first
+        let args = ["--count", "6", "--seed", "7", "--proof-tree", "--tree-difficulty", "30"];
+        let one = generate(&temp_path("jobs-tree-1.json"), &[&args[..], &["--jobs", "1"]].concat());
+        let four = generate(&temp_path("jobs-tree-4.json"), &[&args[..], &["--jobs", "4"]].concat());
+        assert_eq!(one, four);
+    }
+
+    /// A JSONL run cut off after `keep` bytes and resumed must match the
+    /// uninterrupted run.
+    fn assert_resume_matches(name: &str, keep: impl Fn(&str) -> usize) {
+        let args = ["--count", "6", "--seed", "11", "--tier", "easy"];
+        let full = generate(&temp_path(&format!("{}-full.jsonl", name)), &args);
+        let path = temp_path(&format!("{}.jsonl", name));
+        fs::write(&path, &full[..keep(&full)]).unwrap();
+        let resumed = generate(&path, &[&args[..], &["--resume"]].concat());
+        assert_eq!(resumed, full);
+    }
+
+    #[test]
+    fn test_resume_after_complete_lines() {
+        // Cut after the third theorem
+        assert_resume_matches("resume-lines", |full| {
+            full.match_indices('\n').nth(2).unwrap().0 + 1
+        });
+    }
+
+    #[test]
+    fn test_resume_drops_partial_line() {
+        // Cut halfway through the fourth theorem
+        assert_resume_matches("resume-partial", |full| {
+            let start = full.match_indices('\n').nth(2).unwrap().0 + 1;
+            let end = full[start..].find('\n').unwrap() + start;
+            (start + end) / 2
+        });
+    }
+
+    #[test]
+    fn test_resume_rejects_other_seed() {
+        let path = temp_path("resume-seed.jsonl");
+        let first = generate(&path, &["--count", "1", "--seed", "11", "--tier", "easy"]);
+        fs::write(&path, first).unwrap();
+        let cli = Cli::try_parse_from([
+            "propbench", "generate", "--output", path.to_str().unwrap(),
+            "--count", "2", "--seed", "12", "--tier", "easy", "--resume",
+        ]).unwrap();
+        let Commands::Generate(args) = cli.command else { unreachable!() };
+        assert!(cmd_generate(&args).unwrap_err().contains("seed 11"));
+        fs::remove_file(&path).unwrap();
+    }
+}